@@ -0,0 +1,54 @@
+use clap::Parser;
+
+use crate::config::Config;
+
+/// A beautiful terminal music visualizer with album art and lyrics
+#[derive(Debug, Default, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Only follow this player (e.g. spotify, firefox, mpv)
+    #[arg(long, value_name = "NAME")]
+    pub player: Option<String>,
+}
+
+impl Cli {
+    /// Command line options win over the environment
+    pub fn apply(self, config: &mut Config) {
+        if let Some(player) = self.player {
+            config.player = Some(player);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_player() {
+        let cli = Cli::try_parse_from(["trackwatch", "--player", "spotify"]).unwrap();
+        assert_eq!(cli.player, Some("spotify".to_string()));
+
+        let cli = Cli::try_parse_from(["trackwatch"]).unwrap();
+        assert!(cli.player.is_none());
+
+        assert!(Cli::try_parse_from(["trackwatch", "--player"]).is_err());
+    }
+
+    #[test]
+    fn test_apply_overrides_environment() {
+        let mut config = Config {
+            player: Some("mpv".to_string()),
+            ..Default::default()
+        };
+
+        Cli::default().apply(&mut config);
+        assert_eq!(config.player, Some("mpv".to_string()));
+
+        Cli {
+            player: Some("spotify".to_string()),
+        }
+        .apply(&mut config);
+        assert_eq!(config.player, Some("spotify".to_string()));
+    }
+}
@@ -1,17 +1,78 @@
-#[derive(Debug, Clone)]
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default)]
 pub struct Config {
     pub tidal_client_id: Option<String>,
     pub tidal_client_secret: Option<String>,
+    pub player_backend: Option<String>, // "auto", "mpris", "playerctl", "mpd", "mpv" or "cmus"
+    pub poll_only: bool,                // Ignore player events and poll every 500ms
+    pub player: Option<String>,         // Only ever follow this player
+    pub player_priority: Vec<String>,   // Preferred players, most wanted first
+    pub mpd_host: Option<String>,       // [password@]host or socket path
+    pub mpd_port: Option<u16>,
+    pub mpv_socket: Option<String>, // mpv's --input-ipc-server path
+    pub cmus_socket: Option<String>,
+    pub lyrics_dirs: Vec<PathBuf>, // Folders with "Artist - Title.lrc" files
+    pub lyrics_sources: Vec<String>, // Lyrics sources to ask, in order
+    pub romanize: bool,            // Show kana and hangul in Latin letters too
 }
 
 impl Config {
     pub fn from_env() -> Self {
         let tidal_client_id = std::env::var("TIDAL_CLIENT_ID").ok();
         let tidal_client_secret = std::env::var("TIDAL_CLIENT_SECRET").ok();
+        let player_backend = std::env::var("TRACKWATCH_PLAYER_BACKEND").ok();
+        let poll_only = std::env::var("TRACKWATCH_POLL_ONLY")
+            .map(|value| is_truthy(&value))
+            .unwrap_or(false);
+        let player = std::env::var("TRACKWATCH_PLAYER")
+            .ok()
+            .filter(|name| !name.trim().is_empty());
+        let player_priority = std::env::var("TRACKWATCH_PLAYER_PRIORITY")
+            .map(|value| parse_list(&value))
+            .unwrap_or_default();
+        let mpd_host = std::env::var("MPD_HOST")
+            .ok()
+            .filter(|host| !host.trim().is_empty());
+        let mpd_port = std::env::var("MPD_PORT")
+            .ok()
+            .and_then(|port| port.trim().parse().ok());
+        let mpv_socket = std::env::var("TRACKWATCH_MPV_SOCKET")
+            .ok()
+            .filter(|socket| !socket.trim().is_empty());
+        // Same variable cmus and cmus-remote use
+        let cmus_socket = std::env::var("CMUS_SOCKET")
+            .ok()
+            .filter(|socket| !socket.trim().is_empty());
+        // Separated like PATH
+        let lyrics_dirs = std::env::var_os("TRACKWATCH_LYRICS_DIRS")
+            .map(|value| {
+                std::env::split_paths(&value)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let lyrics_sources = std::env::var("TRACKWATCH_LYRICS_SOURCES")
+            .map(|value| parse_list(&value))
+            .unwrap_or_default();
+        let romanize = std::env::var("TRACKWATCH_ROMANIZE")
+            .map(|value| is_truthy(&value))
+            .unwrap_or(false);
 
         Self {
             tidal_client_id,
             tidal_client_secret,
+            player_backend,
+            poll_only,
+            player,
+            player_priority,
+            mpd_host,
+            mpd_port,
+            mpv_socket,
+            cmus_socket,
+            lyrics_dirs,
+            lyrics_sources,
+            romanize,
         }
     }
 
@@ -20,6 +81,22 @@ impl Config {
     }
 }
 
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,11 +158,166 @@ mod tests {
         std::env::remove_var("TIDAL_CLIENT_ID");
     }
 
+    #[test]
+    fn test_from_env_player_backend() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_PLAYER_BACKEND");
+        assert!(Config::from_env().player_backend.is_none());
+
+        std::env::set_var("TRACKWATCH_PLAYER_BACKEND", "playerctl");
+        assert_eq!(
+            Config::from_env().player_backend,
+            Some("playerctl".to_string())
+        );
+
+        // Clean up
+        std::env::remove_var("TRACKWATCH_PLAYER_BACKEND");
+    }
+
+    #[test]
+    fn test_from_env_romanize() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_ROMANIZE");
+        assert!(!Config::from_env().romanize);
+
+        std::env::set_var("TRACKWATCH_ROMANIZE", "yes");
+        assert!(Config::from_env().romanize);
+
+        std::env::remove_var("TRACKWATCH_ROMANIZE");
+    }
+
+    #[test]
+    fn test_from_env_poll_only() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_POLL_ONLY");
+        assert!(!Config::from_env().poll_only);
+
+        std::env::set_var("TRACKWATCH_POLL_ONLY", "1");
+        assert!(Config::from_env().poll_only);
+
+        std::env::set_var("TRACKWATCH_POLL_ONLY", "false");
+        assert!(!Config::from_env().poll_only);
+
+        // Clean up
+        std::env::remove_var("TRACKWATCH_POLL_ONLY");
+    }
+
+    #[test]
+    fn test_from_env_player_selection() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_PLAYER");
+        std::env::remove_var("TRACKWATCH_PLAYER_PRIORITY");
+        let config = Config::from_env();
+        assert!(config.player.is_none());
+        assert!(config.player_priority.is_empty());
+
+        std::env::set_var("TRACKWATCH_PLAYER", "spotify");
+        std::env::set_var("TRACKWATCH_PLAYER_PRIORITY", "mpv, spotify,,firefox ");
+        let config = Config::from_env();
+        assert_eq!(config.player, Some("spotify".to_string()));
+        assert_eq!(config.player_priority, vec!["mpv", "spotify", "firefox"]);
+
+        // Clean up
+        std::env::remove_var("TRACKWATCH_PLAYER");
+        std::env::remove_var("TRACKWATCH_PLAYER_PRIORITY");
+    }
+
+    #[test]
+    fn test_from_env_mpd() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("MPD_HOST");
+        std::env::remove_var("MPD_PORT");
+        let config = Config::from_env();
+        assert!(config.mpd_host.is_none());
+        assert!(config.mpd_port.is_none());
+
+        std::env::set_var("MPD_HOST", "secret@music.lan");
+        std::env::set_var("MPD_PORT", "6601");
+        let config = Config::from_env();
+        assert_eq!(config.mpd_host, Some("secret@music.lan".to_string()));
+        assert_eq!(config.mpd_port, Some(6601));
+
+        // Clean up
+        std::env::remove_var("MPD_HOST");
+        std::env::remove_var("MPD_PORT");
+    }
+
+    #[test]
+    fn test_from_env_mpv_socket() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_MPV_SOCKET");
+        assert!(Config::from_env().mpv_socket.is_none());
+
+        std::env::set_var("TRACKWATCH_MPV_SOCKET", "/run/user/1000/mpv.sock");
+        assert_eq!(
+            Config::from_env().mpv_socket,
+            Some("/run/user/1000/mpv.sock".to_string())
+        );
+
+        // Clean up
+        std::env::remove_var("TRACKWATCH_MPV_SOCKET");
+    }
+
+    #[test]
+    fn test_from_env_cmus_socket() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("CMUS_SOCKET");
+        assert!(Config::from_env().cmus_socket.is_none());
+
+        std::env::set_var("CMUS_SOCKET", "/tmp/cmus-socket");
+        assert_eq!(
+            Config::from_env().cmus_socket,
+            Some("/tmp/cmus-socket".to_string())
+        );
+
+        // Clean up
+        std::env::remove_var("CMUS_SOCKET");
+    }
+
+    #[test]
+    fn test_from_env_lyrics_dirs() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_LYRICS_DIRS");
+        assert!(Config::from_env().lyrics_dirs.is_empty());
+
+        std::env::set_var("TRACKWATCH_LYRICS_DIRS", "/music/lyrics::/srv/lrc");
+        assert_eq!(
+            Config::from_env().lyrics_dirs,
+            vec![PathBuf::from("/music/lyrics"), PathBuf::from("/srv/lrc")]
+        );
+
+        // Clean up
+        std::env::remove_var("TRACKWATCH_LYRICS_DIRS");
+    }
+
+    #[test]
+    fn test_from_env_lyrics_sources() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        std::env::remove_var("TRACKWATCH_LYRICS_SOURCES");
+        assert!(Config::from_env().lyrics_sources.is_empty());
+
+        std::env::set_var("TRACKWATCH_LYRICS_SOURCES", "lrclib, local");
+        assert_eq!(Config::from_env().lyrics_sources, vec!["lrclib", "local"]);
+
+        // Clean up
+        std::env::remove_var("TRACKWATCH_LYRICS_SOURCES");
+    }
+
     #[test]
     fn test_has_tidal_credentials_both_present() {
         let config = Config {
             tidal_client_id: Some("id".to_string()),
             tidal_client_secret: Some("secret".to_string()),
+            ..Default::default()
         };
         assert!(config.has_tidal_credentials());
     }
@@ -95,6 +327,7 @@ mod tests {
         let config = Config {
             tidal_client_id: None,
             tidal_client_secret: None,
+            ..Default::default()
         };
         assert!(!config.has_tidal_credentials());
     }
@@ -104,6 +337,7 @@ mod tests {
         let config = Config {
             tidal_client_id: Some("id".to_string()),
             tidal_client_secret: None,
+            ..Default::default()
         };
         assert!(!config.has_tidal_credentials());
     }
@@ -113,6 +347,7 @@ mod tests {
         let config = Config {
             tidal_client_id: None,
             tidal_client_secret: Some("secret".to_string()),
+            ..Default::default()
         };
         assert!(!config.has_tidal_credentials());
     }
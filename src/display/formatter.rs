@@ -95,6 +95,34 @@ impl DisplayFormatter {
             image::load_from_memory(&bytes)?
         };
 
+        let (pixelated, ratatui, color_palette) = self.process_image(&img)?;
+
+        // Cache all formats
+        if let Err(e) = self.cache.set(
+            url,
+            pixelated.clone(),
+            ratatui.clone(),
+            color_palette.clone(),
+        ) {
+            eprintln!("Failed to cache image: {e}");
+        }
+
+        Ok((pixelated, ratatui, color_palette))
+    }
+
+    /// Same as `fetch_and_process_all_formats`, for cover art the player
+    /// handed over as raw bytes
+    pub fn process_image_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<(PixelatedImage, super::RatatuiImage, ColorPalette)> {
+        self.process_image(&image::load_from_memory(bytes)?)
+    }
+
+    fn process_image(
+        &self,
+        img: &image::DynamicImage,
+    ) -> Result<(PixelatedImage, super::RatatuiImage, ColorPalette)> {
         // Resize to target size
         let resized = img.resize_exact(self.image_size, self.image_size, FilterType::Lanczos3);
 
@@ -121,16 +149,6 @@ impl DisplayFormatter {
         // Extract color palette (3 for progress, 5 for info)
         let color_palette = ColorExtractor::extract_palette(&resized, 3, 5)?;
 
-        // Cache all formats
-        if let Err(e) = self.cache.set(
-            url,
-            pixelated.clone(),
-            ratatui.clone(),
-            color_palette.clone(),
-        ) {
-            eprintln!("Failed to cache image: {e}");
-        }
-
         Ok((pixelated, ratatui, color_palette))
     }
 
@@ -459,4 +477,62 @@ mod tests {
             assert_eq!(space_count, expected_spaces);
         }
     }
+
+    #[test]
+    fn test_process_image_bytes() {
+        use image::{DynamicImage, ImageFormat, RgbaImage};
+        use std::io::Cursor;
+
+        let formatter = DisplayFormatter {
+            image_size: 4,
+            cache: ImageCache {
+                cache_dir: std::env::temp_dir(),
+            },
+        };
+
+        // An 8x8 gradient encoded as PNG, like a cover sent by the player
+        let img = RgbaImage::from_fn(8, 8, |x, y| {
+            image::Rgba([(x * 32) as u8, (y * 32) as u8, 128, 255])
+        });
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let (pixelated, ratatui, _) = formatter.process_image_bytes(&png).unwrap();
+        assert_eq!(pixelated.lines.len(), 4);
+        assert_eq!(ratatui.pixels.len(), 4);
+        assert_eq!(ratatui.pixels[0].len(), 4);
+
+        assert!(formatter.process_image_bytes(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_fetch_local_file_url() {
+        let directory =
+            std::env::temp_dir().join(format!("trackwatch-test-cover-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let formatter = DisplayFormatter {
+            image_size: 4,
+            cache: ImageCache {
+                cache_dir: directory.clone(),
+            },
+        };
+
+        // A cover.png next to a local file, as the cmus backend reports it
+        let cover = directory.join("cover.png");
+        image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 40, 40, 255]))
+            .save(&cover)
+            .unwrap();
+        let url = format!("file://{}", cover.display());
+
+        let (pixelated, _, _) = formatter.fetch_and_process_all_formats(&url).unwrap();
+        assert_eq!(pixelated.lines.len(), 4);
+
+        // Served from the cache once the file is gone
+        std::fs::remove_file(&cover).unwrap();
+        assert!(formatter.fetch_and_process_all_formats(&url).is_ok());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
 }
@@ -1,11 +1,16 @@
 pub mod cache;
+pub mod cli;
 pub mod colors;
 pub mod config;
 pub mod display;
 pub mod lyrics;
+pub mod lyrics_factory;
 pub mod models;
+pub mod normalize;
 pub mod player;
+pub mod player_factory;
 pub mod progress;
 pub mod provider_factory;
 pub mod providers;
+pub mod tags;
 pub mod ui;
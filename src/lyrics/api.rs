@@ -1,13 +1,24 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use reqwest;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::time::Duration;
 use urlencoding::encode;
 
-use super::LyricsResponse;
+use super::source::{LyricsSource, TrackQuery};
+use super::{parser, LyricsResponse, ParsedLyrics};
+use crate::normalize::CanonicalTrack;
+
+const DEFAULT_BASE_URL: &str = "https://lrclib.net";
+// Releases of the same recording differ by a second or two, live versions
+// and extended mixes by much more
+const MAX_DURATION_DELTA_SECS: f64 = 5.0;
 
 #[derive(Clone)]
 pub struct LrcLibClient {
     client: reqwest::Client,
+    base_url: String,
 }
 
 impl Default for LrcLibClient {
@@ -18,13 +29,21 @@ impl Default for LrcLibClient {
 
 impl LrcLibClient {
     pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Talk to an LRCLIB instance other than lrclib.net
+    pub fn with_base_url(base_url: &str) -> Self {
         let client = reqwest::Client::builder()
             .user_agent("trackwatch/0.1.0")
             .timeout(Duration::from_secs(10))
             .build()
             .unwrap();
 
-        Self { client }
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
     }
 
     pub async fn search_lyrics(
@@ -36,7 +55,8 @@ impl LrcLibClient {
         let encoded_artist = encode(artist_name);
 
         let url = format!(
-            "https://lrclib.net/api/search?track_name={encoded_track}&artist_name={encoded_artist}"
+            "{}/api/search?track_name={encoded_track}&artist_name={encoded_artist}",
+            self.base_url
         );
 
         let response = self
@@ -50,32 +70,545 @@ impl LrcLibClient {
         Ok(response)
     }
 
-    pub async fn get_best_match(
+    /// Free text search over track, artist and album names, for queries
+    /// typed by hand
+    pub async fn search_keywords(&self, keywords: &str) -> Result<Vec<LyricsResponse>> {
+        let url = format!("{}/api/search?q={}", self.base_url, encode(keywords));
+        let response = self.client.get(&url).send().await?;
+        Ok(response.error_for_status()?.json().await?)
+    }
+
+    /// Exact lookup by track, artist, album and duration. LRCLIB only
+    /// answers when all four match one of its records.
+    pub async fn get_lyrics(
         &self,
         track_name: &str,
         artist_name: &str,
+        album_name: &str,
+        duration: Duration,
     ) -> Result<Option<LyricsResponse>> {
-        let results = self.search_lyrics(track_name, artist_name).await?;
+        let url = format!(
+            "{}/api/get?track_name={}&artist_name={}&album_name={}&duration={}",
+            self.base_url,
+            encode(track_name),
+            encode(artist_name),
+            encode(album_name),
+            duration.as_secs_f64().round() as u64
+        );
 
-        if results.is_empty() {
+        let response = self.client.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Ok(None);
         }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    /// The exact match when the player knows the album and duration,
+    /// otherwise the best scoring search result. Searches are retried with
+    /// looser versions of the artist and title until something fits.
+    pub async fn get_best_match(&self, query: &TrackQuery) -> Result<Option<LyricsResponse>> {
+        if let (Some(album), Some(duration)) = (&query.album, query.duration) {
+            match self
+                .get_lyrics(&query.title, &query.artist, album, duration)
+                .await
+            {
+                Ok(Some(response)) => return Ok(Some(response)),
+                Ok(None) => {}
+                // Searching may still work
+                Err(e) => eprintln!("LRCLIB lookup failed, searching instead: {e}"),
+            }
+        }
+
+        for (artist, title) in CanonicalTrack::new(&query.artist, &query.title).query_variants() {
+            let results = self.search_lyrics(&title, &artist).await?;
+            let variant = TrackQuery {
+                artist,
+                title,
+                ..query.clone()
+            };
+            if let Some(best) = best_match(results, &variant) {
+                return Ok(Some(best));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Proof-of-work puzzle that has to be solved before publishing
+    pub async fn request_challenge(&self) -> Result<PublishChallenge> {
+        let url = format!("{}/api/request-challenge", self.base_url);
+        let challenge = self
+            .client
+            .post(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(challenge)
+    }
+
+    /// Submit lyrics to LRCLIB. Solving the challenge takes a while, it
+    /// runs off the async workers.
+    pub async fn publish(&self, request: &PublishRequest) -> Result<()> {
+        let challenge = self.request_challenge().await?;
+        let token = tokio::task::spawn_blocking(move || challenge.publish_token()).await??;
+
+        let url = format!("{}/api/publish", self.base_url);
+        self.client
+            .post(&url)
+            .header("X-Publish-Token", token)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// `/api/request-challenge` answer: find a nonce so that the SHA-256 of
+/// prefix and nonce is at most the target
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublishChallenge {
+    pub prefix: String,
+    pub target: String, // hex
+}
+
+impl PublishChallenge {
+    /// The smallest nonce that meets the target
+    pub fn solve(&self) -> Result<u64> {
+        let target = decode_hex(&self.target)
+            .ok_or_else(|| anyhow!("Invalid challenge target: {}", self.target))?;
+
+        (0..u64::MAX)
+            .find(|nonce| {
+                let hash = Sha256::digest(format!("{}{nonce}", self.prefix).as_bytes());
+                hash.as_slice() <= target.as_slice()
+            })
+            .ok_or_else(|| anyhow!("Challenge has no solution"))
+    }
+
+    /// `X-Publish-Token` header value, `prefix:nonce`
+    pub fn publish_token(&self) -> Result<String> {
+        Ok(format!("{}:{}", self.prefix, self.solve()?))
+    }
+}
+
+/// Body of `/api/publish`. LRCLIB wants every field, so only tracks with a
+/// known album and duration can be published.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PublishRequest {
+    #[serde(rename = "trackName")]
+    pub track_name: String,
+    #[serde(rename = "artistName")]
+    pub artist_name: String,
+    #[serde(rename = "albumName")]
+    pub album_name: String,
+    pub duration: u64, // seconds
+    #[serde(rename = "plainLyrics")]
+    pub plain_lyrics: String,
+    #[serde(rename = "syncedLyrics")]
+    pub synced_lyrics: String,
+}
+
+impl PublishRequest {
+    /// Synced lyrics for the track, shifted by `offset_ms` the same way the
+    /// lyrics pane shows them
+    pub fn new(query: &TrackQuery, lyrics: &ParsedLyrics, offset_ms: i64) -> Result<Self> {
+        if !lyrics.is_synced {
+            return Err(anyhow!("Only synced lyrics can be published"));
+        }
+        if query.artist.is_empty() || query.title.is_empty() {
+            return Err(anyhow!("Track has no artist or title"));
+        }
+        let (Some(album), Some(duration)) = (&query.album, query.duration) else {
+            return Err(anyhow!("LRCLIB needs the album and duration of the track"));
+        };
+
+        let mut plain_lyrics = String::new();
+        let mut synced_lyrics = String::new();
+        for line in &lyrics.lines {
+            let Some(timestamp) = line.timestamp_ms else {
+                continue;
+            };
+            let timestamp = parser::format_timestamp(timestamp.saturating_add_signed(-offset_ms));
+            plain_lyrics.push_str(&format!("{}\n", line.text));
+            synced_lyrics.push_str(&format!("{timestamp}{}\n", line.text));
+            if let Some(ref secondary) = line.secondary {
+                synced_lyrics.push_str(&format!("{timestamp}{secondary}\n"));
+            }
+        }
+        if plain_lyrics.trim().is_empty() {
+            return Err(anyhow!("No lyrics to publish"));
+        }
+
+        Ok(Self {
+            track_name: query.title.clone(),
+            artist_name: query.artist.clone(),
+            album_name: album.clone(),
+            duration: duration.as_secs_f64().round() as u64,
+            plain_lyrics: plain_lyrics.trim_end().to_string(),
+            synced_lyrics: synced_lyrics.trim_end().to_string(),
+        })
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[async_trait]
+impl LyricsSource for LrcLibClient {
+    fn name(&self) -> &'static str {
+        "lrclib"
+    }
 
-        // Prefer results with synced lyrics
-        let with_synced = results.iter().find(|r| r.has_synced_lyrics());
+    async fn fetch(&self, query: &TrackQuery) -> Result<Option<LyricsResponse>> {
+        self.get_best_match(query).await
+    }
+}
+
+/// The highest scoring candidate that isn't rejected
+pub fn best_match(candidates: Vec<LyricsResponse>, query: &TrackQuery) -> Option<LyricsResponse> {
+    candidates
+        .into_iter()
+        .filter_map(|candidate| Some((score_candidate(&candidate, query)?, candidate)))
+        // Keep the earlier result on ties, LRCLIB ranks by relevance
+        .fold(
+            None,
+            |best: Option<(f64, LyricsResponse)>, (score, candidate)| match best {
+                Some((best_score, _)) if best_score >= score => best,
+                _ => Some((score, candidate)),
+            },
+        )
+        .map(|(_, candidate)| candidate)
+}
 
-        if let Some(synced) = with_synced {
-            return Ok(Some(synced.clone()));
+/// Every candidate, best fitting first and rejected ones last
+pub fn rank_candidates(candidates: Vec<LyricsResponse>, query: &TrackQuery) -> Vec<LyricsResponse> {
+    let mut scored: Vec<(Option<f64>, LyricsResponse)> = candidates
+        .into_iter()
+        .map(|candidate| (score_candidate(&candidate, query), candidate))
+        .collect();
+    // Stable, so LRCLIB's relevance order breaks ties
+    scored.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// How well a search result fits the track, `None` when it's a different
+/// recording: too long or short, or a title with nothing in common.
+///
+/// Title counts most, then duration and artist, then album. Synced lyrics
+/// break the tie between otherwise equal results.
+pub fn score_candidate(candidate: &LyricsResponse, query: &TrackQuery) -> Option<f64> {
+    let duration_score = match (candidate.duration, query.duration) {
+        (Some(candidate_secs), Some(duration)) => {
+            let delta = (candidate_secs - duration.as_secs_f64()).abs();
+            if delta > MAX_DURATION_DELTA_SECS {
+                return None;
+            }
+            1.0 - delta / MAX_DURATION_DELTA_SECS
         }
+        // Can't tell, neither reward nor punish
+        _ => 0.5,
+    };
 
-        // Otherwise return first result
-        Ok(results.into_iter().next())
+    let title_score = similarity(&candidate.track_name, &query.title);
+    if title_score == 0.0 {
+        return None;
     }
+    let artist_score = similarity(&candidate.artist_name, &query.artist);
+    let album_score = match (&candidate.album_name, &query.album) {
+        (Some(candidate_album), Some(album)) => similarity(candidate_album, album),
+        _ => 0.0,
+    };
+    let synced_score = if candidate.has_synced_lyrics() {
+        0.5
+    } else {
+        0.0
+    };
+
+    Some(3.0 * title_score + 2.0 * duration_score + 2.0 * artist_score + album_score + synced_score)
+}
+
+/// Share of words the two names have in common, ignoring case and
+/// punctuation. 1.0 for the same words, 0.0 for none.
+fn similarity(a: &str, b: &str) -> f64 {
+    let words = |text: &str| -> Vec<String> {
+        let mut words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        words.sort();
+        words.dedup();
+        words
+    };
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let common = a.iter().filter(|word| b.contains(word)).count();
+    // Dice coefficient
+    2.0 * common as f64 / (a.len() + b.len()) as f64
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Trimmed `/api/search?track_name=One+More+Time&artist_name=Daft+Punk`
+    /// response: a live version, the radio edit, the album version and a cover
+    const SEARCH_ONE_MORE_TIME: &str = r#"[
+        {"id":3396226,"name":"One More Time (Live)","trackName":"One More Time (Live)","artistName":"Daft Punk","albumName":"Alive 1997","duration":611.0,"instrumental":false,"plainLyrics":"One more time","syncedLyrics":"[00:40.12] One more time"},
+        {"id":120944,"name":"One More Time","trackName":"One More Time","artistName":"Daft Punk","albumName":"One More Time (Radio Edit)","duration":231.0,"instrumental":false,"plainLyrics":"One more time","syncedLyrics":"[00:02.61] One more time"},
+        {"id":120941,"name":"One More Time","trackName":"One More Time","artistName":"Daft Punk","albumName":"Discovery","duration":320.0,"instrumental":false,"plainLyrics":"One more time","syncedLyrics":null},
+        {"id":120942,"name":"One More Time","trackName":"One More Time","artistName":"Daft Punk","albumName":"Discovery","duration":321.0,"instrumental":false,"plainLyrics":"One more time","syncedLyrics":"[00:02.61] One more time"},
+        {"id":8812331,"name":"One More Time","trackName":"One More Time","artistName":"Daft Punk Tribute Band","albumName":"Covers","duration":319.0,"instrumental":false,"plainLyrics":"One more time","syncedLyrics":"[00:01.00] One more time"}
+    ]"#;
+
+    fn one_more_time(album: Option<&str>, seconds: Option<u64>) -> TrackQuery {
+        TrackQuery {
+            artist: "Daft Punk".to_string(),
+            title: "One More Time".to_string(),
+            album: album.map(str::to_string),
+            duration: seconds.map(Duration::from_secs),
+            ..Default::default()
+        }
+    }
+
+    fn best_id(query: &TrackQuery) -> Option<u64> {
+        let results: Vec<LyricsResponse> = serde_json::from_str(SEARCH_ONE_MORE_TIME).unwrap();
+        best_match(results, query).map(|response| response.id)
+    }
+
+    /// Answers HTTP requests from a path prefix to (status, body) table
+    /// and records the request lines and headers it saw
+    pub(crate) struct FakeLrcLib {
+        pub base_url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+        headers: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeLrcLib {
+        pub(crate) fn start(routes: Vec<(&'static str, u16, String)>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let seen = Arc::clone(&requests);
+            let headers = Arc::new(Mutex::new(Vec::new()));
+            let seen_headers = Arc::clone(&headers);
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { break };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    // Skip the headers, the body is read when there is one
+                    let mut content_length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                        seen_headers.lock().unwrap().push(header.trim().to_string());
+                    }
+                    let mut body = vec![0u8; content_length];
+                    std::io::Read::read_exact(&mut reader, &mut body).unwrap();
+
+                    let path = request_line
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or("")
+                        .to_string();
+                    seen.lock().unwrap().push(
+                        format!(
+                            "{} {path} {}",
+                            request_line.split_whitespace().next().unwrap_or(""),
+                            String::from_utf8_lossy(&body)
+                        )
+                        .trim_end()
+                        .to_string(),
+                    );
+
+                    let (status, response_body) = routes
+                        .iter()
+                        .find(|(prefix, _, _)| path.starts_with(prefix))
+                        .map(|(_, status, body)| (*status, body.clone()))
+                        .unwrap_or((404, String::new()));
+                    let response = format!(
+                        "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response_body}",
+                        response_body.len()
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            });
+
+            Self {
+                base_url,
+                requests,
+                headers,
+            }
+        }
+
+        pub(crate) fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        /// Value of the last header called `name`
+        pub(crate) fn header(&self, name: &str) -> Option<String> {
+            let prefix = format!("{}:", name.to_lowercase());
+            self.headers
+                .lock()
+                .unwrap()
+                .iter()
+                .rev()
+                .find_map(|header| {
+                    header
+                        .to_lowercase()
+                        .starts_with(&prefix)
+                        .then(|| header[prefix.len()..].trim().to_string())
+                })
+        }
+    }
+
+    #[test]
+    fn test_score_prefers_matching_duration() {
+        // The album version, with synced lyrics over the plain duplicate
+        assert_eq!(
+            best_id(&one_more_time(Some("Discovery"), Some(320))),
+            Some(120942)
+        );
+        // The radio edit, whatever its album is called
+        assert_eq!(best_id(&one_more_time(None, Some(230))), Some(120944));
+        // Nothing is close to 7 minutes
+        assert_eq!(best_id(&one_more_time(None, Some(420))), None);
+        // Without a duration the live version isn't rejected, but the
+        // exact title still wins
+        assert_eq!(best_id(&one_more_time(None, None)), Some(120944));
+    }
+
+    #[test]
+    fn test_score_candidate() {
+        let results: Vec<LyricsResponse> = serde_json::from_str(SEARCH_ONE_MORE_TIME).unwrap();
+        let query = one_more_time(Some("Discovery"), Some(320));
+        let scores: Vec<Option<f64>> = results
+            .iter()
+            .map(|candidate| score_candidate(candidate, &query))
+            .collect();
+
+        assert_eq!(scores[0], None); // live, 611s
+        assert_eq!(scores[1], None); // radio edit, 231s
+        let (album, album_synced, cover) =
+            (scores[2].unwrap(), scores[3].unwrap(), scores[4].unwrap());
+        assert!(album_synced > album);
+        assert!(album > cover, "the tribute band only shares half its name");
+
+        // A different song of the same length
+        let other = LyricsResponse {
+            track_name: "Aerodynamic".to_string(),
+            ..results[2].clone()
+        };
+        assert_eq!(score_candidate(&other, &query), None);
+    }
+
+    #[test]
+    fn test_rank_candidates() {
+        let results: Vec<LyricsResponse> = serde_json::from_str(SEARCH_ONE_MORE_TIME).unwrap();
+        let ranked = rank_candidates(results, &one_more_time(Some("Discovery"), Some(320)));
+        let ids: Vec<u64> = ranked.iter().map(|candidate| candidate.id).collect();
+        // The rejected live version and radio edit stay listed, at the end
+        assert_eq!(ids, vec![120942, 120941, 8812331, 3396226, 120944]);
+    }
+
+    #[tokio::test]
+    async fn test_search_keywords() {
+        let server =
+            FakeLrcLib::start(vec![("/api/search", 200, SEARCH_ONE_MORE_TIME.to_string())]);
+        let client = LrcLibClient::with_base_url(&server.base_url);
+
+        let results = client.search_keywords("daft punk one more").await.unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(
+            server.requests(),
+            vec!["GET /api/search?q=daft%20punk%20one%20more"]
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("One More Time", "one more time!"), 1.0);
+        assert_eq!(
+            similarity("Daft Punk", "Daft Punk Tribute Band"),
+            2.0 * 2.0 / 6.0
+        );
+        assert_eq!(similarity("Da Da Da", "Da"), 1.0);
+        assert_eq!(similarity("", "Song"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_best_match_tries_exact_lookup_first() {
+        let exact: Vec<LyricsResponse> = serde_json::from_str(SEARCH_ONE_MORE_TIME).unwrap();
+        let server = FakeLrcLib::start(vec![
+            ("/api/get", 200, serde_json::to_string(&exact[3]).unwrap()),
+            ("/api/search", 200, SEARCH_ONE_MORE_TIME.to_string()),
+        ]);
+        let client = LrcLibClient::with_base_url(&server.base_url);
+
+        let found = client
+            .get_best_match(&one_more_time(Some("Discovery"), Some(320)))
+            .await
+            .unwrap();
+        assert_eq!(found.map(|response| response.id), Some(120942));
+        assert_eq!(
+            server.requests(),
+            vec!["GET /api/get?track_name=One%20More%20Time&artist_name=Daft%20Punk&album_name=Discovery&duration=320"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_best_match_falls_back_to_search() {
+        let server = FakeLrcLib::start(vec![
+            (
+                "/api/get",
+                404,
+                r#"{"code":404,"name":"TrackNotFound"}"#.to_string(),
+            ),
+            ("/api/search", 200, SEARCH_ONE_MORE_TIME.to_string()),
+        ]);
+        let client = LrcLibClient::with_base_url(&server.base_url);
+
+        let found = client
+            .get_best_match(&one_more_time(Some("Single"), Some(231)))
+            .await
+            .unwrap();
+        assert_eq!(found.map(|response| response.id), Some(120944));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /api/search?"));
+
+        // Without album and duration there is nothing to look up exactly
+        client
+            .get_best_match(&one_more_time(None, None))
+            .await
+            .unwrap();
+        assert!(server.requests()[2].starts_with("GET /api/search?"));
+    }
 
     fn create_test_response() -> LyricsResponse {
         LyricsResponse {
@@ -128,7 +661,7 @@ mod tests {
         };
 
         // Test selection logic
-        let responses = vec![
+        let responses = [
             response_without_synced.clone(),
             response_with_synced.clone(),
             instrumental.clone(),
@@ -223,4 +756,108 @@ mod tests {
         assert_eq!(encoded_track, "Bohemian%20Rhapsody");
         assert_eq!(encoded_artist, "Simon%20%26%20Garfunkel");
     }
+
+    /// A target any hash with a leading zero byte meets
+    fn easy_challenge() -> PublishChallenge {
+        PublishChallenge {
+            prefix: "trackwatch".to_string(),
+            target: format!("00{}", "ff".repeat(31)),
+        }
+    }
+
+    #[test]
+    fn test_solve_challenge() {
+        let challenge = easy_challenge();
+        let nonce = challenge.solve().unwrap();
+        let first_byte = |nonce: u64| Sha256::digest(format!("trackwatch{nonce}").as_bytes())[0];
+        assert_eq!(first_byte(nonce), 0);
+        assert!((0..nonce).all(|smaller| first_byte(smaller) != 0));
+        assert_eq!(
+            challenge.publish_token().unwrap(),
+            format!("trackwatch:{nonce}")
+        );
+
+        let broken = PublishChallenge {
+            target: "zz".to_string(),
+            ..easy_challenge()
+        };
+        assert!(broken.solve().is_err());
+    }
+
+    #[test]
+    fn test_publish_request() {
+        let lyrics =
+            parser::parse_lrc("[ar:Daft Punk]\n[00:02.50]One more time\n[00:05.00]Celebrate");
+        let query = one_more_time(Some("Discovery"), Some(320));
+
+        let request = PublishRequest::new(&query, &lyrics, 500).unwrap();
+        assert_eq!(request.track_name, "One More Time");
+        assert_eq!(request.album_name, "Discovery");
+        assert_eq!(request.duration, 320);
+        assert_eq!(request.plain_lyrics, "One more time\nCelebrate");
+        // Shown half a second sooner, so published half a second earlier
+        assert_eq!(
+            request.synced_lyrics,
+            "[00:02.00]One more time\n[00:04.50]Celebrate"
+        );
+
+        assert!(PublishRequest::new(&one_more_time(None, Some(320)), &lyrics, 0).is_err());
+        let plain = parser::parse_lrc("One more time");
+        assert!(PublishRequest::new(&query, &plain, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_publish() {
+        let challenge = easy_challenge();
+        let server = FakeLrcLib::start(vec![
+            (
+                "/api/request-challenge",
+                200,
+                format!(
+                    r#"{{"prefix":"{}","target":"{}"}}"#,
+                    challenge.prefix, challenge.target
+                ),
+            ),
+            ("/api/publish", 201, String::new()),
+        ]);
+        let client = LrcLibClient::with_base_url(&server.base_url);
+        let lyrics = parser::parse_lrc("[00:02.50]One more time");
+        let request =
+            PublishRequest::new(&one_more_time(Some("Discovery"), Some(320)), &lyrics, 0).unwrap();
+
+        client.publish(&request).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0], "POST /api/request-challenge");
+        assert_eq!(
+            requests[1],
+            format!(
+                "POST /api/publish {}",
+                serde_json::to_string(&request).unwrap()
+            )
+        );
+        assert_eq!(
+            server.header("X-Publish-Token"),
+            Some(challenge.publish_token().unwrap())
+        );
+        assert!(requests[1].contains(r#""syncedLyrics":"[00:02.50]One more time""#));
+    }
+
+    #[tokio::test]
+    async fn test_publish_rejected() {
+        let server = FakeLrcLib::start(vec![
+            (
+                "/api/request-challenge",
+                200,
+                r#"{"prefix":"p","target":"ffff"}"#.to_string(),
+            ),
+            ("/api/publish", 400, r#"{"message":"Invalid"}"#.to_string()),
+        ]);
+        let client = LrcLibClient::with_base_url(&server.base_url);
+        let lyrics = parser::parse_lrc("[00:02.50]One more time");
+        let request =
+            PublishRequest::new(&one_more_time(Some("Discovery"), Some(320)), &lyrics, 0).unwrap();
+
+        assert!(client.publish(&request).await.is_err());
+    }
 }
@@ -12,10 +12,17 @@ const CACHE_EXPIRY_DAYS: u64 = 7; // Lyrics update more frequently
 
 #[derive(Serialize, Deserialize)]
 struct CachedLyrics {
+    #[serde(default)]
+    pub source: String, // the LyricsSource that answered
     pub response: Option<LyricsResponse>, // None means "not found"
     pub cached_at: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+struct CachedOffset {
+    pub offset_ms: i64,
+}
+
 #[derive(Clone)]
 pub struct LyricsCache {
     cache_dir: PathBuf,
@@ -24,13 +31,17 @@ pub struct LyricsCache {
 impl LyricsCache {
     pub fn new() -> Result<Self> {
         let home = std::env::var("HOME")?;
-        let cache_dir = Path::new(&home).join(CACHE_DIR);
+        Self::with_dir(Path::new(&home).join(CACHE_DIR))
+    }
+
+    pub fn with_dir(cache_dir: PathBuf) -> Result<Self> {
         fs::create_dir_all(&cache_dir)?;
         Ok(Self { cache_dir })
     }
 
-    pub fn get(&self, artist: &str, title: &str) -> Option<Option<LyricsResponse>> {
-        let key = self.generate_key(artist, title);
+    /// What `source` answered for the track, `Some(None)` when it had nothing
+    pub fn get(&self, source: &str, artist: &str, title: &str) -> Option<Option<LyricsResponse>> {
+        let key = self.generate_key(source, artist, title);
         let cache_path = self.cache_dir.join(format!("{key}.json"));
 
         if !cache_path.exists() {
@@ -57,11 +68,18 @@ impl LyricsCache {
         Some(cached.response)
     }
 
-    pub fn set(&self, artist: &str, title: &str, lyrics: Option<&LyricsResponse>) -> Result<()> {
-        let key = self.generate_key(artist, title);
+    pub fn set(
+        &self,
+        source: &str,
+        artist: &str,
+        title: &str,
+        lyrics: Option<&LyricsResponse>,
+    ) -> Result<()> {
+        let key = self.generate_key(source, artist, title);
         let cache_path = self.cache_dir.join(format!("{key}.json"));
 
         let cached = CachedLyrics {
+            source: source.to_string(),
             response: lyrics.cloned(),
             cached_at: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -75,9 +93,73 @@ impl LyricsCache {
         Ok(())
     }
 
-    fn generate_key(&self, artist: &str, title: &str) -> String {
+    /// Sync offset picked by hand for the track, whichever source answered
+    pub fn get_offset(&self, artist: &str, title: &str) -> Option<i64> {
+        let contents = fs::read_to_string(self.offset_path(artist, title)).ok()?;
+        let cached: CachedOffset = serde_json::from_str(&contents).ok()?;
+        Some(cached.offset_ms)
+    }
+
+    /// Offsets never expire, a zero offset removes the file
+    pub fn set_offset(&self, artist: &str, title: &str, offset_ms: i64) -> Result<()> {
+        let offset_path = self.offset_path(artist, title);
+        if offset_ms == 0 {
+            if offset_path.exists() {
+                fs::remove_file(offset_path)?;
+            }
+            return Ok(());
+        }
+
+        let json = serde_json::to_string_pretty(&CachedOffset { offset_ms })?;
+        fs::write(offset_path, json)?;
+
+        Ok(())
+    }
+
+    /// Lyrics picked by hand for the track, used instead of any source
+    pub fn get_pinned(&self, artist: &str, title: &str) -> Option<LyricsResponse> {
+        let contents = fs::read_to_string(self.pinned_path(artist, title)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Pins never expire, `None` goes back to automatic selection
+    pub fn set_pinned(
+        &self,
+        artist: &str,
+        title: &str,
+        lyrics: Option<&LyricsResponse>,
+    ) -> Result<()> {
+        let pinned_path = self.pinned_path(artist, title);
+        let Some(lyrics) = lyrics else {
+            if pinned_path.exists() {
+                fs::remove_file(pinned_path)?;
+            }
+            return Ok(());
+        };
+
+        let json = serde_json::to_string_pretty(lyrics)?;
+        fs::write(pinned_path, json)?;
+
+        Ok(())
+    }
+
+    fn pinned_path(&self, artist: &str, title: &str) -> PathBuf {
+        let key = self.generate_key("pinned", artist, title);
+        self.cache_dir.join(format!("{key}.pinned.json"))
+    }
+
+    fn offset_path(&self, artist: &str, title: &str) -> PathBuf {
+        let key = self.generate_key("offset", artist, title);
+        self.cache_dir.join(format!("{key}.offset.json"))
+    }
+
+    fn generate_key(&self, source: &str, artist: &str, title: &str) -> String {
         let mut hasher = Sha256::new();
-        let normalized = format!("{}:{}", artist.to_lowercase(), title.to_lowercase());
+        let normalized = format!(
+            "{source}:{}:{}",
+            artist.to_lowercase(),
+            title.to_lowercase()
+        );
         hasher.update(normalized.as_bytes());
         format!("{:x}", hasher.finalize())
     }
@@ -95,29 +177,95 @@ mod tests {
         };
 
         // Same artist/title should produce same key
-        let key1 = cache.generate_key("Queen", "Bohemian Rhapsody");
-        let key2 = cache.generate_key("Queen", "Bohemian Rhapsody");
+        let key1 = cache.generate_key("lrclib", "Queen", "Bohemian Rhapsody");
+        let key2 = cache.generate_key("lrclib", "Queen", "Bohemian Rhapsody");
         assert_eq!(key1, key2);
 
         // Case insensitive
-        let key3 = cache.generate_key("QUEEN", "BOHEMIAN RHAPSODY");
-        let key4 = cache.generate_key("queen", "bohemian rhapsody");
+        let key3 = cache.generate_key("lrclib", "QUEEN", "BOHEMIAN RHAPSODY");
+        let key4 = cache.generate_key("lrclib", "queen", "bohemian rhapsody");
         assert_eq!(key3, key4);
         assert_eq!(key1, key3);
 
         // Different songs should have different keys
-        let key5 = cache.generate_key("Queen", "We Will Rock You");
+        let key5 = cache.generate_key("lrclib", "Queen", "We Will Rock You");
         assert_ne!(key1, key5);
 
         // Different artists should have different keys
-        let key6 = cache.generate_key("David Bowie", "Bohemian Rhapsody");
+        let key6 = cache.generate_key("lrclib", "David Bowie", "Bohemian Rhapsody");
         assert_ne!(key1, key6);
 
+        // Each source has its own entry
+        let key7 = cache.generate_key("other", "Queen", "Bohemian Rhapsody");
+        assert_ne!(key1, key7);
+
         // Key should be valid hex string (SHA256)
         assert_eq!(key1.len(), 64);
         assert!(key1.chars().all(|c| c.is_ascii_hexdigit()));
     }
 
+    #[test]
+    fn test_get_and_set() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-lyrics-cache-{}", std::process::id()));
+        let cache = LyricsCache::with_dir(cache_dir.clone()).unwrap();
+        assert!(cache.get("lrclib", "Queen", "Innuendo").is_none());
+
+        cache.set("lrclib", "Queen", "Innuendo", None).unwrap();
+        assert_eq!(
+            cache
+                .get("lrclib", "Queen", "Innuendo")
+                .map(|r| r.is_none()),
+            Some(true)
+        );
+        assert!(cache.get("other", "Queen", "Innuendo").is_none());
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_offsets() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-lyrics-offset-{}", std::process::id()));
+        let cache = LyricsCache::with_dir(cache_dir.clone()).unwrap();
+        assert_eq!(cache.get_offset("Queen", "Innuendo"), None);
+
+        cache.set_offset("Queen", "Innuendo", -400).unwrap();
+        assert_eq!(cache.get_offset("QUEEN", "innuendo"), Some(-400));
+        assert_eq!(cache.get_offset("Queen", "Headlong"), None);
+        // The offset does not pass for cached lyrics
+        assert!(cache.get("offset", "Queen", "Innuendo").is_none());
+
+        cache.set_offset("Queen", "Innuendo", 0).unwrap();
+        assert_eq!(cache.get_offset("Queen", "Innuendo"), None);
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_pinned() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-lyrics-pinned-{}", std::process::id()));
+        let cache = LyricsCache::with_dir(cache_dir.clone()).unwrap();
+        assert!(cache.get_pinned("Queen", "Innuendo").is_none());
+
+        let mut lyrics = crate::lyrics::source::tests::response(None, Some("While the sun"));
+        lyrics.id = 42;
+        cache
+            .set_pinned("Queen", "Innuendo", Some(&lyrics))
+            .unwrap();
+        assert_eq!(
+            cache.get_pinned("queen", "INNUENDO").map(|l| l.id),
+            Some(42)
+        );
+        assert!(cache.get_pinned("Queen", "Headlong").is_none());
+
+        cache.set_pinned("Queen", "Innuendo", None).unwrap();
+        assert!(cache.get_pinned("Queen", "Innuendo").is_none());
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
     #[test]
     fn test_key_normalization() {
         let temp_dir = std::env::temp_dir();
@@ -136,8 +284,8 @@ mod tests {
         ];
 
         for ((artist1, title1), (artist2, title2)) in test_cases {
-            let key1 = cache.generate_key(artist1, title1);
-            let key2 = cache.generate_key(artist2, title2);
+            let key1 = cache.generate_key("lrclib", artist1, title1);
+            let key2 = cache.generate_key("lrclib", artist2, title2);
             assert_eq!(
                 key1, key2,
                 "Keys should match for {artist1}/{title1} vs {artist2}/{title2}"
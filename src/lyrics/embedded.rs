@@ -0,0 +1,84 @@
+//! Lyrics embedded in the tags of the audio file
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::source::{response_from_text, LyricsSource, TrackQuery};
+use super::{parser, LyricsResponse};
+use crate::tags::{self, EmbeddedLyrics};
+
+#[derive(Debug, Clone, Default)]
+pub struct TagLyrics;
+
+#[async_trait]
+impl LyricsSource for TagLyrics {
+    fn name(&self) -> &'static str {
+        "embedded"
+    }
+
+    async fn fetch(&self, query: &TrackQuery) -> Result<Option<LyricsResponse>> {
+        let Some(audio_file) = query.local_path.as_deref().filter(|path| path.is_file()) else {
+            return Ok(None);
+        };
+
+        let text = match tags::embedded_lyrics(audio_file)? {
+            Some(EmbeddedLyrics::Text(text)) => text,
+            Some(EmbeddedLyrics::Synced(lines)) => to_lrc(lines),
+            None => return Ok(None),
+        };
+        Ok(response_from_text(query, text))
+    }
+
+    fn cacheable(&self) -> bool {
+        false
+    }
+}
+
+/// SYLT lines as LRC text
+fn to_lrc(lines: Vec<(u64, String)>) -> String {
+    lines
+        .into_iter()
+        .map(|(timestamp, text)| format!("{}{text}\n", parser::format_timestamp(timestamp)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::TagLike;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_synced_tag_lyrics() {
+        let song =
+            std::env::temp_dir().join(format!("trackwatch-embedded-{}.mp3", std::process::id()));
+        fs::write(&song, b"").unwrap();
+        let mut tag = id3::Tag::new();
+        tag.add_frame(id3::frame::SynchronisedLyrics {
+            lang: "eng".to_string(),
+            timestamp_format: id3::frame::TimestampFormat::Ms,
+            content_type: id3::frame::SynchronisedLyricsType::Lyrics,
+            description: String::new(),
+            content: vec![(5_000, "Later".to_string()), (1_000, "Sooner".to_string())],
+        });
+        tag.write_to_path(&song, id3::Version::Id3v24).unwrap();
+
+        let query = TrackQuery {
+            local_path: Some(song.clone()),
+            ..Default::default()
+        };
+        let response = TagLyrics.fetch(&query).await.unwrap().unwrap();
+        let parsed = parser::parse_lrc(response.synced_lyrics.as_deref().unwrap());
+        assert_eq!(parsed.lines[0].timestamp_ms, Some(1_000));
+        assert_eq!(parsed.lines[1].text, "Later");
+
+        // Streams and missing files have no tags to read
+        fs::remove_file(&song).unwrap();
+        assert!(TagLyrics.fetch(&query).await.unwrap().is_none());
+        assert!(TagLyrics
+            .fetch(&TrackQuery::default())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,251 @@
+//! Lyrics kept next to the music: `.lrc`/`.txt` sidecar files and lyrics
+//! directories with `Artist - Title.lrc` files
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::source::{response_from_text, LyricsSource, TrackQuery};
+use super::LyricsResponse;
+
+const DEFAULT_LYRICS_DIR: &str = "Music/lyrics";
+// Synced lyrics win over plain text
+const EXTENSIONS: [&str; 2] = ["lrc", "txt"];
+
+#[derive(Debug, Clone, Default)]
+pub struct LocalLyrics {
+    directories: Vec<PathBuf>,
+}
+
+impl LocalLyrics {
+    pub fn new(directories: Vec<PathBuf>) -> Self {
+        Self { directories }
+    }
+
+    /// The configured lyrics directories, `~/Music/lyrics` when there are none
+    pub fn with_default_dirs(directories: &[PathBuf]) -> Self {
+        if !directories.is_empty() {
+            return Self::new(directories.to_vec());
+        }
+        let directories = std::env::var("HOME")
+            .map(|home| vec![Path::new(&home).join(DEFAULT_LYRICS_DIR)])
+            .unwrap_or_default();
+        Self::new(directories)
+    }
+
+    /// Lyrics file for the track: a sidecar next to the audio file first,
+    /// then the lyrics directories
+    pub fn find(&self, query: &TrackQuery) -> Option<PathBuf> {
+        if let Some(sidecar) = query.local_path.as_deref().and_then(find_sidecar) {
+            return Some(sidecar);
+        }
+
+        let wanted = normalize_name(&format!("{} - {}", query.artist, query.title));
+        if wanted.is_empty() {
+            return None;
+        }
+        self.directories
+            .iter()
+            .find_map(|directory| find_in_directory(directory, &wanted))
+    }
+
+    /// Write an LRC file `find` picks up next time: a sidecar `.lrc` next to
+    /// the audio file, or `Artist - Title.lrc` in the first lyrics directory
+    /// for streams and read-only music folders
+    pub fn save(&self, query: &TrackQuery, lrc: &str) -> Result<PathBuf> {
+        if let Some(audio_file) = query.local_path.as_deref().filter(|path| path.is_file()) {
+            let sidecar = audio_file.with_extension(EXTENSIONS[0]);
+            if fs::write(&sidecar, lrc).is_ok() {
+                return Ok(sidecar);
+            }
+        }
+
+        let directory = self
+            .directories
+            .first()
+            .ok_or_else(|| anyhow!("No lyrics directory to save to"))?;
+        if normalize_name(&format!("{}{}", query.artist, query.title)).is_empty() {
+            return Err(anyhow!("Track has no artist or title to name the file"));
+        }
+        fs::create_dir_all(directory)?;
+        let file_name = format!("{} - {}.{}", query.artist, query.title, EXTENSIONS[0])
+            .replace(['/', '\\', '\0'], "_");
+        let path = directory.join(file_name);
+        fs::write(&path, lrc)?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl LyricsSource for LocalLyrics {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn fetch(&self, query: &TrackQuery) -> Result<Option<LyricsResponse>> {
+        let Some(path) = self.find(query) else {
+            return Ok(None);
+        };
+        let text = String::from_utf8_lossy(&fs::read(path)?).into_owned();
+        Ok(response_from_text(query, text))
+    }
+
+    fn cacheable(&self) -> bool {
+        false
+    }
+}
+
+fn find_sidecar(audio_file: &Path) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|extension| audio_file.with_extension(extension))
+        .find(|path| path.is_file())
+}
+
+fn find_in_directory(directory: &Path, wanted: &str) -> Option<PathBuf> {
+    let mut matches: Vec<PathBuf> = fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| extension_rank(path).is_some() && path.is_file())
+        .filter(|path| {
+            path.file_stem()
+                .is_some_and(|stem| normalize_name(&stem.to_string_lossy()) == wanted)
+        })
+        .collect();
+    matches.sort_by_key(|path| extension_rank(path));
+    matches.into_iter().next()
+}
+
+fn extension_rank(path: &Path) -> Option<usize> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    EXTENSIONS.iter().position(|known| *known == extension)
+}
+
+/// Lowercase letters and digits only, so "AC/DC - T.N.T." matches a file
+/// saved as "acdc - tnt.lrc"
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_dir() -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "trackwatch-lyrics-{}-{}",
+            std::process::id(),
+            DIR_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn track(artist: &str, title: &str, local_path: Option<PathBuf>) -> TrackQuery {
+        TrackQuery {
+            artist: artist.to_string(),
+            title: title.to_string(),
+            local_path,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sidecar_lrc_before_txt() {
+        let album = temp_dir();
+        let song = album.join("01 Song.flac");
+        fs::write(&song, b"").unwrap();
+        fs::write(album.join("01 Song.txt"), "Plain words").unwrap();
+        let query = track("Artist", "Song", Some(song));
+
+        let local = LocalLyrics::new(Vec::new());
+        assert_eq!(local.find(&query), Some(album.join("01 Song.txt")));
+        let response = local.fetch(&query).await.unwrap().unwrap();
+        assert_eq!(response.plain_lyrics.as_deref(), Some("Plain words"));
+        assert!(response.synced_lyrics.is_none());
+
+        fs::write(album.join("01 Song.lrc"), "[00:01.00]Timed words").unwrap();
+        let response = local.fetch(&query).await.unwrap().unwrap();
+        assert!(response.has_synced_lyrics());
+        assert_eq!(response.track_name, "Song");
+
+        fs::remove_dir_all(album).unwrap();
+    }
+
+    #[test]
+    fn test_lyrics_directory_normalized_match() {
+        let directory = temp_dir();
+        fs::write(directory.join("acdc - tnt.txt"), "Plain").unwrap();
+        fs::write(directory.join("ACDC - TNT.LRC"), "[00:02.00]Synced").unwrap();
+        fs::write(directory.join("Other - Song.lrc"), "[00:02.00]Other").unwrap();
+
+        // Streams have no sidecar, the directory still matches
+        let local = LocalLyrics::new(vec![PathBuf::from("/nonexistent"), directory.clone()]);
+        assert_eq!(
+            local.find(&track("AC/DC", "T.N.T.", None)),
+            Some(directory.join("ACDC - TNT.LRC"))
+        );
+
+        assert!(local.find(&track("AC/DC", "Thunderstruck", None)).is_none());
+        assert!(local.find(&track("", "", None)).is_none());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_empty_file_is_skipped() {
+        let directory = temp_dir();
+        fs::write(directory.join("Artist - Song.lrc"), "[ar:Artist]\n").unwrap();
+
+        let local = LocalLyrics::new(vec![directory.clone()]);
+        let query = track("Artist", "Song", None);
+        assert!(local.fetch(&query).await.unwrap().is_none());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_save_is_found_again() {
+        let album = temp_dir();
+        let directory = album.join("lyrics");
+        let local = LocalLyrics::new(vec![directory.clone()]);
+        let lrc = "[00:01.00]Timed words\n";
+
+        // Local files get a sidecar that beats the plain one
+        let song = album.join("01 Song.flac");
+        fs::write(&song, b"").unwrap();
+        fs::write(album.join("01 Song.txt"), "Plain words").unwrap();
+        let query = track("Artist", "Song", Some(song));
+        assert_eq!(local.save(&query, lrc).unwrap(), album.join("01 Song.lrc"));
+        assert_eq!(local.find(&query), Some(album.join("01 Song.lrc")));
+
+        // Streams go to the lyrics directory, created on demand
+        let query = track("AC/DC", "T.N.T.", None);
+        let saved = local.save(&query, lrc).unwrap();
+        assert_eq!(saved, directory.join("AC_DC - T.N.T..lrc"));
+        assert_eq!(local.find(&query), Some(saved));
+
+        assert!(local.save(&track("", "", None), lrc).is_err());
+        assert!(LocalLyrics::new(Vec::new())
+            .save(&track("Artist", "Song", None), lrc)
+            .is_err());
+
+        fs::remove_dir_all(album).unwrap();
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(
+            normalize_name("Sigur Rós - Hoppípolla"),
+            "sigurróshoppípolla"
+        );
+        assert_eq!(normalize_name("  A - B (Live)!"), "ablive");
+    }
+}
@@ -1,6 +1,11 @@
 pub mod api;
 pub mod cache;
+pub mod embedded;
+pub mod local;
 pub mod parser;
+pub mod recorder;
+pub mod romanize;
+pub mod source;
 
 use serde::{Deserialize, Serialize};
 
@@ -22,16 +27,66 @@ pub struct LyricsResponse {
     pub synced_lyrics: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ParsedLyrics {
     pub lines: Vec<LyricLine>,
     pub is_synced: bool,
+    pub metadata: LyricsMetadata,
+    pub source: Option<String>, // name of the LyricsSource that found them
+    pub instrumental: bool,     // the source says there are no vocals
 }
 
-#[derive(Debug, Clone)]
+/// Stretch of synced lyrics without singing: the intro before the first
+/// line, or a break marked by a blank line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LyricsGap {
+    pub start_ms: u64,
+    pub end_ms: u64,      // when the next line starts
+    pub next_line: usize, // index of that line
+}
+
+impl LyricsGap {
+    /// Milliseconds until the next line
+    pub fn remaining_ms(&self, position_ms: u64) -> u64 {
+        self.end_ms.saturating_sub(position_ms)
+    }
+}
+
+/// ID tags from the header of an LRC file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LyricsMetadata {
+    pub artist: Option<String>, // [ar:]
+    pub title: Option<String>,  // [ti:]
+    pub album: Option<String>,  // [al:]
+    pub author: Option<String>, // [au:] songwriter
+    pub by: Option<String>,     // [by:] who made the LRC file
+    pub length_ms: Option<u64>, // [length:]
+    pub offset_ms: i64,         // [offset:], already applied to the timestamps
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct LyricLine {
     pub timestamp_ms: Option<u64>, // milliseconds
     pub text: String,
+    pub words: Vec<LyricWord>, // enhanced LRC word timing, empty without
+    pub secondary: Option<String>, // translation sharing the timestamp
+}
+
+/// One sung segment of an enhanced LRC line, `<00:12.40>world`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricWord {
+    pub timestamp_ms: u64,
+    pub text: String,
+}
+
+impl LyricLine {
+    /// How many words have started by `position_ms`
+    pub fn sung_words(&self, position_ms: u64) -> usize {
+        self.words
+            .iter()
+            .take_while(|word| word.timestamp_ms <= position_ms)
+            .count()
+    }
 }
 
 impl LyricsResponse {
@@ -174,17 +229,21 @@ mod tests {
                 LyricLine {
                     timestamp_ms: Some(0),
                     text: "First line".to_string(),
+                    ..Default::default()
                 },
                 LyricLine {
                     timestamp_ms: Some(5000),
                     text: "Second line".to_string(),
+                    ..Default::default()
                 },
                 LyricLine {
                     timestamp_ms: None,
                     text: "Unsynced line".to_string(),
+                    ..Default::default()
                 },
             ],
             is_synced: true,
+            ..Default::default()
         };
 
         assert_eq!(lyrics.lines.len(), 3);
@@ -193,4 +252,26 @@ mod tests {
         assert_eq!(lyrics.lines[0].timestamp_ms, Some(0));
         assert_eq!(lyrics.lines[2].timestamp_ms, None);
     }
+
+    #[test]
+    fn test_sung_words() {
+        let word = |timestamp_ms, text: &str| LyricWord {
+            timestamp_ms,
+            text: text.to_string(),
+        };
+        let line = LyricLine {
+            timestamp_ms: Some(12_000),
+            text: "Hello world".to_string(),
+            words: vec![word(12_000, "Hello "), word(12_400, "world")],
+            ..Default::default()
+        };
+
+        assert_eq!(line.sung_words(11_999), 0);
+        assert_eq!(line.sung_words(12_000), 1);
+        assert_eq!(line.sung_words(12_399), 1);
+        assert_eq!(line.sung_words(20_000), 2);
+
+        // Lines without word timing have nothing to sing
+        assert_eq!(LyricLine::default().sung_words(20_000), 0);
+    }
 }
@@ -1,50 +1,254 @@
-use super::{LyricLine, ParsedLyrics};
+use super::{LyricLine, LyricWord, LyricsGap, LyricsMetadata, ParsedLyrics};
 use regex::Regex;
+use std::sync::LazyLock;
+
+// Shorter pauses are just breaths between lines
+const MIN_GAP_MS: u64 = 5_000;
+
+/// Offsets beyond a day are nonsense, and clamping keeps negating them safe
+pub const MAX_OFFSET_MS: i64 = 86_400_000;
+
+// [mm:ss], [mm:ss.x], [mm:ss.xx] or [mm:ss.xxx]. Some tools write the
+// fraction after a colon, [mm:ss:xx].
+static TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[(\d+):(\d{2})(?:[.:](\d{1,3}))?\]").unwrap());
+
+// Enhanced LRC word timing inside a line, <mm:ss.xx>
+static WORD_TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(\d+):(\d{2})(?:[.:](\d{1,3}))?>").unwrap());
+
+// [ar:Artist], [offset:+250]...
+static ID_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[([A-Za-z]+):(.*)\]\s*$").unwrap());
+
+static TIME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+):(\d{2})(?:[.:](\d{1,3}))?$").unwrap());
 
 pub fn parse_lrc(lrc_content: &str) -> ParsedLyrics {
     let mut lines = Vec::new();
+    let mut metadata = LyricsMetadata::default();
 
-    for line in lrc_content.lines() {
-        if let Some(parsed) = parse_lrc_line(line) {
-            lines.push(parsed);
+    for line in lrc_content.trim_start_matches('\u{feff}').lines() {
+        if let Some((key, value)) = parse_id_tag(line) {
+            apply_id_tag(&mut metadata, &key, value);
+        } else {
+            lines.extend(parse_lrc_line(line));
+        }
+    }
+
+    // A positive offset makes the lyrics show up sooner
+    if metadata.offset_ms != 0 {
+        let shift = |timestamp: &mut u64| {
+            *timestamp = timestamp.saturating_add_signed(-metadata.offset_ms);
+        };
+        for line in &mut lines {
+            if let Some(timestamp) = line.timestamp_ms.as_mut() {
+                shift(timestamp);
+            }
+            line.words
+                .iter_mut()
+                .for_each(|word| shift(&mut word.timestamp_ms));
         }
     }
 
-    // Sort by timestamp if synced
+    // Sort by timestamp if synced, lines sharing one keep their file order
     lines.sort_by_key(|line| line.timestamp_ms.unwrap_or(u64::MAX));
+    let lines = group_translations(lines);
 
     ParsedLyrics {
         is_synced: lines.iter().any(|l| l.timestamp_ms.is_some()),
         lines,
+        metadata,
+        ..Default::default()
     }
 }
 
-fn parse_lrc_line(line: &str) -> Option<LyricLine> {
-    // Match format: [MM:SS.ms] Text
-    let timestamp_regex = Regex::new(r"^\[(\d{2}):(\d{2})\.(\d{2})\]\s*(.*)$").unwrap();
+/// Lines sharing a timestamp are an original and its translation. The
+/// first one is sung, the rest becomes its secondary text.
+fn group_translations(lines: Vec<LyricLine>) -> Vec<LyricLine> {
+    let mut grouped: Vec<LyricLine> = Vec::with_capacity(lines.len());
+    for line in lines {
+        match grouped.last_mut() {
+            Some(previous)
+                if line.timestamp_ms.is_some()
+                    && previous.timestamp_ms == line.timestamp_ms
+                    && !previous.text.is_empty()
+                    && !line.text.is_empty() =>
+            {
+                previous.secondary = Some(match previous.secondary.take() {
+                    Some(secondary) => format!("{secondary} / {}", line.text),
+                    None => line.text,
+                });
+            }
+            _ => grouped.push(line),
+        }
+    }
+    grouped
+}
 
-    if let Some(captures) = timestamp_regex.captures(line) {
-        let minutes: u64 = captures[1].parse().ok()?;
-        let seconds: u64 = captures[2].parse().ok()?;
-        let centiseconds: u64 = captures[3].parse().ok()?;
+/// One line of an LRC file. A line with several timestamps is repeated
+/// lyrics (a chorus) and gives one `LyricLine` per timestamp.
+fn parse_lrc_line(line: &str) -> Vec<LyricLine> {
+    let mut timestamps = Vec::new();
+    let mut rest = line.trim_start();
+    while let Some(captures) = TIMESTAMP.captures(rest) {
+        timestamps.push(timestamp_ms(
+            &captures[1],
+            &captures[2],
+            captures.get(3).map(|m| m.as_str()),
+        ));
+        rest = rest[captures[0].len()..].trim_start();
+    }
+    let mut timestamps: Vec<u64> = timestamps.into_iter().flatten().collect();
 
-        let timestamp_ms = (minutes * 60 * 1000) + (seconds * 1000) + (centiseconds * 10);
+    let words = parse_words(rest, timestamps.first().copied());
+    // Some files only time the words
+    if timestamps.is_empty() {
+        timestamps.extend(words.first().map(|word| word.timestamp_ms));
+    }
 
-        Some(LyricLine {
-            timestamp_ms: Some(timestamp_ms),
-            text: captures[4].to_string(),
-        })
+    if let Some(&first) = timestamps.first() {
+        let text = if words.is_empty() {
+            rest.trim().to_string()
+        } else {
+            words.iter().map(|word| word.text.as_str()).collect()
+        };
+        timestamps
+            .into_iter()
+            .map(|timestamp| LyricLine {
+                timestamp_ms: Some(timestamp),
+                text: text.clone(),
+                // Word times are absolute, move them along with repeats
+                words: words
+                    .iter()
+                    .map(|word| LyricWord {
+                        timestamp_ms: word
+                            .timestamp_ms
+                            .saturating_add_signed(timestamp as i64 - first as i64),
+                        text: word.text.clone(),
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect()
     } else if !line.trim().is_empty() && !line.starts_with('[') {
         // Plain text line without timestamp
-        Some(LyricLine {
+        vec![LyricLine {
             timestamp_ms: None,
             text: line.to_string(),
-        })
+            ..Default::default()
+        }]
     } else {
-        None
+        Vec::new()
     }
 }
 
+/// Split `<00:12.00>Hello <00:12.40>world` into timed words. Text before
+/// the first word timestamp is sung at the start of the line.
+fn parse_words(text: &str, line_start: Option<u64>) -> Vec<LyricWord> {
+    let mut words = Vec::new();
+    let mut current: Option<u64> = None;
+    let mut last_end = 0;
+
+    for captures in WORD_TIMESTAMP.captures_iter(text) {
+        let tag = captures.get(0).unwrap();
+        if let Some(timestamp_ms) = current.or(line_start) {
+            words.push(LyricWord {
+                timestamp_ms,
+                text: text[last_end..tag.start()].to_string(),
+            });
+        }
+        current = timestamp_ms(
+            &captures[1],
+            &captures[2],
+            captures.get(3).map(|m| m.as_str()),
+        );
+        last_end = tag.end();
+    }
+
+    let Some(timestamp_ms) = current else {
+        return Vec::new();
+    };
+    words.push(LyricWord {
+        timestamp_ms,
+        text: text[last_end..].to_string(),
+    });
+
+    // Surrounding spaces don't belong to any word, and a trailing
+    // timestamp only marks where the last word ends
+    if let Some(first) = words.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = words.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    words.retain(|word| !word.text.is_empty());
+    words
+}
+
+fn parse_id_tag(line: &str) -> Option<(String, &str)> {
+    let captures = ID_TAG.captures(line.trim_start())?;
+    let value = captures.get(2)?.as_str().trim();
+    Some((captures[1].to_ascii_lowercase(), value))
+}
+
+fn apply_id_tag(metadata: &mut LyricsMetadata, key: &str, value: &str) {
+    let text = || Some(value.to_string()).filter(|v| !v.is_empty());
+    match key {
+        "ar" => metadata.artist = text(),
+        "ti" => metadata.title = text(),
+        "al" => metadata.album = text(),
+        "au" => metadata.author = text(),
+        "by" => metadata.by = text(),
+        "length" => metadata.length_ms = parse_time(value),
+        "offset" => {
+            metadata.offset_ms = value
+                .trim_start_matches('+')
+                .parse::<i64>()
+                .map_or(0, |offset| offset.clamp(-MAX_OFFSET_MS, MAX_OFFSET_MS))
+        }
+        // [re:], [ve:] and unknown tags
+        _ => {}
+    }
+}
+
+/// `mm:ss.xx` without brackets, as used by `[length:]`
+fn parse_time(value: &str) -> Option<u64> {
+    let captures = TIME.captures(value)?;
+    timestamp_ms(
+        &captures[1],
+        &captures[2],
+        captures.get(3).map(|m| m.as_str()),
+    )
+}
+
+/// Milliseconds from minutes, seconds and a 1-3 digit fraction
+fn timestamp_ms(minutes: &str, seconds: &str, fraction: Option<&str>) -> Option<u64> {
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: u64 = seconds.parse().ok()?;
+    let fraction_ms = match fraction {
+        // .5 is 500ms, .05 is 50ms, .005 is 5ms
+        Some(digits) => digits.parse::<u64>().ok()? * 10u64.pow(3 - digits.len() as u32),
+        None => 0,
+    };
+
+    // Absurd minute counts are dropped instead of overflowing
+    minutes
+        .checked_mul(60_000)?
+        .checked_add(seconds * 1000 + fraction_ms)
+}
+
+/// `[mm:ss.xx]` for a time in milliseconds
+pub fn format_timestamp(timestamp_ms: u64) -> String {
+    let centiseconds = timestamp_ms / 10;
+    format!(
+        "[{:02}:{:02}.{:02}]",
+        centiseconds / 6000,
+        centiseconds / 100 % 60,
+        centiseconds % 100
+    )
+}
+
 pub fn find_current_line(lyrics: &ParsedLyrics, position_ms: u64) -> Option<usize> {
     if !lyrics.is_synced {
         return None;
@@ -65,14 +269,53 @@ pub fn find_current_line(lyrics: &ParsedLyrics, position_ms: u64) -> Option<usiz
     current_index
 }
 
+/// The gap being played at `position_ms`, when it is long enough to be
+/// worth showing. Blank lines right after each other count as one gap.
+pub fn find_gap(lyrics: &ParsedLyrics, position_ms: u64) -> Option<LyricsGap> {
+    let is_blank = |line: &LyricLine| line.text.trim().is_empty() && line.secondary.is_none();
+    let current = find_current_line(lyrics, position_ms);
+    if !lyrics.is_synced || current.is_some_and(|index| !is_blank(&lyrics.lines[index])) {
+        return None;
+    }
+
+    // Back to where the singing stopped, or the start of the song
+    let start_ms = match current {
+        Some(index) => {
+            let first_blank = lyrics.lines[..index]
+                .iter()
+                .rposition(|line| !is_blank(line))
+                .map_or(0, |sung| sung + 1);
+            lyrics.lines[first_blank].timestamp_ms?
+        }
+        None => 0,
+    };
+    let (next_line, next) = lyrics
+        .lines
+        .iter()
+        .enumerate()
+        .skip(current.map_or(0, |index| index + 1))
+        .find(|(_, line)| !is_blank(line))?;
+    let end_ms = next.timestamp_ms?;
+
+    (end_ms.saturating_sub(start_ms) >= MIN_GAP_MS).then_some(LyricsGap {
+        start_ms,
+        end_ms,
+        next_line,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn first_line(line: &str) -> Option<LyricLine> {
+        parse_lrc_line(line).into_iter().next()
+    }
+
     #[test]
     fn test_parse_lrc_line() {
         let line = "[01:23.45] Test lyrics";
-        let parsed = parse_lrc_line(line).unwrap();
+        let parsed = first_line(line).unwrap();
         assert_eq!(parsed.timestamp_ms, Some(83450));
         assert_eq!(parsed.text, "Test lyrics");
     }
@@ -80,7 +323,7 @@ mod tests {
     #[test]
     fn test_parse_plain_line() {
         let line = "Plain lyrics without timestamp";
-        let parsed = parse_lrc_line(line).unwrap();
+        let parsed = first_line(line).unwrap();
         assert_eq!(parsed.timestamp_ms, None);
         assert_eq!(parsed.text, "Plain lyrics without timestamp");
     }
@@ -88,25 +331,25 @@ mod tests {
     #[test]
     fn test_parse_lrc_line_various_timestamps() {
         // Test zero timestamp
-        let parsed = parse_lrc_line("[00:00.00] Start").unwrap();
+        let parsed = first_line("[00:00.00] Start").unwrap();
         assert_eq!(parsed.timestamp_ms, Some(0));
         assert_eq!(parsed.text, "Start");
 
         // Test single digit seconds
-        let parsed = parse_lrc_line("[00:05.50] Five seconds").unwrap();
+        let parsed = first_line("[00:05.50] Five seconds").unwrap();
         assert_eq!(parsed.timestamp_ms, Some(5500));
 
         // Test maximum valid values
-        let parsed = parse_lrc_line("[99:59.99] Max time").unwrap();
+        let parsed = first_line("[99:59.99] Max time").unwrap();
         assert_eq!(parsed.timestamp_ms, Some(5999990));
 
         // Test with extra spaces
-        let parsed = parse_lrc_line("[01:30.00]     Spaced text").unwrap();
+        let parsed = first_line("[01:30.00]     Spaced text").unwrap();
         assert_eq!(parsed.timestamp_ms, Some(90000));
         assert_eq!(parsed.text, "Spaced text");
 
         // Test empty text after timestamp
-        let parsed = parse_lrc_line("[01:00.00]").unwrap();
+        let parsed = first_line("[01:00.00]").unwrap();
         assert_eq!(parsed.timestamp_ms, Some(60000));
         assert_eq!(parsed.text, "");
     }
@@ -114,26 +357,190 @@ mod tests {
     #[test]
     fn test_parse_lrc_line_invalid_formats() {
         // Missing closing bracket
-        assert!(parse_lrc_line("[01:23.45 Test").is_none());
+        assert!(first_line("[01:23.45 Test").is_none());
 
         // Invalid timestamp format
-        assert!(parse_lrc_line("[1:23.45] Test").is_none());
-        assert!(parse_lrc_line("[01:2.45] Test").is_none());
-        assert!(parse_lrc_line("[01:23.4] Test").is_none());
+        assert!(first_line("[01:2.45] Test").is_none());
+        assert!(first_line("[01:23.4567] Test").is_none());
 
         // Non-numeric values
-        assert!(parse_lrc_line("[aa:bb.cc] Test").is_none());
+        assert!(first_line("[aa:bb.cc] Test").is_none());
 
         // Empty line
-        assert!(parse_lrc_line("").is_none());
+        assert!(first_line("").is_none());
 
         // Just whitespace
-        assert!(parse_lrc_line("   ").is_none());
+        assert!(first_line("   ").is_none());
 
         // Metadata tags (should be ignored)
-        assert!(parse_lrc_line("[ar:Artist Name]").is_none());
-        assert!(parse_lrc_line("[ti:Song Title]").is_none());
-        assert!(parse_lrc_line("[al:Album Name]").is_none());
+        assert!(first_line("[ar:Artist Name]").is_none());
+        assert!(first_line("[ti:Song Title]").is_none());
+        assert!(first_line("[al:Album Name]").is_none());
+    }
+
+    #[test]
+    fn test_parse_lrc_line_timestamp_variants() {
+        let cases = [
+            ("[1:23.45] Short minutes", 83_450),
+            ("[01:23.456] Milliseconds", 83_456),
+            ("[01:23.4] Tenths", 83_400),
+            ("[01:23] No fraction", 83_000),
+            ("[01:23:45] Colon before the fraction", 83_450),
+            ("[120:00.00] Long mixes", 7_200_000),
+        ];
+
+        for (input, expected_ms) in cases {
+            let parsed = first_line(input).unwrap();
+            assert_eq!(parsed.timestamp_ms, Some(expected_ms), "Failed for {input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_lrc_line_repeated_timestamps() {
+        let lines = parse_lrc_line("[00:12.00][01:30.00] [02:45.50]Chorus");
+        let timestamps: Vec<_> = lines.iter().map(|line| line.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![Some(12_000), Some(90_000), Some(165_500)]);
+        assert!(lines.iter().all(|line| line.text == "Chorus"));
+    }
+
+    #[test]
+    fn test_parse_lrc_line_word_timing() {
+        let lines = parse_lrc_line("[00:12.00]<00:12.00>Hello <00:12.40>world<00:13.10>");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].timestamp_ms, Some(12_000));
+        assert_eq!(lines[0].text, "Hello world");
+        assert_eq!(
+            lines[0].words,
+            vec![
+                LyricWord {
+                    timestamp_ms: 12_000,
+                    text: "Hello ".to_string()
+                },
+                LyricWord {
+                    timestamp_ms: 12_400,
+                    text: "world".to_string()
+                },
+            ]
+        );
+
+        // Untimed lead-in text starts with the line, words-only lines take
+        // their start from the first word
+        let lines = parse_lrc_line("[00:05.00] Oh, <00:05.50>yeah");
+        assert_eq!(lines[0].words[0].timestamp_ms, 5_000);
+        assert_eq!(lines[0].words[0].text, "Oh, ");
+        let lines = parse_lrc_line("<00:07.00>Only <00:07.30>words");
+        assert_eq!(lines[0].timestamp_ms, Some(7_000));
+        assert_eq!(lines[0].text, "Only words");
+
+        // Plain lines have no words
+        assert!(parse_lrc_line("[00:01.00]Plain")
+            .pop()
+            .unwrap()
+            .words
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_lrc_word_timing_with_repeats_and_offset() {
+        let parsed = parse_lrc("[offset:100]\n[00:10.00][01:10.00]<00:10.00>La <00:10.50>la");
+        assert_eq!(parsed.lines.len(), 2);
+
+        let word_times = |line: &LyricLine| -> Vec<u64> {
+            line.words.iter().map(|word| word.timestamp_ms).collect()
+        };
+        assert_eq!(word_times(&parsed.lines[0]), vec![9_900, 10_400]);
+        assert_eq!(word_times(&parsed.lines[1]), vec![69_900, 70_400]);
+    }
+
+    #[test]
+    fn test_parse_lrc_translations() {
+        let parsed = parse_lrc(
+            "[00:05.00]君の名前を\n[00:05.00]Your name\n[00:09.00]\n[00:09.00]Eu sei\n\
+             [00:12.00]サヨナラ\n[00:12.00]Goodbye\n[00:12.00]Adeus\n[00:15.00]Alone",
+        );
+        let lines: Vec<_> = parsed
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.timestamp_ms,
+                    line.text.as_str(),
+                    line.secondary.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (Some(5_000), "君の名前を", Some("Your name")),
+                // An empty line clears the screen, it doesn't pair up
+                (Some(9_000), "", None),
+                (Some(9_000), "Eu sei", None),
+                (Some(12_000), "サヨナラ", Some("Goodbye / Adeus")),
+                (Some(15_000), "Alone", None),
+            ]
+        );
+
+        // Plain lyrics have no timestamps to pair on
+        let plain = parse_lrc("Same\nSame");
+        assert!(plain.lines.iter().all(|line| line.secondary.is_none()));
+    }
+
+    #[test]
+    fn test_parse_lrc_metadata() {
+        let lrc_content = "\u{feff}[ar: Daft Punk]
+[ti:One More Time]
+[al:Discovery]
+[au:Thomas Bangalter]
+[by:lrc maker]
+[length: 05:20.50]
+[re:Some Editor]
+[00:01.00]One more time";
+
+        let parsed = parse_lrc(lrc_content);
+        assert_eq!(
+            parsed.metadata,
+            LyricsMetadata {
+                artist: Some("Daft Punk".to_string()),
+                title: Some("One More Time".to_string()),
+                album: Some("Discovery".to_string()),
+                author: Some("Thomas Bangalter".to_string()),
+                by: Some("lrc maker".to_string()),
+                length_ms: Some(320_500),
+                offset_ms: 0,
+            }
+        );
+        assert_eq!(parsed.lines.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_lrc_offset() {
+        // Positive offsets make lyrics appear sooner, the tag may come last
+        let parsed = parse_lrc("[00:00.10]First\n[00:10.00][00:20.00]Again\n[offset:+250]");
+        let timestamps: Vec<_> = parsed.lines.iter().map(|l| l.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![Some(0), Some(9_750), Some(19_750)]);
+        assert_eq!(parsed.metadata.offset_ms, 250);
+
+        let parsed = parse_lrc("[offset:-500]\n[00:10.00]Later");
+        assert_eq!(parsed.lines[0].timestamp_ms, Some(10_500));
+
+        // Garbage offsets are ignored
+        let parsed = parse_lrc("[offset:soon]\n[00:10.00]Same");
+        assert_eq!(parsed.lines[0].timestamp_ms, Some(10_000));
+
+        // Huge offsets are clamped instead of overflowing
+        let parsed = parse_lrc("[offset:-9223372036854775808]\n[00:10.00]Never");
+        assert_eq!(parsed.metadata.offset_ms, -MAX_OFFSET_MS);
+        assert_eq!(
+            parsed.lines[0].timestamp_ms,
+            Some(10_000 + MAX_OFFSET_MS as u64)
+        );
+
+        // So are timestamps too big to count in milliseconds
+        let parsed = parse_lrc("[length:9999999999999999:00]\n[9999999999999999:00]x\n[00:01.00]y");
+        assert_eq!(parsed.metadata.length_ms, None);
+        let timestamps: Vec<_> = parsed.lines.iter().map(|l| l.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![Some(1_000)]);
     }
 
     #[test]
@@ -222,6 +629,19 @@ Unsynced line
         assert_eq!(parsed.lines[2].timestamp_ms, None); // Unsynced sorted to end
     }
 
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "[00:00.00]");
+        assert_eq!(format_timestamp(83_456), "[01:23.45]");
+        assert_eq!(format_timestamp(7_200_000), "[120:00.00]");
+        assert_eq!(
+            first_line(&format!("{}x", format_timestamp(83_450)))
+                .unwrap()
+                .timestamp_ms,
+            Some(83_450)
+        );
+    }
+
     #[test]
     fn test_find_current_line() {
         let lyrics = ParsedLyrics {
@@ -230,20 +650,25 @@ Unsynced line
                 LyricLine {
                     timestamp_ms: Some(0),
                     text: "Line 1".to_string(),
+                    ..Default::default()
                 },
                 LyricLine {
                     timestamp_ms: Some(5000),
                     text: "Line 2".to_string(),
+                    ..Default::default()
                 },
                 LyricLine {
                     timestamp_ms: Some(10000),
                     text: "Line 3".to_string(),
+                    ..Default::default()
                 },
                 LyricLine {
                     timestamp_ms: Some(15000),
                     text: "Line 4".to_string(),
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         };
 
         // Before first line
@@ -262,6 +687,41 @@ Unsynced line
         assert_eq!(find_current_line(&lyrics, 20000), Some(3));
     }
 
+    #[test]
+    fn test_find_gap() {
+        let lyrics = parse_lrc(
+            "[00:12.00]Last night\n[00:15.00]I had a dream\n[00:18.00]\n[00:20.00]\n\
+             [00:40.00]Digital love\n[00:42.00]\n[00:44.00]Oh\n[00:50.00]",
+        );
+
+        // The intro, until the first line
+        let intro = LyricsGap {
+            start_ms: 0,
+            end_ms: 12_000,
+            next_line: 0,
+        };
+        assert_eq!(find_gap(&lyrics, 3_000), Some(intro));
+        assert_eq!(intro.remaining_ms(3_000), 9_000);
+        assert_eq!(find_gap(&lyrics, 13_000), None);
+
+        // Both blank lines make up one break
+        let break_gap = Some(LyricsGap {
+            start_ms: 18_000,
+            end_ms: 40_000,
+            next_line: 4,
+        });
+        assert_eq!(find_gap(&lyrics, 18_500), break_gap);
+        assert_eq!(find_gap(&lyrics, 30_000), break_gap);
+
+        // Too short to bother, and nothing to count down to at the end
+        assert_eq!(find_gap(&lyrics, 42_500), None);
+        assert_eq!(find_gap(&lyrics, 55_000), None);
+
+        // A song that starts singing right away has no intro
+        assert_eq!(find_gap(&parse_lrc("[00:01.00]Hi"), 500), None);
+        assert_eq!(find_gap(&parse_lrc("No timestamps"), 500), None);
+    }
+
     #[test]
     fn test_find_current_line_unsynced() {
         let lyrics = ParsedLyrics {
@@ -270,12 +730,15 @@ Unsynced line
                 LyricLine {
                     timestamp_ms: None,
                     text: "Line 1".to_string(),
+                    ..Default::default()
                 },
                 LyricLine {
                     timestamp_ms: None,
                     text: "Line 2".to_string(),
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         };
 
         // Should always return None for unsynced lyrics
@@ -288,6 +751,7 @@ Unsynced line
         let lyrics = ParsedLyrics {
             is_synced: true,
             lines: vec![],
+            ..Default::default()
         };
 
         // Should return None for empty lyrics
@@ -307,7 +771,7 @@ Unsynced line
 
         for (input, expected_ms) in test_cases {
             let line = format!("{input} Text");
-            let parsed = parse_lrc_line(&line).unwrap();
+            let parsed = first_line(&line).unwrap();
             assert_eq!(
                 parsed.timestamp_ms,
                 Some(expected_ms),
@@ -0,0 +1,205 @@
+//! Sync recorder: turns plain lyrics into LRC by stamping each line with
+//! the playback position while the song plays
+
+use super::source::TrackQuery;
+use super::{parser, LyricLine, ParsedLyrics};
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncRecorder {
+    lines: Vec<String>,
+    secondary: Vec<Option<String>>, // translations, kept alongside each line
+    stamps: Vec<u64>,               // one per line stamped so far, in milliseconds
+}
+
+impl SyncRecorder {
+    /// Record the lines of `lyrics`, blank lines between verses are skipped
+    pub fn new(lyrics: &ParsedLyrics) -> Self {
+        let (lines, secondary) = lyrics
+            .lines
+            .iter()
+            .filter(|line| !line.text.trim().is_empty())
+            .map(|line| (line.text.trim().to_string(), line.secondary.clone()))
+            .unzip();
+        Self {
+            lines,
+            secondary,
+            stamps: Vec::new(),
+        }
+    }
+
+    /// Stamp the next line, returns false once every line has its timestamp.
+    /// Stamps never go backwards, even if the player was seeked.
+    pub fn stamp(&mut self, position_ms: u64) -> bool {
+        if self.is_complete() {
+            return false;
+        }
+        let last = self.stamps.last().copied().unwrap_or(0);
+        self.stamps.push(position_ms.max(last));
+        true
+    }
+
+    /// Take back the last stamp
+    pub fn undo(&mut self) -> bool {
+        self.stamps.pop().is_some()
+    }
+
+    /// Index of the line the next stamp goes to
+    pub fn next_line(&self) -> Option<usize> {
+        (!self.is_complete()).then_some(self.stamps.len())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.stamps.len() >= self.lines.len()
+    }
+
+    pub fn stamped(&self) -> usize {
+        self.stamps.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Every line, with the timestamps recorded so far
+    pub fn lines(&self) -> impl Iterator<Item = (Option<u64>, &str)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(index, text)| (self.stamps.get(index).copied(), text.as_str()))
+    }
+
+    /// The stamped lines as synced lyrics, unstamped lines are left out
+    pub fn to_parsed(&self) -> ParsedLyrics {
+        let lines = self
+            .lines()
+            .zip(&self.secondary)
+            .filter_map(|((timestamp, text), secondary)| {
+                timestamp.map(|timestamp| LyricLine {
+                    timestamp_ms: Some(timestamp),
+                    text: text.to_string(),
+                    secondary: secondary.clone(),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        ParsedLyrics {
+            lines,
+            is_synced: true,
+            ..Default::default()
+        }
+    }
+
+    /// LRC file with the track's ID tags and the stamped lines
+    pub fn to_lrc(&self, query: &TrackQuery) -> String {
+        let mut lrc = String::new();
+        if !query.artist.is_empty() {
+            lrc.push_str(&format!("[ar:{}]\n", query.artist));
+        }
+        if !query.title.is_empty() {
+            lrc.push_str(&format!("[ti:{}]\n", query.title));
+        }
+        if let Some(ref album) = query.album {
+            lrc.push_str(&format!("[al:{album}]\n"));
+        }
+        if let Some(duration) = query.duration {
+            let seconds = duration.as_secs();
+            lrc.push_str(&format!(
+                "[length:{:02}:{:02}]\n",
+                seconds / 60,
+                seconds % 60
+            ));
+        }
+        lrc.push_str("[by:trackwatch]\n");
+
+        for ((timestamp, text), secondary) in self.lines().zip(&self.secondary) {
+            let Some(timestamp) = timestamp else {
+                continue;
+            };
+            let timestamp = parser::format_timestamp(timestamp);
+            lrc.push_str(&format!("{timestamp}{text}\n"));
+            // The translation goes on a line with the same timestamp
+            if let Some(secondary) = secondary {
+                lrc.push_str(&format!("{timestamp}{secondary}\n"));
+            }
+        }
+        lrc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn recorder() -> SyncRecorder {
+        let plain = parser::parse_lrc("One more time\n\nWe're gonna celebrate\nOh yeah");
+        assert!(!plain.is_synced);
+        SyncRecorder::new(&plain)
+    }
+
+    #[test]
+    fn test_stamp_and_undo() {
+        let mut recorder = recorder();
+        assert_eq!(recorder.len(), 3);
+        assert_eq!(recorder.next_line(), Some(0));
+
+        assert!(recorder.stamp(1_000));
+        assert!(recorder.stamp(4_000));
+        assert!(recorder.undo());
+        assert_eq!(recorder.next_line(), Some(1));
+
+        // A seek backwards does not reorder the lines
+        assert!(recorder.stamp(500));
+        assert!(recorder.stamp(6_500));
+        assert!(recorder.is_complete());
+        assert!(!recorder.stamp(9_000));
+        assert_eq!(recorder.next_line(), None);
+
+        let parsed = recorder.to_parsed();
+        assert!(parsed.is_synced);
+        let stamps: Vec<_> = parsed.lines.iter().map(|l| l.timestamp_ms).collect();
+        assert_eq!(stamps, vec![Some(1_000), Some(1_000), Some(6_500)]);
+        assert_eq!(parsed.lines[1].text, "We're gonna celebrate");
+    }
+
+    #[test]
+    fn test_translations_are_kept() {
+        let synced = parser::parse_lrc("[00:01.00]사랑해\n[00:01.00]I love you\n[00:03.00]Oh");
+        let mut recorder = SyncRecorder::new(&synced);
+        recorder.stamp(1_500);
+
+        let lrc = recorder.to_lrc(&TrackQuery::default());
+        assert!(lrc.ends_with("[00:01.50]사랑해\n[00:01.50]I love you\n"));
+        let parsed = recorder.to_parsed();
+        assert_eq!(parsed.lines[0].secondary.as_deref(), Some("I love you"));
+    }
+
+    #[test]
+    fn test_to_lrc_round_trip() {
+        let mut recorder = recorder();
+        recorder.stamp(1_230);
+        recorder.stamp(65_000);
+
+        let query = TrackQuery {
+            artist: "Daft Punk".to_string(),
+            title: "One More Time".to_string(),
+            album: Some("Discovery".to_string()),
+            duration: Some(Duration::from_secs(320)),
+            ..Default::default()
+        };
+        let lrc = recorder.to_lrc(&query);
+        assert!(lrc.starts_with("[ar:Daft Punk]\n[ti:One More Time]\n[al:Discovery]\n"));
+        assert!(!lrc.contains("Oh yeah"));
+
+        let parsed = parser::parse_lrc(&lrc);
+        assert!(parsed.is_synced);
+        assert_eq!(parsed.metadata.length_ms, Some(320_000));
+        assert_eq!(parsed.lines.len(), 2);
+        assert_eq!(parsed.lines[0].timestamp_ms, Some(1_230));
+        assert_eq!(parsed.lines[1].text, "We're gonna celebrate");
+    }
+}
@@ -0,0 +1,279 @@
+//! Romanization of kana (Hepburn) and hangul (Revised Romanization). Kanji
+//! need a dictionary and are left as they are.
+
+const HANGUL_START: u32 = 0xAC00;
+const HANGUL_END: u32 = 0xD7A3;
+
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+const MEDIALS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+// Final consonants as they sound at the end of a syllable
+const FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+// The silent ㅇ initial that lets a final consonant carry over
+const SILENT_INITIAL: u32 = 11;
+
+/// The line in Latin letters, `None` when there is no kana or hangul in it
+pub fn romanize(text: &str) -> Option<String> {
+    if !text.chars().any(|c| is_kana(c) || is_hangul(c)) {
+        return None;
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut romanized = String::new();
+    let mut double_next = false; // after a small tsu
+    for (index, &c) in chars.iter().enumerate() {
+        if is_hangul(c) {
+            romanized.push_str(&hangul_syllable(c, chars.get(index + 1).copied()));
+            continue;
+        }
+
+        let Some(kana) = to_hiragana(c) else {
+            romanized.push(c);
+            continue;
+        };
+        match kana {
+            'っ' => double_next = true,
+            'ー' => {
+                // Long vowel mark repeats the vowel before it
+                if let Some(vowel) = romanized.chars().last().filter(|c| is_vowel(*c)) {
+                    romanized.push(vowel);
+                }
+            }
+            'ゃ' | 'ゅ' | 'ょ' => {
+                let vowel = &kana_syllable(kana).unwrap_or_default()[1..];
+                if romanized.ends_with('i') && romanized.len() > 1 {
+                    romanized.pop();
+                    // shi + ya is sha, ki + ya is kya
+                    if !(romanized.ends_with("sh")
+                        || romanized.ends_with("ch")
+                        || romanized.ends_with('j'))
+                    {
+                        romanized.push('y');
+                    }
+                    romanized.push_str(vowel);
+                } else {
+                    romanized.push_str(kana_syllable(kana).unwrap_or_default());
+                }
+            }
+            'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => {
+                let vowel = kana_syllable(kana).unwrap_or_default();
+                // fu + a is fa, te + i is ti, u + i is wi
+                match romanized.chars().last() {
+                    Some('u') if ends_with_bare_u(&romanized) => {
+                        romanized.pop();
+                        romanized.push('w');
+                    }
+                    Some(last) if is_vowel(last) => {
+                        romanized.pop();
+                    }
+                    _ => {}
+                }
+                romanized.push_str(vowel);
+            }
+            _ => match kana_syllable(kana) {
+                Some(syllable) => {
+                    if std::mem::take(&mut double_next) {
+                        if syllable.starts_with("ch") {
+                            romanized.push('t');
+                        } else if let Some(consonant) =
+                            syllable.chars().next().filter(|c| !is_vowel(*c))
+                        {
+                            romanized.push(consonant);
+                        }
+                    }
+                    romanized.push_str(syllable);
+                }
+                None => romanized.push(c),
+            },
+        }
+    }
+    Some(romanized)
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/// A "u" that is a syllable of its own, like ウ in ウィ
+fn ends_with_bare_u(romanized: &str) -> bool {
+    let mut chars = romanized.chars().rev();
+    chars.next() == Some('u') && chars.next().is_none_or(|c| !c.is_ascii_alphabetic())
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}' | '\u{30A1}'..='\u{30FA}' | 'ー')
+}
+
+fn is_hangul(c: char) -> bool {
+    (HANGUL_START..=HANGUL_END).contains(&(c as u32))
+}
+
+/// Katakana folded onto hiragana, which shares its romanization
+fn to_hiragana(c: char) -> Option<char> {
+    match c {
+        '\u{3041}'..='\u{3096}' | 'ー' => Some(c),
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60),
+        _ => None,
+    }
+}
+
+fn kana_syllable(kana: char) -> Option<&'static str> {
+    Some(match kana {
+        'あ' | 'ぁ' => "a",
+        'い' | 'ぃ' => "i",
+        'う' | 'ぅ' => "u",
+        'え' | 'ぇ' => "e",
+        'お' | 'ぉ' => "o",
+        'か' | 'ゕ' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' | 'ゖ' => "ke",
+        'こ' => "ko",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'ざ' => "za",
+        'じ' | 'ぢ' => "ji",
+        'ず' | 'づ' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' | 'ゃ' => "ya",
+        'ゆ' | 'ゅ' => "yu",
+        'よ' | 'ょ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' => "wa",
+        'ゐ' => "wi",
+        'ゑ' => "we",
+        'を' => "o",
+        'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    })
+}
+
+/// One hangul syllable. A final consonant before a vowel is sounded as the
+/// start of the next syllable, 음악 is eumak.
+fn hangul_syllable(syllable: char, next: Option<char>) -> String {
+    let code = syllable as u32 - HANGUL_START;
+    let initial = (code / 588) as usize;
+    let medial = ((code % 588) / 28) as usize;
+    let final_index = (code % 28) as usize;
+
+    let next_is_vowel = next
+        .filter(|c| is_hangul(*c))
+        .is_some_and(|c| (c as u32 - HANGUL_START) / 588 == SILENT_INITIAL);
+    let final_sound = match final_index {
+        1 if next_is_vowel => "g",
+        7 if next_is_vowel => "d",
+        8 if next_is_vowel => "r",
+        17 if next_is_vowel => "b",
+        19 if next_is_vowel => "s",
+        20 if next_is_vowel => "ss",
+        22 if next_is_vowel => "j",
+        23 if next_is_vowel => "ch",
+        25 if next_is_vowel => "t",
+        26 if next_is_vowel => "p",
+        _ => FINALS[final_index],
+    };
+
+    format!("{}{}{final_sound}", INITIALS[initial], MEDIALS[medial])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_romanize_kana() {
+        let cases = [
+            ("さくら", "sakura"),
+            ("きゃりーぱみゅぱみゅ", "kyariipamyupamyu"),
+            ("しゃしん", "shashin"),
+            ("ちょっと", "chotto"),
+            ("まっちゃ", "matcha"),
+            ("ラーメン", "raamen"),
+            ("ファイト", "faito"),
+            ("ウィンター", "wintaa"),
+            ("パーティー", "paatii"),
+            ("君の名は", "君no名ha"),
+            ("Hello ジャパン!", "Hello japan!"),
+        ];
+        for (kana, expected) in cases {
+            assert_eq!(romanize(kana).as_deref(), Some(expected), "{kana}");
+        }
+    }
+
+    #[test]
+    fn test_romanize_hangul() {
+        let cases = [
+            ("사랑해", "saranghae"),
+            ("안녕하세요", "annyeonghaseyo"),
+            ("음악", "eumak"),
+            ("한국어", "hangugeo"),
+            ("빛", "bit"),
+            ("너를 사랑해", "neoreul saranghae"),
+        ];
+        for (hangul, expected) in cases {
+            assert_eq!(romanize(hangul).as_deref(), Some(expected), "{hangul}");
+        }
+    }
+
+    #[test]
+    fn test_nothing_to_romanize() {
+        assert_eq!(romanize("Eu sei que vou te amar"), None);
+        assert_eq!(romanize("名前"), None);
+        assert_eq!(romanize(""), None);
+    }
+}
@@ -0,0 +1,346 @@
+//! Lyrics sources and the ordered chain that asks them in turn
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{cache::LyricsCache, parser, LyricsResponse, ParsedLyrics};
+use crate::normalize::CanonicalTrack;
+use crate::player::PlayerMetadata;
+
+/// Source name of lyrics picked by hand in the candidate picker
+pub const PINNED_SOURCE: &str = "pinned";
+
+/// What a lyrics source gets to look up a track
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackQuery {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+    pub local_path: Option<PathBuf>, // the audio file, when it's on this machine
+}
+
+impl TrackQuery {
+    /// Query for the playing track, with the noise taken out of the
+    /// artist and title
+    pub fn from_metadata(metadata: &PlayerMetadata) -> Self {
+        let track = CanonicalTrack::from_metadata(metadata);
+        Self {
+            artist: track.artist,
+            title: track.title,
+            album: metadata.album.clone(),
+            duration: metadata.length,
+            local_path: metadata.local_path(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait LyricsSource: Send + Sync {
+    /// Short name used in `TRACKWATCH_LYRICS_SOURCES`, the cache and the UI
+    fn name(&self) -> &'static str;
+
+    async fn fetch(&self, query: &TrackQuery) -> Result<Option<LyricsResponse>>;
+
+    /// Whether answers are worth caching. Local files are cheap to read
+    /// again and may be edited at any time.
+    fn cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// Wrap lyrics text read from a file as a response, synced when it has
+/// timestamps. `None` when there are no lyrics in it.
+pub fn response_from_text(query: &TrackQuery, text: String) -> Option<LyricsResponse> {
+    let parsed = parser::parse_lrc(&text);
+    if parsed.lines.is_empty() {
+        return None;
+    }
+
+    let (synced_lyrics, plain_lyrics) = if parsed.is_synced {
+        (Some(text), None)
+    } else {
+        (None, Some(text))
+    };
+    Some(LyricsResponse {
+        id: 0,
+        name: query.title.clone(),
+        track_name: query.title.clone(),
+        artist_name: query.artist.clone(),
+        album_name: query.album.clone(),
+        duration: query.duration.map(|duration| duration.as_secs_f64()),
+        instrumental: false,
+        plain_lyrics,
+        synced_lyrics,
+    })
+}
+
+/// Lyrics and the source that found them
+#[derive(Debug, Clone)]
+pub struct FoundLyrics {
+    pub source: String,
+    pub response: LyricsResponse,
+}
+
+impl FoundLyrics {
+    pub fn parse(&self) -> Option<ParsedLyrics> {
+        if self.response.instrumental {
+            return Some(ParsedLyrics {
+                instrumental: true,
+                source: Some(self.source.clone()),
+                ..Default::default()
+            });
+        }
+        let mut parsed = parser::parse_lrc(self.response.get_best_lyrics()?);
+        parsed.source = Some(self.source.clone());
+        Some(parsed)
+    }
+}
+
+/// Asks each source in order. The first synced lyrics win, plain lyrics
+/// are kept in case nothing later has timestamps.
+#[derive(Clone)]
+pub struct LyricsChain {
+    sources: Vec<Arc<dyn LyricsSource>>,
+    cache: Option<LyricsCache>,
+}
+
+impl LyricsChain {
+    pub fn new(sources: Vec<Arc<dyn LyricsSource>>, cache: Option<LyricsCache>) -> Self {
+        Self { sources, cache }
+    }
+
+    pub fn source_names(&self) -> Vec<&'static str> {
+        self.sources.iter().map(|source| source.name()).collect()
+    }
+
+    pub async fn fetch(&self, query: &TrackQuery) -> Option<FoundLyrics> {
+        // A pick made by hand beats every source
+        if let Some(response) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get_pinned(&query.artist, &query.title))
+        {
+            return Some(FoundLyrics {
+                source: PINNED_SOURCE.to_string(),
+                response,
+            });
+        }
+
+        let mut fallback = None;
+
+        for source in &self.sources {
+            let Some(response) = self.fetch_from(source.as_ref(), query).await else {
+                continue;
+            };
+            // An instrumental track is an answer too, there is nothing to sing
+            if response.get_best_lyrics().is_none() && !response.instrumental {
+                continue;
+            }
+
+            let found = FoundLyrics {
+                source: source.name().to_string(),
+                response,
+            };
+            if found.response.has_synced_lyrics() {
+                return Some(found);
+            }
+            fallback.get_or_insert(found);
+        }
+
+        fallback
+    }
+
+    async fn fetch_from(
+        &self,
+        source: &dyn LyricsSource,
+        query: &TrackQuery,
+    ) -> Option<LyricsResponse> {
+        let cache = self.cache.as_ref().filter(|_| source.cacheable());
+        if let Some(cached) =
+            cache.and_then(|cache| cache.get(source.name(), &query.artist, &query.title))
+        {
+            return cached;
+        }
+
+        match source.fetch(query).await {
+            Ok(response) => {
+                if let Some(cache) = cache {
+                    let _ = cache.set(
+                        source.name(),
+                        &query.artist,
+                        &query.title,
+                        response.as_ref(),
+                    );
+                }
+                response
+            }
+            Err(e) => {
+                // Not cached, the next track change tries again
+                eprintln!("Failed to fetch lyrics from {}: {e}", source.name());
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub(crate) fn response(synced: Option<&str>, plain: Option<&str>) -> LyricsResponse {
+        LyricsResponse {
+            id: 0,
+            name: "Song".to_string(),
+            track_name: "Song".to_string(),
+            artist_name: "Artist".to_string(),
+            album_name: None,
+            duration: None,
+            instrumental: false,
+            plain_lyrics: plain.map(str::to_string),
+            synced_lyrics: synced.map(str::to_string),
+        }
+    }
+
+    /// Gives the same answer every time and counts how often it was asked
+    struct FakeSource {
+        name: &'static str,
+        answer: Result<Option<LyricsResponse>, String>,
+        calls: AtomicUsize,
+    }
+
+    impl FakeSource {
+        fn new(
+            name: &'static str,
+            answer: Result<Option<LyricsResponse>, String>,
+        ) -> Arc<FakeSource> {
+            Arc::new(Self {
+                name,
+                answer,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LyricsSource for FakeSource {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn fetch(&self, _query: &TrackQuery) -> Result<Option<LyricsResponse>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.answer.clone().map_err(|e| anyhow!(e))
+        }
+    }
+
+    fn query() -> TrackQuery {
+        TrackQuery {
+            artist: "Artist".to_string(),
+            title: "Song".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_synced_result_wins() {
+        let plain = FakeSource::new("plain", Ok(Some(response(None, Some("Words")))));
+        let failing = FakeSource::new("failing", Err("offline".to_string()));
+        let synced = FakeSource::new("synced", Ok(Some(response(Some("[00:01.00]A"), None))));
+        let later = FakeSource::new("later", Ok(Some(response(Some("[00:01.00]B"), None))));
+        let chain = LyricsChain::new(
+            vec![plain.clone(), failing.clone(), synced, later.clone()],
+            None,
+        );
+
+        let found = chain.fetch(&query()).await.unwrap();
+        assert_eq!(found.source, "synced");
+        assert_eq!(found.parse().unwrap().source.as_deref(), Some("synced"));
+        assert_eq!(plain.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(failing.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(later.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_plain_lyrics_fallback() {
+        let empty = FakeSource::new("empty", Ok(None));
+        let instrumental = FakeSource::new("instrumental", Ok(Some(response(None, None))));
+        let plain = FakeSource::new("plain", Ok(Some(response(None, Some("Words")))));
+        let other = FakeSource::new("other", Ok(Some(response(None, Some("Other")))));
+        let chain = LyricsChain::new(vec![empty, instrumental, plain, other], None);
+
+        let found = chain.fetch(&query()).await.unwrap();
+        assert_eq!(found.source, "plain");
+        assert!(!found.parse().unwrap().is_synced);
+
+        let chain = LyricsChain::new(vec![FakeSource::new("empty", Ok(None))], None);
+        assert!(chain.fetch(&query()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_instrumental_answer() {
+        let instrumental = LyricsResponse {
+            instrumental: true,
+            ..response(None, None)
+        };
+        let chain = LyricsChain::new(
+            vec![
+                FakeSource::new("lrclib", Ok(Some(instrumental))),
+                FakeSource::new("other", Ok(None)),
+            ],
+            None,
+        );
+
+        let parsed = chain.fetch(&query()).await.unwrap().parse().unwrap();
+        assert!(parsed.instrumental);
+        assert!(parsed.lines.is_empty());
+        assert_eq!(parsed.source.as_deref(), Some("lrclib"));
+    }
+
+    #[tokio::test]
+    async fn test_answers_are_cached_per_source() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-lyrics-chain-{}", std::process::id()));
+        let cache = LyricsCache::with_dir(cache_dir.clone()).unwrap();
+        let missing = FakeSource::new("missing", Ok(None));
+        let online = FakeSource::new("online", Ok(Some(response(Some("[00:01.00]A"), None))));
+        let chain = LyricsChain::new(vec![missing.clone(), online.clone()], Some(cache));
+
+        assert_eq!(chain.fetch(&query()).await.unwrap().source, "online");
+        assert_eq!(chain.fetch(&query()).await.unwrap().source, "online");
+        // "Not found" is remembered too
+        assert_eq!(missing.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(online.calls.load(Ordering::SeqCst), 1);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pinned_lyrics_win() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-lyrics-pin-{}", std::process::id()));
+        let cache = LyricsCache::with_dir(cache_dir.clone()).unwrap();
+        let online = FakeSource::new("online", Ok(Some(response(Some("[00:01.00]A"), None))));
+        let chain = LyricsChain::new(vec![online.clone()], Some(cache.clone()));
+
+        let pinned = response(None, Some("The right song"));
+        cache.set_pinned("Artist", "Song", Some(&pinned)).unwrap();
+        let found = chain.fetch(&query()).await.unwrap();
+        assert_eq!(found.source, PINNED_SOURCE);
+        assert_eq!(
+            found.response.plain_lyrics.as_deref(),
+            Some("The right song")
+        );
+        assert_eq!(online.calls.load(Ordering::SeqCst), 0);
+
+        cache.set_pinned("Artist", "Song", None).unwrap();
+        assert_eq!(chain.fetch(&query()).await.unwrap().source, "online");
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::lyrics::api::LrcLibClient;
+use crate::lyrics::cache::LyricsCache;
+use crate::lyrics::embedded::TagLyrics;
+use crate::lyrics::local::LocalLyrics;
+use crate::lyrics::source::{LyricsChain, LyricsSource};
+
+/// Files next to the music first, the network last
+pub const DEFAULT_LYRICS_SOURCES: [&str; 3] = ["local", "embedded", "lrclib"];
+
+/// Build the lyrics chain from the sources named in the config
+pub fn create_lyrics_chain(config: &Config) -> Result<LyricsChain> {
+    // Lyrics still work without a cache, just slower
+    Ok(LyricsChain::new(
+        lyrics_sources(config)?,
+        LyricsCache::new().ok(),
+    ))
+}
+
+/// The sources named in the config, in that order
+fn lyrics_sources(config: &Config) -> Result<Vec<Arc<dyn LyricsSource>>> {
+    let names: Vec<&str> = if config.lyrics_sources.is_empty() {
+        DEFAULT_LYRICS_SOURCES.to_vec()
+    } else {
+        config.lyrics_sources.iter().map(String::as_str).collect()
+    };
+
+    names
+        .into_iter()
+        .map(|name| -> Result<Arc<dyn LyricsSource>> {
+            match name {
+                "local" => Ok(Arc::new(LocalLyrics::with_default_dirs(
+                    &config.lyrics_dirs,
+                ))),
+                "embedded" => Ok(Arc::new(TagLyrics)),
+                "lrclib" => Ok(Arc::new(LrcLibClient::new())),
+                other => Err(anyhow!("Unknown lyrics source: {}", other)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Without a cache, so tests don't write to $HOME
+    fn chain(config: &Config) -> Result<LyricsChain> {
+        Ok(LyricsChain::new(lyrics_sources(config)?, None))
+    }
+
+    #[test]
+    fn test_default_chain() {
+        let chain = chain(&Config::default()).unwrap();
+        assert_eq!(chain.source_names(), DEFAULT_LYRICS_SOURCES);
+    }
+
+    #[test]
+    fn test_configured_order() {
+        let config = Config {
+            lyrics_sources: vec!["lrclib".to_string(), "local".to_string()],
+            ..Default::default()
+        };
+        let chain = chain(&config).unwrap();
+        assert_eq!(chain.source_names(), vec!["lrclib", "local"]);
+    }
+
+    #[test]
+    fn test_unknown_source() {
+        let config = Config {
+            lyrics_sources: vec!["genius".to_string()],
+            ..Default::default()
+        };
+        let error = chain(&config).err().unwrap();
+        assert_eq!(error.to_string(), "Unknown lyrics source: genius");
+    }
+}
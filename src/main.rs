@@ -1,222 +1,208 @@
 use anyhow::Result;
+use clap::Parser;
 use std::{
-    sync::{Arc, Mutex},
+    sync::{mpsc, Arc, Mutex},
     thread,
     time::Duration,
 };
 
 use trackwatch::{
+    cli::Cli,
     colors::ColorPalette,
     config::Config,
     display::{DisplayFormatter, PixelatedImage, RatatuiImage},
-    lyrics::{api::LrcLibClient, cache::LyricsCache, parser},
-    models::AlbumMetadata,
-    player::{self, PlayerMetadata},
+    lyrics::{cache::LyricsCache, local::LocalLyrics, source::TrackQuery},
+    lyrics_factory::create_lyrics_chain,
+    models::{AlbumMetadata, TrackMetadata},
+    normalize::CanonicalTrack,
+    player::{
+        selection::PlayerSelector,
+        watcher::{PlayerUpdate, TrackWatcher},
+        PlayerCommand, PlayerEvent, PlayerMetadata,
+    },
+    player_factory::create_player_backend,
     provider_factory::create_tidal_provider,
     providers::MusicProvider,
     ui::{self, App},
 };
 
 const IMAGE_SIZE: u32 = 30; // 30x30 pixels like the Go version
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// While following player events, still re-read the player now and then in
+// case a notification got lost
+const RESYNC_INTERVAL: Duration = Duration::from_secs(5);
+// Players tend to send several notifications for one change
+const EVENT_SETTLE_DELAY: Duration = Duration::from_millis(50);
 
 fn main() -> Result<()> {
     // Load .env file if it exists
     dotenv::dotenv().ok();
 
-    // Check if playerctl is installed first
-    if !player::is_playerctl_installed() {
-        println!("playerctl is not installed. Please install playerctl to use this application.");
-        return Ok(());
-    }
+    // Load configuration, command line options take precedence
+    let mut config = Config::from_env();
+    Cli::parse().apply(&mut config);
 
-    // Load configuration
-    let config = Config::from_env();
+    // Pick the player backend (MPRIS over D-Bus by default, playerctl as fallback)
+    let backend = match create_player_backend(&config) {
+        Ok(backend) => backend,
+        Err(e) => {
+            println!("Failed to start player backend: {e}");
+            return Ok(());
+        }
+    };
+
+    let lyrics_chain = match create_lyrics_chain(&config) {
+        Ok(chain) => chain,
+        Err(e) => {
+            println!("Failed to set up lyrics sources: {e}");
+            return Ok(());
+        }
+    };
 
     // Setup terminal
     let mut terminal = ui::setup_terminal()?;
 
     // Create app state
-    let app = Arc::new(Mutex::new(App::new()));
+    let mut app = App::new();
+    app.lyrics_cache = LyricsCache::new().ok();
+    app.local_lyrics = LocalLyrics::with_default_dirs(&config.lyrics_dirs);
+    app.show_romanization = config.romanize;
+    let app = Arc::new(Mutex::new(app));
     let app_clone = Arc::clone(&app);
 
+    // Player notifications and UI commands both wake the background thread
+    let (event_sender, events) = mpsc::channel();
+    let ui_commands = event_sender.clone();
+
     // Spawn background thread for fetching player data
     thread::spawn(move || {
-        let mut provider = create_tidal_provider(&config);
-        let formatter = DisplayFormatter::new(IMAGE_SIZE);
+        let provider =
+            create_tidal_provider(&config).map(|provider| Arc::new(Mutex::new(provider)));
+        let formatter = Arc::new(DisplayFormatter::new(IMAGE_SIZE));
+        let (lookup_sender, provider_lookups) = mpsc::channel();
 
-        // Initialize lyrics components
+        // Lyrics and provider lookups run on a runtime of their own
         let runtime = tokio::runtime::Runtime::new().unwrap();
-        let lyrics_client = LrcLibClient::new();
-        let lyrics_cache = LyricsCache::new().unwrap();
 
-        let mut last_track: Option<PlayerMetadata> = None;
+        let selector = PlayerSelector::new(config.player.clone(), config.player_priority.clone());
+        let mut watcher = TrackWatcher::with_selector(backend, selector);
+
+        // Follow player events when the backend supports it, poll otherwise
+        let mut following = !config.poll_only
+            && match watcher.subscribe(event_sender.clone()) {
+                Ok(following) => following,
+                Err(e) => {
+                    eprintln!("{} backend cannot push events: {e}", watcher.backend_name());
+                    false
+                }
+            };
+
         let mut cached_album_metadata: Option<AlbumMetadata> = None;
+        let mut cached_track_metadata: Option<TrackMetadata> = None;
         let mut cached_album_art: Option<PixelatedImage> = None;
         let mut cached_album_art_ratatui: Option<RatatuiImage> = None;
         let mut cached_color_palette: Option<ColorPalette> = None;
-        let mut last_position: Option<Duration> = None;
 
         loop {
-            // Get current track metadata from playerctl
-            match player::get_current_track() {
-                Ok(player_metadata) => {
-                    // Check if track changed
-                    let track_changed = match &last_track {
-                        None => true,
-                        Some(last) => {
-                            last.artist != player_metadata.artist
-                                || last.title != player_metadata.title
-                                || last.album != player_metadata.album
-                        }
-                    };
-
+            // Get current track metadata from the player backend
+            match watcher.poll() {
+                PlayerUpdate::Playing {
+                    metadata: player_metadata,
+                    track_changed,
+                } => {
                     if track_changed {
-                        // Try to get album metadata from Tidal if available
-                        cached_album_metadata = if let (Some(album), Some(ref mut provider)) =
-                            (player_metadata.album.as_ref(), provider.as_mut())
-                        {
-                            match provider.get_album_metadata(&player_metadata.artist, album) {
-                                Ok(metadata) => {
-                                    // Also fetch album art
-                                    if let Some(ref cover_url) = metadata.cover_url {
-                                        match formatter.fetch_and_process_all_formats(cover_url) {
-                                            Ok((pixelated, ratatui, colors)) => {
-                                                cached_album_art = Some(pixelated);
-                                                cached_album_art_ratatui = Some(ratatui);
-                                                cached_color_palette = Some(colors);
-                                            }
-                                            Err(e) => {
-                                                eprintln!("Failed to fetch album art: {e}");
-                                                cached_album_art = None;
-                                                cached_album_art_ratatui = None;
-                                                cached_color_palette = None;
-                                            }
-                                        }
-                                    }
-                                    Some(metadata)
-                                }
-                                Err(_) => {
-                                    // Silently fail - we'll use playerctl data
-                                    // Try to use playerctl album art as fallback
-                                    if let Some(ref art_url) = player_metadata.art_url {
-                                        match formatter.fetch_and_process_all_formats(art_url) {
-                                            Ok((pixelated, ratatui, colors)) => {
-                                                cached_album_art = Some(pixelated);
-                                                cached_album_art_ratatui = Some(ratatui);
-                                                cached_color_palette = Some(colors);
-                                            }
-                                            Err(e) => {
-                                                eprintln!(
-                                                    "Failed to fetch playerctl album art: {e}"
-                                                );
-                                                cached_album_art = None;
-                                                cached_album_art_ratatui = None;
-                                                cached_color_palette = None;
-                                            }
-                                        }
-                                    } else {
-                                        cached_album_art = None;
-                                        cached_album_art_ratatui = None;
-                                        cached_color_palette = None;
-                                    }
-
-                                    None
-                                }
-                            }
-                        } else {
-                            // No album info from playerctl, but try to get art anyway
-                            if let Some(ref art_url) = player_metadata.art_url {
-                                match formatter.fetch_and_process_all_formats(art_url) {
-                                    Ok((pixelated, ratatui, colors)) => {
-                                        cached_album_art = Some(pixelated);
-                                        cached_album_art_ratatui = Some(ratatui);
-                                        cached_color_palette = Some(colors);
-                                    }
-                                    Err(e) => {
-                                        eprintln!("Failed to fetch playerctl album art: {e}");
-                                        cached_album_art = None;
-                                        cached_album_art_ratatui = None;
-                                        cached_color_palette = None;
-                                    }
-                                }
-                            } else {
-                                cached_album_art = None;
-                                cached_album_art_ratatui = None;
-                                cached_color_palette = None;
-                            }
-                            None
-                        };
-
                         // Fetch lyrics for the new track
                         let app_for_loading = Arc::clone(&app_clone);
-                        let artist = player_metadata.artist.clone();
-                        let title = player_metadata.title.clone();
-                        let lyrics_client_clone = lyrics_client.clone();
-                        let lyrics_cache_clone = lyrics_cache.clone();
+                        let lyrics_chain_clone = lyrics_chain.clone();
+                        let query = TrackQuery::from_metadata(&player_metadata);
 
                         // Set loading state
-                        if let Ok(mut app) = app_for_loading.lock() {
-                            app.set_lyrics_loading(true);
-                        }
+                        let request = match app_for_loading.lock() {
+                            Ok(mut app) => {
+                                app.set_lyrics_loading(true);
+                                app.set_lyrics_track(&query.artist, &query.title);
+                                app.lyrics_request
+                            }
+                            Err(_) => 0,
+                        };
 
                         // Spawn async task for lyrics fetching
                         runtime.spawn(async move {
-                            // Check cache first
-                            if let Some(cached_response) = lyrics_cache_clone.get(&artist, &title) {
-                                if let Some(response) = cached_response {
-                                    if let Some(lyrics_text) = response.get_best_lyrics() {
-                                        let parsed = parser::parse_lrc(lyrics_text);
-                                        if let Ok(mut app) = app_for_loading.lock() {
-                                            app.update_lyrics(Some(parsed));
-                                        }
-                                    }
-                                }
-                                return;
+                            let parsed = lyrics_chain_clone
+                                .fetch(&query)
+                                .await
+                                .and_then(|found| found.parse());
+                            if let Ok(mut app) = app_for_loading.lock() {
+                                app.update_lyrics(request, parsed);
                             }
+                        });
 
-                            // Fetch from API
-                            match lyrics_client_clone.get_best_match(&title, &artist).await {
-                                Ok(Some(response)) => {
-                                    // Cache the response
-                                    let _ =
-                                        lyrics_cache_clone.set(&artist, &title, Some(&response));
-
-                                    if let Some(lyrics_text) = response.get_best_lyrics() {
-                                        let parsed = parser::parse_lrc(lyrics_text);
-                                        if let Ok(mut app) = app_for_loading.lock() {
-                                            app.update_lyrics(Some(parsed));
-                                        }
-                                    }
-                                }
-                                Ok(None) => {
-                                    // Cache "not found" result
-                                    let _ = lyrics_cache_clone.set(&artist, &title, None);
-                                    if let Ok(mut app) = app_for_loading.lock() {
-                                        app.update_lyrics(None);
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to fetch lyrics: {e}");
-                                    if let Ok(mut app) = app_for_loading.lock() {
-                                        app.update_lyrics(None);
-                                    }
+                        // Album art from the player until the provider finds better
+                        (
+                            cached_album_art,
+                            cached_album_art_ratatui,
+                            cached_color_palette,
+                        ) = fetch_player_art(&formatter, &mut watcher, &player_metadata);
+                        cached_album_metadata = None;
+                        cached_track_metadata = None;
+
+                        // Ask Tidal about the album and track if it is configured.
+                        // Its calls block, so they run off this thread.
+                        if let Some(ref provider) = provider {
+                            let provider = Arc::clone(provider);
+                            let formatter = Arc::clone(&formatter);
+                            let lookups = lookup_sender.clone();
+                            let wake = event_sender.clone();
+                            let track = player_metadata.clone();
+                            runtime.spawn_blocking(move || {
+                                let Ok(mut provider) = provider.lock() else {
+                                    return;
+                                };
+                                let lookup = lookup_provider(&mut *provider, &formatter, track);
+                                if lookups.send(lookup).is_ok() {
+                                    let _ = wake.send(PlayerEvent::Changed);
                                 }
-                            }
-                        });
+                            });
+                        }
+                    }
+
+                    // Provider lookups finish in the background, keep the one for this track
+                    for lookup in provider_lookups.try_iter() {
+                        if !lookup.track.is_same_track(&player_metadata) {
+                            continue;
+                        }
+                        cached_album_metadata = lookup.album;
+                        cached_track_metadata = lookup.details;
+                        if let Some((pixelated, ratatui, colors)) = lookup.art {
+                            cached_album_art = Some(pixelated);
+                            cached_album_art_ratatui = Some(ratatui);
+                            cached_color_palette = Some(colors);
+                        }
                     }
 
                     // Handle position and progress based on play state
                     let (position, progress) =
                         if player_metadata.status.as_deref() == Some("Playing") {
                             // Update position and calculate progress when playing
-                            last_position = player_metadata.position;
                             (
                                 player_metadata.position,
                                 player_metadata.get_progress_percentage().unwrap_or(0.0),
                             )
                         } else {
-                            // When paused, use last known position
-                            let frozen_position = last_position.or(player_metadata.position);
+                            // When paused, keep the position the local clock reached.
+                            // The last player sample can be seconds old in follow mode,
+                            // but a seek made while paused is taken as is.
+                            let clock_position = if track_changed {
+                                None
+                            } else {
+                                app_clone.lock().ok().and_then(|app| {
+                                    match player_metadata.position {
+                                        Some(sampled) if app.clock.is_seek(sampled) => None,
+                                        _ => app.current_position(),
+                                    }
+                                })
+                            };
+                            let frozen_position = clock_position.or(player_metadata.position);
                             let frozen_progress = if let (Some(pos), Some(len)) =
                                 (frozen_position, player_metadata.length)
                             {
@@ -244,50 +230,73 @@ fn main() -> Result<()> {
                             progress,
                             cached_color_palette.clone(),
                         );
+                        app.track_metadata = cached_track_metadata.clone();
                     }
-
-                    last_track = Some(player_metadata);
                 }
-                Err(_) => {
-                    if last_track.is_some() {
-                        // Clear app state when player stops
-                        if let Ok(mut app) = app_clone.lock() {
-                            app.waiting_for_player = true;
-                            app.album_art = None;
-                            app.album_art_ratatui = None;
-                            app.album_metadata = None;
-                            app.player_metadata = PlayerMetadata {
-                                artist: String::new(),
-                                title: String::new(),
-                                album: None,
-                                position: None,
-                                length: None,
-                                streaming_source: None,
-                                art_url: None,
-                                status: None,
+                PlayerUpdate::Stopped => {
+                    // Clear app state when player stops
+                    if let Ok(mut app) = app_clone.lock() {
+                        app.waiting_for_player = true;
+                        app.album_art = None;
+                        app.album_art_ratatui = None;
+                        app.album_metadata = None;
+                        app.track_metadata = None;
+                        app.player_metadata = PlayerMetadata::default();
+                        app.progress = 0.0;
+                        app.clock.reset();
+                        app.color_palette = None;
+                        app.lyrics = None;
+                        app.lyrics_track = None;
+                        app.lyrics_request += 1;
+                        app.lyrics_offset_ms = 0;
+                        app.recorder = None;
+                    }
+                    cached_album_metadata = None;
+                    cached_track_metadata = None;
+                    cached_album_art = None;
+                    cached_album_art_ratatui = None;
+                    cached_color_palette = None;
+                }
+                PlayerUpdate::Idle => {
+                    // Nothing to do - it's normal when no player is running
+                }
+            }
+
+            // Wait for a change notification, or poll again once the timeout expires.
+            // The UI advances the position on its own in between.
+            let timeout = if following {
+                RESYNC_INTERVAL
+            } else {
+                POLL_INTERVAL
+            };
+            if let Ok(event) = events.recv_timeout(timeout) {
+                thread::sleep(EVENT_SETTLE_DELAY);
+
+                // Coalesce the burst into a single poll
+                for event in std::iter::once(event).chain(events.try_iter()) {
+                    match event {
+                        PlayerEvent::Changed => {}
+                        PlayerEvent::SubscriptionEnded => following = false,
+                        PlayerEvent::Command(PlayerCommand::CyclePlayer) => {
+                            watcher.cycle_player();
+                        }
+                        PlayerEvent::Command(PlayerCommand::Control(control)) => {
+                            let feedback = match watcher.control(control) {
+                                Ok(()) => control.describe(),
+                                Err(e) => format!("{} failed: {e}", control.describe()),
                             };
-                            app.progress = 0.0;
-                            app.color_palette = None;
-                            app.lyrics = None;
+                            if let Ok(mut app) = app_clone.lock() {
+                                app.show_feedback(feedback);
+                            }
                         }
-                        last_track = None;
-                        cached_album_metadata = None;
-                        cached_album_art = None;
-                        cached_album_art_ratatui = None;
-                        cached_color_palette = None;
-                        last_position = None;
                     }
-                    // Don't print error message - it's normal when no player is running
                 }
             }
-
-            // Sleep for 500ms before next update
-            thread::sleep(Duration::from_millis(500));
         }
     });
 
     // Run the UI
-    let res = ui::run_app(&mut terminal, app);
+    let res = ui::run_app(&mut terminal, app, ui_commands);
 
     // Restore terminal
     ui::restore_terminal(&mut terminal)?;
@@ -298,3 +307,94 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// What the provider found for a track
+struct ProviderLookup {
+    track: PlayerMetadata, // the track it was looked up for
+    album: Option<AlbumMetadata>,
+    details: Option<TrackMetadata>,
+    art: Option<(PixelatedImage, RatatuiImage, ColorPalette)>,
+}
+
+/// Album and track details from the provider, with the album's cover art.
+/// The track is found without an album tag, and its album stands in when
+/// the tag finds nothing.
+fn lookup_provider(
+    provider: &mut impl MusicProvider,
+    formatter: &DisplayFormatter,
+    track: PlayerMetadata,
+) -> ProviderLookup {
+    // Same cleaned up names the lyrics are looked up with
+    let canonical = CanonicalTrack::from_metadata(&track);
+    let details =
+        match provider.get_track_metadata(&canonical.artist, &canonical.title, track.length) {
+            Ok(mut details) => {
+                // Credits are extra, the track is shown without them
+                details.credits = provider.get_credits(&details.id).unwrap_or_default();
+                Some(details)
+            }
+            Err(_) => None, // the player's data is used instead
+        };
+
+    let album = track
+        .album
+        .as_ref()
+        .and_then(|album| provider.get_album_metadata(&canonical.artist, album).ok())
+        .or_else(|| details.as_ref().and_then(|details| details.album.clone()));
+
+    let art = album
+        .as_ref()
+        .and_then(|album| album.cover_url.as_ref())
+        .and_then(
+            |cover_url| match formatter.fetch_and_process_all_formats(cover_url) {
+                Ok(art) => Some(art),
+                Err(e) => {
+                    eprintln!("Failed to fetch album art: {e}");
+                    None
+                }
+            },
+        );
+
+    ProviderLookup {
+        track,
+        album,
+        details,
+        art,
+    }
+}
+
+/// Album art from the player itself: cover bytes when the backend hands them
+/// out (MPD), otherwise the `art_url` from the track metadata
+fn fetch_player_art(
+    formatter: &DisplayFormatter,
+    watcher: &mut TrackWatcher,
+    metadata: &PlayerMetadata,
+) -> (
+    Option<PixelatedImage>,
+    Option<RatatuiImage>,
+    Option<ColorPalette>,
+) {
+    let processed = match watcher.cover_art() {
+        Ok(Some(bytes)) => Some(formatter.process_image_bytes(&bytes)),
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Failed to read cover art from the player: {e}");
+            None
+        }
+    }
+    .or_else(|| {
+        metadata
+            .art_url
+            .as_ref()
+            .map(|art_url| formatter.fetch_and_process_all_formats(art_url))
+    });
+
+    match processed {
+        Some(Ok((pixelated, ratatui, colors))) => (Some(pixelated), Some(ratatui), Some(colors)),
+        Some(Err(e)) => {
+            eprintln!("Failed to fetch player album art: {e}");
+            (None, None, None)
+        }
+        None => (None, None, None),
+    }
+}
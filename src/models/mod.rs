@@ -21,6 +21,51 @@ pub struct ArtistInfo {
     pub name: String,
 }
 
+/// One recording, found by artist and title when the player has no album tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackMetadata {
+    pub id: String,
+    pub title: String,
+    pub artists: Vec<ArtistInfo>,
+    pub album: Option<AlbumMetadata>, // the release it was found on
+    pub track_number: Option<u32>,
+    pub volume_number: Option<u32>, // disc
+    pub duration: Option<u32>,      // seconds
+    pub isrc: Option<String>,
+    pub explicit: bool,
+    pub popularity: Option<f64>, // 0.0-1.0
+    pub credits: Vec<Credit>,    // filled in by get_credits
+}
+
+/// What the provider knows about an artist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtistDetails {
+    pub id: String,
+    pub name: String,
+    pub popularity: Option<f64>, // 0.0-1.0
+}
+
+/// Everyone credited with one role on a track
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credit {
+    pub role: String, // "Producer", "Composer", "Lyricist"...
+    pub names: Vec<String>,
+}
+
+impl TrackMetadata {
+    /// Names credited with any of `roles`, in the provider's order
+    pub fn credited(&self, roles: &[&str]) -> Vec<&Credit> {
+        self.credits
+            .iter()
+            .filter(|credit| {
+                roles
+                    .iter()
+                    .any(|role| credit.role.eq_ignore_ascii_case(role))
+            })
+            .collect()
+    }
+}
+
 impl AlbumMetadata {
     /// Parse ISO 8601 duration string (PT3M45S) to seconds
     pub fn parse_iso8601_duration(iso_duration: &str) -> u32 {
@@ -242,6 +287,39 @@ mod tests {
         assert_eq!(empty.all_artists(), "Unknown Artist");
     }
 
+    #[test]
+    fn test_credited() {
+        let credit = |role: &str, name: &str| Credit {
+            role: role.to_string(),
+            names: vec![name.to_string()],
+        };
+        let track = TrackMetadata {
+            id: "1".to_string(),
+            title: "Digital Love".to_string(),
+            artists: Vec::new(),
+            album: None,
+            track_number: Some(3),
+            volume_number: Some(1),
+            duration: Some(301),
+            isrc: Some("GBDUW0000053".to_string()),
+            explicit: false,
+            popularity: None,
+            credits: vec![
+                credit("Composer", "Thomas Bangalter"),
+                credit("Mixing Engineer", "Nilesh Patel"),
+                credit("Producer", "Daft Punk"),
+            ],
+        };
+
+        let shown: Vec<&str> = track
+            .credited(&["producer", "Composer"])
+            .iter()
+            .map(|credit| credit.role.as_str())
+            .collect();
+        assert_eq!(shown, vec!["Composer", "Producer"]);
+        assert!(track.credited(&["Lyricist"]).is_empty());
+    }
+
     #[test]
     fn test_album_metadata_creation() {
         let album = AlbumMetadata {
@@ -0,0 +1,607 @@
+//! Turning what players report ("Artist - Song (Official Video) [4K]",
+//! "Song - Remastered 2011", "Song (feat. X)") into something lyrics and
+//! metadata searches can find
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::player::PlayerMetadata;
+
+// (feat. X), [ft. X], (with X)
+static BRACKETED_FEATURE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\s*[(\[]\s*(?:feat\.?|ft\.?|featuring|with)\s+([^)\]]+)[)\]]").unwrap()
+});
+
+// Song feat. X, without brackets, up to a dash, a bracket or the end
+static TRAILING_FEATURE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\s+(?:feat\.?|ft\.?|featuring)\s+([^(\[]+?)(\s+[-–—]\s+.*|\s*[(\[].*)?$")
+        .unwrap()
+});
+
+static BRACKET_GROUP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*(?:\([^)]*\)|\[[^\]]*\]|\{[^}]*\})").unwrap());
+
+// Words that mark a bracket group or dash suffix as being about the
+// recording or the upload rather than the song
+const NOISE_WORDS: &[&str] = &[
+    "official",
+    "video",
+    "audio",
+    "lyric",
+    "lyrics",
+    "visualizer",
+    "visualiser",
+    "hd",
+    "hq",
+    "4k",
+    "mv",
+    "remaster",
+    "remastered",
+    "live",
+    "explicit",
+    "clean",
+    "mono",
+    "stereo",
+    "version",
+    "edit",
+];
+
+// Only stripped from " - suffixes" together with noise words, "Radio Edit",
+// "2011 Remaster"
+const FILLER_WORDS: &[&str] = &[
+    "radio", "single", "album", "original", "digital", "bonus", "track", "deluxe", "edition",
+];
+
+// Uploader names that are a channel rather than the artist
+const CHANNEL_WORDS: &[&str] = &["vevo", "official", "records", "recordings", "tv", "channel"];
+
+const DASHES: [&str; 3] = [" - ", " – ", " — "];
+
+/// A track with the noise taken out of its artist and title
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CanonicalTrack {
+    pub artist: String,
+    pub title: String,
+    pub featured: Vec<String>, // featured artists taken out of the title or artist
+}
+
+impl CanonicalTrack {
+    /// Clean up what the player reports. Web video titles are assumed to
+    /// be "Artist - Title" uploaded by a channel.
+    pub fn from_metadata(metadata: &PlayerMetadata) -> Self {
+        let is_video = metadata.streaming_source.as_deref() == Some("YouTube");
+        Self::clean(&metadata.artist, &metadata.title, is_video)
+    }
+
+    pub fn new(artist: &str, title: &str) -> Self {
+        Self::clean(artist, title, false)
+    }
+
+    fn clean(artist: &str, title: &str, is_video: bool) -> Self {
+        let mut featured = Vec::new();
+        let artist = artist.trim();
+        let artist = artist.strip_suffix(" - Topic").unwrap_or(artist);
+        let mut artist = take_features(artist, &mut featured);
+        let mut title = strip_noise(&take_features(&strip_noise(title), &mut featured));
+
+        // "Artist - Title" uploaded by a channel, or with the artist repeated
+        if let Some((left, right)) = split_dash(&title).filter(|(_, right)| !is_noise_suffix(right))
+        {
+            if is_video || is_channel_name(&artist) || same_words(left, &artist) {
+                artist = take_features(left, &mut featured);
+                title = strip_noise(&take_features(right, &mut featured));
+            }
+        }
+
+        Self {
+            artist,
+            title,
+            featured,
+        }
+    }
+
+    /// (artist, title) pairs to search with, strictest first: the cleaned
+    /// names, then only the first of several artists, then the title
+    /// without anything in brackets or after a dash
+    pub fn query_variants(&self) -> Vec<(String, String)> {
+        let primary = primary_artist(&self.artist);
+        let bare_title = strip_brackets(&self.title);
+        let short_title = split_dash(&bare_title)
+            .map(|(left, _)| left.to_string())
+            .unwrap_or_else(|| bare_title.clone());
+        dedup(vec![
+            (self.artist.clone(), self.title.clone()),
+            (primary.clone(), self.title.clone()),
+            (primary.clone(), bare_title),
+            (primary, short_title),
+        ])
+    }
+}
+
+/// (artist, album) pairs to search with, strictest first. Editions,
+/// remasters and volume numbers are dropped in turn.
+pub fn album_query_variants(artist: &str, album: &str) -> Vec<(String, String)> {
+    let track = CanonicalTrack::new(artist, "");
+    let primary = primary_artist(&track.artist);
+    let cleaned = strip_noise(album);
+    let bare = strip_brackets(&cleaned);
+    let without_volume = bare
+        .split(", Vol.")
+        .next()
+        .unwrap_or(&bare)
+        .trim()
+        .to_string();
+    dedup(vec![
+        (artist.trim().to_string(), album.trim().to_string()),
+        (track.artist.clone(), cleaned),
+        (primary.clone(), bare),
+        (primary, without_volume),
+    ])
+}
+
+/// The first of "A & B", "A, B", "A x B" or "A / B"
+pub fn primary_artist(artist: &str) -> String {
+    [", ", " & ", " x ", " X ", " / ", "; "]
+        .iter()
+        .fold(artist, |artist, separator| {
+            artist.split(separator).next().unwrap_or(artist)
+        })
+        .trim()
+        .to_string()
+}
+
+/// Move "feat. X" out of `text` into `featured`
+fn take_features(text: &str, featured: &mut Vec<String>) -> String {
+    let mut names = Vec::new();
+    let text = BRACKETED_FEATURE.replace_all(text, |captures: &regex::Captures| {
+        names.push(captures[1].to_string());
+        ""
+    });
+    let text = TRAILING_FEATURE.replace(&text, |captures: &regex::Captures| {
+        names.push(captures[1].to_string());
+        // Whatever came after the names stays
+        captures
+            .get(2)
+            .map(|rest| rest.as_str().to_string())
+            .unwrap_or_default()
+    });
+
+    for name in names {
+        featured.extend(
+            name.split([',', '&'])
+                .flat_map(|name| name.split(" and "))
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+        );
+    }
+    text.trim().to_string()
+}
+
+/// Drop bracket groups and dash suffixes about the recording or upload,
+/// "(Official Video)", "[4K]", " - Remastered 2011"
+fn strip_noise(text: &str) -> String {
+    let text = BRACKET_GROUP.replace_all(text, |captures: &regex::Captures| {
+        if has_noise_word(&captures[0]) {
+            String::new()
+        } else {
+            captures[0].to_string()
+        }
+    });
+
+    let mut text = text.trim().to_string();
+    while let Some((head, suffix)) = split_last_dash(&text) {
+        if !is_noise_suffix(suffix) {
+            break;
+        }
+        text = head.trim().to_string();
+    }
+    text
+}
+
+/// A dash suffix made only of noise, filler words and years. "Live" is,
+/// "Live Forever" isn't.
+fn is_noise_suffix(suffix: &str) -> bool {
+    has_noise_word(suffix)
+        && words(suffix).all(|word| {
+            NOISE_WORDS.contains(&word.as_str())
+                || FILLER_WORDS.contains(&word.as_str())
+                || word.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+fn strip_brackets(text: &str) -> String {
+    BRACKET_GROUP.replace_all(text, "").trim().to_string()
+}
+
+fn has_noise_word(text: &str) -> bool {
+    words(text).any(|word| NOISE_WORDS.contains(&word.as_str()))
+}
+
+fn is_channel_name(artist: &str) -> bool {
+    let lower = artist.to_lowercase();
+    lower.is_empty()
+        || lower == "unknown artist"
+        || lower.ends_with("vevo")
+        || words(artist).any(|word| CHANNEL_WORDS.contains(&word.as_str()))
+}
+
+fn same_words(a: &str, b: &str) -> bool {
+    words(a).eq(words(b))
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn split_dash(text: &str) -> Option<(&str, &str)> {
+    DASHES
+        .iter()
+        .filter_map(|dash| text.split_once(dash))
+        .min_by_key(|(left, _)| left.len())
+        .map(|(left, right)| (left.trim(), right.trim()))
+        .filter(|(left, right)| !left.is_empty() && !right.is_empty())
+}
+
+fn split_last_dash(text: &str) -> Option<(&str, &str)> {
+    DASHES
+        .iter()
+        .filter_map(|dash| text.rsplit_once(dash))
+        .max_by_key(|(left, _)| left.len())
+}
+
+fn dedup(variants: Vec<(String, String)>) -> Vec<(String, String)> {
+    let mut unique: Vec<(String, String)> = Vec::new();
+    for variant in variants {
+        if !variant.1.is_empty() && !unique.contains(&variant) {
+            unique.push(variant);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Pair = (&'static str, &'static str);
+    // (artist, title, is_video) => (artist, title, featured)
+    type TrackCase = (
+        (&'static str, &'static str, bool),
+        (&'static str, &'static str, &'static [&'static str]),
+    );
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_canonical_track() {
+        let cases: &[TrackCase] = &[
+            // Already clean
+            (
+                ("Daft Punk", "One More Time", false),
+                ("Daft Punk", "One More Time", &[]),
+            ),
+            // Video and upload noise
+            (
+                (
+                    "Rick Astley",
+                    "Never Gonna Give You Up (Official Music Video)",
+                    false,
+                ),
+                ("Rick Astley", "Never Gonna Give You Up", &[]),
+            ),
+            (
+                ("Queen", "Bohemian Rhapsody [4K]", false),
+                ("Queen", "Bohemian Rhapsody", &[]),
+            ),
+            (("Adele", "Hello (Lyrics)", false), ("Adele", "Hello", &[])),
+            (
+                ("Adele", "Hello [Official Audio] (HD)", false),
+                ("Adele", "Hello", &[]),
+            ),
+            (
+                ("Billie Eilish", "bad guy (Visualizer)", false),
+                ("Billie Eilish", "bad guy", &[]),
+            ),
+            // Remasters and live recordings
+            (
+                ("The Beatles", "Let It Be - Remastered 2009", false),
+                ("The Beatles", "Let It Be", &[]),
+            ),
+            (
+                ("Queen", "Somebody to Love - 2011 Remaster", false),
+                ("Queen", "Somebody to Love", &[]),
+            ),
+            (
+                ("Nirvana", "Lithium (Live at Reading 1992)", false),
+                ("Nirvana", "Lithium", &[]),
+            ),
+            (
+                ("Oasis", "Wonderwall - Live", false),
+                ("Oasis", "Wonderwall", &[]),
+            ),
+            (
+                ("Prince", "Purple Rain - Single Version - Remastered", false),
+                ("Prince", "Purple Rain", &[]),
+            ),
+            (
+                ("Kate Bush", "Running Up That Hill - Radio Edit", false),
+                ("Kate Bush", "Running Up That Hill", &[]),
+            ),
+            // Song-defining brackets and suffixes stay
+            (
+                ("Robyn", "Dancing On My Own (Remix)", false),
+                ("Robyn", "Dancing On My Own (Remix)", &[]),
+            ),
+            (
+                ("Daft Punk", "Harder, Better, Faster, Stronger", false),
+                ("Daft Punk", "Harder, Better, Faster, Stronger", &[]),
+            ),
+            (
+                ("Gorillaz", "Clint Eastwood - Ed Case Refix", false),
+                ("Gorillaz", "Clint Eastwood - Ed Case Refix", &[]),
+            ),
+            // Featured artists
+            (
+                ("Daft Punk", "Get Lucky (feat. Pharrell Williams)", false),
+                ("Daft Punk", "Get Lucky", &["Pharrell Williams"]),
+            ),
+            (
+                ("Mark Ronson", "Uptown Funk ft. Bruno Mars", false),
+                ("Mark Ronson", "Uptown Funk", &["Bruno Mars"]),
+            ),
+            (
+                (
+                    "Calvin Harris",
+                    "Feels [feat. Pharrell Williams, Katy Perry & Big Sean]",
+                    false,
+                ),
+                (
+                    "Calvin Harris",
+                    "Feels",
+                    &["Pharrell Williams", "Katy Perry", "Big Sean"],
+                ),
+            ),
+            (
+                ("Eminem feat. Rihanna", "Love The Way You Lie", false),
+                ("Eminem", "Love The Way You Lie", &["Rihanna"]),
+            ),
+            (
+                ("Post Malone", "Sunflower (with Swae Lee)", false),
+                ("Post Malone", "Sunflower", &["Swae Lee"]),
+            ),
+            // Channel uploads
+            (
+                (
+                    "RickAstleyVEVO",
+                    "Rick Astley - Never Gonna Give You Up (Official Video)",
+                    false,
+                ),
+                ("Rick Astley", "Never Gonna Give You Up", &[]),
+            ),
+            (
+                ("Majestic Casual", "Daft Punk - Something About Us", true),
+                ("Daft Punk", "Something About Us", &[]),
+            ),
+            (
+                ("", "Radiohead - Creep", false),
+                ("Radiohead", "Creep", &[]),
+            ),
+            (
+                ("Sub Pop Records", "Fleet Foxes - Mykonos [HD]", false),
+                ("Fleet Foxes", "Mykonos", &[]),
+            ),
+            (
+                (
+                    "Daft Punk",
+                    "Daft Punk - Around the World (Official Audio)",
+                    false,
+                ),
+                ("Daft Punk", "Around the World", &[]),
+            ),
+            (
+                (
+                    "Tame Impala",
+                    "Tame Impala – The Less I Know The Better",
+                    false,
+                ),
+                ("Tame Impala", "The Less I Know The Better", &[]),
+            ),
+            (
+                (
+                    "Dua Lipa",
+                    "Dua Lipa - Levitating feat. DaBaby (Official Music Video)",
+                    true,
+                ),
+                ("Dua Lipa", "Levitating", &["DaBaby"]),
+            ),
+            (
+                ("Genre Channel", "Artist feat. Guest - Song", true),
+                ("Artist", "Song", &["Guest"]),
+            ),
+            // Topic channels have clean titles
+            (
+                ("Daft Punk - Topic", "Veridis Quo", false),
+                ("Daft Punk", "Veridis Quo", &[]),
+            ),
+            // A dash in the title of a real artist isn't a channel split
+            (
+                ("Pink Floyd", "Shine On You Crazy Diamond - Pts. 1-5", false),
+                ("Pink Floyd", "Shine On You Crazy Diamond - Pts. 1-5", &[]),
+            ),
+        ];
+
+        for ((artist, title, is_video), (want_artist, want_title, want_featured)) in cases {
+            let track = CanonicalTrack::clean(artist, title, *is_video);
+            assert_eq!(
+                track,
+                CanonicalTrack {
+                    artist: want_artist.to_string(),
+                    title: want_title.to_string(),
+                    featured: strings(want_featured),
+                },
+                "Failed for {artist:?} / {title:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical_track_is_idempotent() {
+        let track = CanonicalTrack::clean("SomeVEVO", "A & B - Song (Live) [feat. C]", false);
+        assert_eq!(
+            CanonicalTrack::new(&track.artist, &track.title).title,
+            track.title
+        );
+        assert_eq!(
+            CanonicalTrack::new(&track.artist, &track.title).artist,
+            track.artist
+        );
+    }
+
+    #[test]
+    fn test_from_metadata_uses_youtube_source() {
+        let metadata = PlayerMetadata {
+            artist: "Lofi Girl".to_string(),
+            title: "Nujabes - Aruarian Dance".to_string(),
+            streaming_source: Some("YouTube".to_string()),
+            ..Default::default()
+        };
+        let track = CanonicalTrack::from_metadata(&metadata);
+        assert_eq!(
+            (track.artist.as_str(), track.title.as_str()),
+            ("Nujabes", "Aruarian Dance")
+        );
+
+        // Elsewhere the dash is part of the title
+        let metadata = PlayerMetadata {
+            streaming_source: Some("Spotify".to_string()),
+            ..metadata
+        };
+        assert_eq!(
+            CanonicalTrack::from_metadata(&metadata).title,
+            "Nujabes - Aruarian Dance"
+        );
+    }
+
+    #[test]
+    fn test_query_variants() {
+        let cases: &[(Pair, &[Pair])] = &[
+            (
+                ("Daft Punk", "One More Time"),
+                &[("Daft Punk", "One More Time")],
+            ),
+            (
+                ("Simon & Garfunkel", "The Boxer"),
+                &[("Simon & Garfunkel", "The Boxer"), ("Simon", "The Boxer")],
+            ),
+            (
+                ("Robyn", "Dancing On My Own (Remix)"),
+                &[
+                    ("Robyn", "Dancing On My Own (Remix)"),
+                    ("Robyn", "Dancing On My Own"),
+                ],
+            ),
+            (
+                ("Calvin Harris, Dua Lipa", "One Kiss (Extended Mix)"),
+                &[
+                    ("Calvin Harris, Dua Lipa", "One Kiss (Extended Mix)"),
+                    ("Calvin Harris", "One Kiss (Extended Mix)"),
+                    ("Calvin Harris", "One Kiss"),
+                ],
+            ),
+            (
+                ("Queen", "Love of My Life - Live at Rock in Rio"),
+                &[
+                    ("Queen", "Love of My Life - Live at Rock in Rio"),
+                    ("Queen", "Love of My Life"),
+                ],
+            ),
+        ];
+
+        for ((artist, title), expected) in cases {
+            let variants = CanonicalTrack::new(artist, title).query_variants();
+            let expected: Vec<(String, String)> = expected
+                .iter()
+                .map(|(artist, title)| (artist.to_string(), title.to_string()))
+                .collect();
+            assert_eq!(variants, expected, "Failed for {artist:?} / {title:?}");
+        }
+    }
+
+    #[test]
+    fn test_album_query_variants() {
+        let cases: &[(Pair, &[Pair])] = &[
+            (
+                ("Radiohead", "OK Computer"),
+                &[("Radiohead", "OK Computer")],
+            ),
+            (
+                (
+                    "Pink Floyd",
+                    "The Dark Side of the Moon (50th Anniversary Remastered Deluxe Edition)",
+                ),
+                &[
+                    (
+                        "Pink Floyd",
+                        "The Dark Side of the Moon (50th Anniversary Remastered Deluxe Edition)",
+                    ),
+                    ("Pink Floyd", "The Dark Side of the Moon"),
+                ],
+            ),
+            (
+                ("Queen", "Greatest Hits, Vol. 2 (Deluxe)"),
+                &[
+                    ("Queen", "Greatest Hits, Vol. 2 (Deluxe)"),
+                    ("Queen", "Greatest Hits, Vol. 2"),
+                    ("Queen", "Greatest Hits"),
+                ],
+            ),
+            (
+                ("Daft Punk - Topic", "Discovery"),
+                &[
+                    ("Daft Punk - Topic", "Discovery"),
+                    ("Daft Punk", "Discovery"),
+                ],
+            ),
+            (
+                ("Nick Cave & The Bad Seeds", "Let Love In - 2011 Remaster"),
+                &[
+                    ("Nick Cave & The Bad Seeds", "Let Love In - 2011 Remaster"),
+                    ("Nick Cave & The Bad Seeds", "Let Love In"),
+                    ("Nick Cave", "Let Love In"),
+                ],
+            ),
+        ];
+
+        for ((artist, album), expected) in cases {
+            let expected: Vec<(String, String)> = expected
+                .iter()
+                .map(|(artist, album)| (artist.to_string(), album.to_string()))
+                .collect();
+            assert_eq!(
+                album_query_variants(artist, album),
+                expected,
+                "Failed for {artist:?} / {album:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_primary_artist() {
+        let cases = [
+            ("Daft Punk", "Daft Punk"),
+            ("Simon & Garfunkel", "Simon"),
+            ("Calvin Harris, Dua Lipa", "Calvin Harris"),
+            ("Skrillex x Diplo", "Skrillex"),
+            ("AC/DC", "AC/DC"),
+            ("Earth, Wind & Fire", "Earth"),
+        ];
+        for (artist, expected) in cases {
+            assert_eq!(primary_artist(artist), expected, "Failed for {artist}");
+        }
+    }
+}
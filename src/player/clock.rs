@@ -0,0 +1,228 @@
+use std::time::{Duration, Instant};
+
+// Samples further than this from the extrapolated position are seeks
+const SEEK_THRESHOLD: Duration = Duration::from_secs(1);
+// Smaller differences are sampling noise (process spawn, D-Bus round trip)
+// and are ignored so the position never stutters backwards
+const JITTER_TOLERANCE: Duration = Duration::from_millis(150);
+
+/// Cheap local clock that extrapolates the playback position between player
+/// samples, so the UI does not have to query the player to advance.
+#[derive(Debug, Clone)]
+pub struct PlaybackClock {
+    anchor: Option<(Duration, Instant)>, // last sampled position and when it was taken
+    playing: bool,
+    rate: f64,
+}
+
+impl Default for PlaybackClock {
+    fn default() -> Self {
+        Self {
+            anchor: None,
+            playing: false,
+            rate: 1.0,
+        }
+    }
+}
+
+impl PlaybackClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a position sample from the player
+    pub fn sync(&mut self, position: Option<Duration>, playing: bool, rate: Option<f64>) {
+        self.sync_at(position, playing, rate, Instant::now());
+    }
+
+    pub fn sync_at(
+        &mut self,
+        position: Option<Duration>,
+        playing: bool,
+        rate: Option<f64>,
+        now: Instant,
+    ) {
+        self.anchor = match (position, self.position_at(now)) {
+            // Close enough to where we expected to be: keep the local timeline
+            (Some(sampled), Some(expected)) if sampled.abs_diff(expected) <= JITTER_TOLERANCE => {
+                Some((expected, now))
+            }
+            // Drift, seek or first sample: jump to the player's position
+            (Some(sampled), _) => Some((sampled, now)),
+            (None, _) => None,
+        };
+        self.playing = playing;
+        self.rate = rate.unwrap_or(1.0).max(0.0);
+    }
+
+    /// Whether `position` is a jump rather than normal playback progress
+    pub fn is_seek(&self, position: Duration) -> bool {
+        self.is_seek_at(position, Instant::now())
+    }
+
+    pub fn is_seek_at(&self, position: Duration, now: Instant) -> bool {
+        self.position_at(now)
+            .is_some_and(|expected| position.abs_diff(expected) > SEEK_THRESHOLD)
+    }
+
+    /// Current position, advanced by the time elapsed since the last sample
+    pub fn position(&self) -> Option<Duration> {
+        self.position_at(Instant::now())
+    }
+
+    pub fn position_at(&self, now: Instant) -> Option<Duration> {
+        self.anchor.map(|(position, sampled_at)| {
+            if self.playing {
+                position + now.saturating_duration_since(sampled_at).mul_f64(self.rate)
+            } else {
+                position
+            }
+        })
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_without_samples() {
+        let clock = PlaybackClock::new();
+        assert_eq!(clock.position(), None);
+    }
+
+    #[test]
+    fn test_clock_advances_while_playing() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, None, start);
+
+        assert_eq!(clock.position_at(start), Some(Duration::from_secs(10)));
+        assert_eq!(
+            clock.position_at(start + Duration::from_millis(750)),
+            Some(Duration::from_millis(10_750))
+        );
+    }
+
+    #[test]
+    fn test_clock_holds_while_paused() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), false, None, start);
+
+        assert_eq!(
+            clock.position_at(start + Duration::from_secs(5)),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_clock_follows_playback_rate() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, Some(1.5), start);
+
+        assert_eq!(
+            clock.position_at(start + Duration::from_secs(2)),
+            Some(Duration::from_secs(13))
+        );
+
+        // Rate 0 behaves like paused
+        clock.sync_at(Some(Duration::from_secs(10)), true, Some(0.0), start);
+        assert_eq!(
+            clock.position_at(start + Duration::from_secs(2)),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_clock_ignores_sampling_jitter() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, None, start);
+
+        // The player reports a position slightly behind what we extrapolated
+        let later = start + Duration::from_millis(500);
+        clock.sync_at(Some(Duration::from_millis(10_420)), true, None, later);
+        assert_eq!(
+            clock.position_at(later),
+            Some(Duration::from_millis(10_500))
+        );
+    }
+
+    #[test]
+    fn test_clock_corrects_drift() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, None, start);
+
+        let later = start + Duration::from_secs(5);
+        clock.sync_at(Some(Duration::from_millis(14_600)), true, None, later);
+        assert_eq!(
+            clock.position_at(later),
+            Some(Duration::from_millis(14_600))
+        );
+    }
+
+    #[test]
+    fn test_clock_detects_and_follows_seeks() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, None, start);
+
+        let later = start + Duration::from_millis(500);
+        assert!(!clock.is_seek_at(Duration::from_millis(10_400), later));
+        assert!(clock.is_seek_at(Duration::from_secs(60), later));
+        assert!(clock.is_seek_at(Duration::from_secs(2), later));
+
+        clock.sync_at(Some(Duration::from_secs(60)), true, None, later);
+        assert_eq!(
+            clock.position_at(later + Duration::from_secs(1)),
+            Some(Duration::from_secs(61))
+        );
+
+        // A sample without position clears the clock
+        clock.sync_at(None, true, None, later);
+        assert_eq!(clock.position_at(later), None);
+        assert!(!clock.is_seek_at(Duration::from_secs(60), later));
+    }
+
+    #[test]
+    fn test_clock_pause_keeps_extrapolated_position() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, None, start);
+
+        // Paused 2s later, the player's sample lags a little behind
+        let paused_at = start + Duration::from_secs(2);
+        clock.sync_at(Some(Duration::from_millis(11_900)), false, None, paused_at);
+        assert_eq!(
+            clock.position_at(paused_at + Duration::from_secs(10)),
+            Some(Duration::from_secs(12))
+        );
+    }
+
+    #[test]
+    fn test_clock_ignores_instants_before_sample() {
+        let start = Instant::now() + Duration::from_secs(1);
+        let mut clock = PlaybackClock::new();
+        clock.sync_at(Some(Duration::from_secs(10)), true, None, start);
+
+        assert_eq!(
+            clock.position_at(start - Duration::from_millis(500)),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_clock_reset() {
+        let mut clock = PlaybackClock::new();
+        clock.sync(Some(Duration::from_secs(10)), true, Some(2.0));
+        clock.reset();
+        assert_eq!(clock.position(), None);
+    }
+}
@@ -0,0 +1,426 @@
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::{
+    detect_streaming_source, local_cover_url, next_loop_status, PlaybackControl, PlayerBackend,
+    PlayerMetadata,
+};
+use crate::tags;
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How commands reach cmus
+#[derive(Debug, Clone, PartialEq)]
+pub enum CmusTransport {
+    /// cmus' own control socket
+    Socket(PathBuf),
+    /// Through `cmus-remote`, which finds the socket itself
+    Remote,
+}
+
+impl CmusTransport {
+    /// Send one command and return the reply
+    fn send(&self, command: &str) -> Result<String> {
+        match self {
+            CmusTransport::Socket(path) => socket_command(path, command),
+            CmusTransport::Remote => remote_command(command),
+        }
+    }
+}
+
+/// Reads cmus, which has no MPRIS support of its own
+pub struct CmusBackend {
+    transport: CmusTransport,
+}
+
+impl CmusBackend {
+    pub fn new(transport: CmusTransport) -> Self {
+        Self { transport }
+    }
+
+    fn query(&self) -> Result<Vec<(String, String)>> {
+        Ok(parse_status(&self.transport.send("status")?))
+    }
+}
+
+impl PlayerBackend for CmusBackend {
+    fn name(&self) -> &str {
+        "cmus"
+    }
+
+    fn current_track(&mut self) -> Result<PlayerMetadata> {
+        metadata_from_status(&self.query()?)
+    }
+
+    fn status(&mut self) -> Result<String> {
+        let status = self.query()?;
+        lookup(&status, "status")
+            .map(playback_status)
+            .ok_or_else(|| anyhow!("cmus did not report its status"))
+    }
+
+    fn cover_art(&mut self) -> Result<Option<Vec<u8>>> {
+        let status = self.query()?;
+        match lookup(&status, "file") {
+            Some(file) if file.starts_with('/') => tags::embedded_picture(Path::new(file)),
+            _ => Ok(None),
+        }
+    }
+
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let status = self.query()?;
+        let setting = |name: &str| lookup(&status, &format!("set {name}")) == Some("true");
+
+        let commands: Vec<String> = match control {
+            // player-pause does nothing while stopped
+            PlaybackControl::PlayPause => match lookup(&status, "status") {
+                Some("stopped") | None => vec!["player-play".to_string()],
+                _ => vec!["player-pause".to_string()],
+            },
+            PlaybackControl::Next => vec!["player-next".to_string()],
+            PlaybackControl::Previous => vec!["player-prev".to_string()],
+            PlaybackControl::Seek(seconds) => vec![format!("seek {seconds:+}")],
+            // cmus only seeks to whole seconds
+            PlaybackControl::SeekTo(position) => vec![format!("seek {}", position.as_secs())],
+            PlaybackControl::Volume(percent) => vec![format!("vol {percent:+}%")],
+            PlaybackControl::ToggleShuffle => vec!["toggle shuffle".to_string()],
+            PlaybackControl::ToggleLoop => {
+                let current = match (setting("repeat"), setting("repeat_current")) {
+                    (_, true) => "Track",
+                    (true, false) => "Playlist",
+                    (false, false) => "None",
+                };
+                let (repeat, repeat_current) = match next_loop_status(current) {
+                    "Playlist" => (true, false),
+                    "Track" => (true, true),
+                    _ => (false, false),
+                };
+                vec![
+                    format!("set repeat={repeat}"),
+                    format!("set repeat_current={repeat_current}"),
+                ]
+            }
+        };
+
+        for command in commands {
+            self.transport.send(&command)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where cmus puts its socket unless `CMUS_SOCKET` says otherwise:
+/// `$XDG_RUNTIME_DIR/cmus-socket`, then `~/.config/cmus/socket`
+pub fn default_socket() -> Option<PathBuf> {
+    if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        return Some(Path::new(&runtime_dir).join("cmus-socket"));
+    }
+    std::env::var_os("HOME").map(|home| Path::new(&home).join(".config/cmus/socket"))
+}
+
+pub fn is_cmus_remote_installed() -> bool {
+    Command::new("cmus-remote")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// cmus answers every command and ends the reply with an empty line
+fn socket_command(path: &Path, command: &str) -> Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(COMMAND_TIMEOUT))?;
+    stream.write_all(format!("{command}\n").as_bytes())?;
+
+    let mut reply = String::new();
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        reply.push_str(&line);
+        reply.push('\n');
+    }
+    Ok(reply)
+}
+
+fn remote_command(command: &str) -> Result<String> {
+    let mut remote = Command::new("cmus-remote");
+    if command == "status" {
+        remote.arg("-Q");
+    } else {
+        remote.args(["-C", command]);
+    }
+
+    let output = remote.stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(anyhow!("cmus-remote failed, is cmus running?"));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// `cmus-remote -Q` output as `(key, value)` pairs. Tags keep their
+/// `tag ` prefix and settings their `set ` prefix.
+fn parse_status(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, rest) = line.split_once(' ')?;
+            if key == "tag" || key == "set" {
+                let (name, value) = rest.split_once(' ').unwrap_or((rest, ""));
+                Some((format!("{key} {name}"), value.to_string()))
+            } else {
+                Some((key.to_string(), rest.to_string()))
+            }
+        })
+        .collect()
+}
+
+fn metadata_from_status(status: &[(String, String)]) -> Result<PlayerMetadata> {
+    let file = lookup(status, "file").ok_or_else(|| anyhow!("cmus has no track loaded"))?;
+    let tag = |name: &str| lookup(status, &format!("tag {name}")).map(|v| v.to_string());
+    let number = |name: &str| {
+        tag(name)
+            .and_then(|v| v.split('/').next()?.trim().parse::<u32>().ok())
+            .filter(|n| *n > 0)
+    };
+    // -1 for streams
+    let seconds = |key: &str| {
+        lookup(status, key)
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs)
+    };
+
+    let is_local = file.starts_with('/');
+    // Radio streams announce the current song in `stream`
+    let title = tag("title")
+        .or_else(|| lookup(status, "stream").map(|v| v.to_string()))
+        .unwrap_or_else(|| file_stem(file));
+
+    Ok(PlayerMetadata {
+        artist: tag("artist").unwrap_or_default(),
+        title,
+        album: tag("album"),
+        position: seconds("position"),
+        length: seconds("duration"),
+        streaming_source: if is_local {
+            Some("Local File".to_string())
+        } else {
+            detect_streaming_source(file)
+        },
+        // Embedded covers come from `cover_art`, this is the cover.jpg next
+        // to the file
+        art_url: if is_local {
+            local_cover_url(Path::new(file))
+        } else {
+            None
+        },
+        status: lookup(status, "status").map(playback_status),
+        rate: None,
+        player: Some("cmus".to_string()),
+        album_artist: tag("albumartist"),
+        track_number: number("tracknumber"),
+        disc_number: number("discnumber"),
+        genre: tag("genre"),
+        composer: tag("composer"),
+        user_rating: None,
+        track_id: Some(file.to_string()),
+        url: Some(file.to_string()),
+    })
+}
+
+fn lookup<'a>(pairs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    pairs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+fn playback_status(status: &str) -> String {
+    match status {
+        "playing" => "Playing",
+        "paused" => "Paused",
+        _ => "Stopped",
+    }
+    .to_string()
+}
+
+fn file_stem(file: &str) -> String {
+    Path::new(file)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    const STATUS: &str = "status playing
+file /music/Boards of Canada/Geogaddi/03 Music Is Math.flac
+duration 321
+position 42
+tag artist Boards of Canada
+tag album Geogaddi
+tag title Music Is Math
+tag date 2002
+tag genre Electronic
+tag tracknumber 3
+tag discnumber 1/1
+tag albumartist Boards of Canada
+tag comment
+set aaa_mode all
+set continue true
+set repeat true
+set repeat_current false
+set shuffle false
+set vol_left 80
+set vol_right 80
+";
+
+    static SOCKET_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Pretends to be cmus listening on its socket
+    fn fake_cmus(status: &'static str) -> (PathBuf, Arc<Mutex<Vec<String>>>) {
+        let path = std::env::temp_dir().join(format!(
+            "trackwatch-test-cmus-{}-{}.sock",
+            std::process::id(),
+            SOCKET_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+
+        let received = commands.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                let command = line.trim_end().to_string();
+                let reply = if command == "status" {
+                    format!("{status}\n")
+                } else {
+                    "\n".to_string()
+                };
+                received.lock().unwrap().push(command);
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+
+        (path, commands)
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let status = parse_status(STATUS);
+        assert_eq!(lookup(&status, "status"), Some("playing"));
+        assert_eq!(lookup(&status, "tag artist"), Some("Boards of Canada"));
+        assert_eq!(lookup(&status, "set repeat"), Some("true"));
+        // Empty tags count as missing
+        assert_eq!(lookup(&status, "tag comment"), None);
+    }
+
+    #[test]
+    fn test_metadata_from_status() {
+        let metadata = metadata_from_status(&parse_status(STATUS)).unwrap();
+
+        assert_eq!(metadata.artist, "Boards of Canada");
+        assert_eq!(metadata.title, "Music Is Math");
+        assert_eq!(metadata.album, Some("Geogaddi".to_string()));
+        assert_eq!(metadata.position, Some(Duration::from_secs(42)));
+        assert_eq!(metadata.length, Some(Duration::from_secs(321)));
+        assert_eq!(metadata.status, Some("Playing".to_string()));
+        assert_eq!(metadata.streaming_source, Some("Local File".to_string()));
+        assert_eq!(metadata.track_number, Some(3));
+        assert_eq!(metadata.disc_number, Some(1));
+        assert_eq!(metadata.player, Some("cmus".to_string()));
+        assert_eq!(
+            metadata.track_id,
+            Some("/music/Boards of Canada/Geogaddi/03 Music Is Math.flac".to_string())
+        );
+    }
+
+    #[test]
+    fn test_metadata_from_stream_and_stopped_cmus() {
+        let status = parse_status(
+            "status playing\nfile http://radio.example.com/live\nduration -1\nposition 12\nstream Artist - Live Song\n",
+        );
+        let metadata = metadata_from_status(&status).unwrap();
+        assert_eq!(metadata.title, "Artist - Live Song");
+        assert_eq!(metadata.length, None);
+        assert_eq!(metadata.art_url, None);
+
+        assert!(metadata_from_status(&parse_status("status stopped\n")).is_err());
+    }
+
+    #[test]
+    fn test_reads_status_over_socket() {
+        let (socket, _) = fake_cmus(STATUS);
+        let mut backend = CmusBackend::new(CmusTransport::Socket(socket.clone()));
+
+        assert_eq!(backend.current_track().unwrap().title, "Music Is Math");
+        assert_eq!(backend.status().unwrap(), "Playing");
+        std::fs::remove_file(socket).unwrap();
+    }
+
+    #[test]
+    fn test_controls_over_socket() {
+        let (socket, commands) =
+            fake_cmus("status paused\nset repeat true\nset repeat_current false");
+        let mut backend = CmusBackend::new(CmusTransport::Socket(socket.clone()));
+
+        for control in [
+            PlaybackControl::PlayPause,
+            PlaybackControl::Next,
+            PlaybackControl::Previous,
+            PlaybackControl::Seek(5),
+            PlaybackControl::SeekTo(std::time::Duration::from_millis(83_900)),
+            PlaybackControl::Volume(-5),
+            PlaybackControl::ToggleShuffle,
+            PlaybackControl::ToggleLoop,
+        ] {
+            backend.control(control).unwrap();
+        }
+
+        let sent: Vec<String> = commands
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|command| *command != "status")
+            .cloned()
+            .collect();
+        assert_eq!(
+            sent,
+            vec![
+                "player-pause",
+                "player-next",
+                "player-prev",
+                "seek +5",
+                "seek 83",
+                "vol -5%",
+                "toggle shuffle",
+                "set repeat=true",
+                "set repeat_current=true",
+            ]
+        );
+        std::fs::remove_file(socket).unwrap();
+    }
+
+    #[test]
+    fn test_cmus_not_running() {
+        let mut backend =
+            CmusBackend::new(CmusTransport::Socket(PathBuf::from("/nonexistent/cmus")));
+        assert!(backend.current_track().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+use super::{PlaybackControl, PlayerBackend, PlayerEvent, PlayerInfo, PlayerMetadata};
+
+const DEFAULT_PLAYER: &str = "mock";
+
+/// In-memory backend for tests.
+///
+/// Clones share their state, so a test can keep one handle to drive the
+/// player while another is boxed into a `Box<dyn PlayerBackend>`.
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    players: BTreeMap<String, PlayerMetadata>,
+    active: Option<String>,
+    controls: Vec<PlaybackControl>,
+    subscribers: Vec<Sender<PlayerEvent>>,
+}
+
+impl MockState {
+    fn notify(&mut self) {
+        self.subscribers
+            .retain(|events| events.send(PlayerEvent::Changed).is_ok());
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_track(track: PlayerMetadata) -> Self {
+        let backend = Self::new();
+        backend.set_track(Some(track));
+        backend
+    }
+
+    /// Replace the current track (`None` means no player is running) and
+    /// notify subscribers
+    pub fn set_track(&self, track: Option<PlayerMetadata>) {
+        self.set_player_track(DEFAULT_PLAYER, track);
+    }
+
+    /// Like `set_track`, for one of several players
+    pub fn set_player_track(&self, player: &str, track: Option<PlayerMetadata>) {
+        let mut state = self.state.lock().unwrap();
+        match track {
+            Some(track) => state.players.insert(player.to_string(), track),
+            None => state.players.remove(player),
+        };
+        state.notify();
+    }
+
+    /// The player last selected with `set_active_player`
+    pub fn active_player(&self) -> Option<String> {
+        self.state.lock().unwrap().active.clone()
+    }
+
+    /// Playback controls received so far, oldest first
+    pub fn controls(&self) -> Vec<PlaybackControl> {
+        self.state.lock().unwrap().controls.clone()
+    }
+}
+
+impl PlayerBackend for MockBackend {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn current_track(&mut self) -> Result<PlayerMetadata> {
+        let state = self.state.lock().unwrap();
+        let (name, track) = match state.active {
+            Some(ref active) => state.players.get_key_value(active),
+            None => state.players.iter().next(),
+        }
+        .ok_or_else(|| anyhow!("No player running"))?;
+
+        Ok(PlayerMetadata {
+            player: Some(name.clone()),
+            ..track.clone()
+        })
+    }
+
+    fn status(&mut self) -> Result<String> {
+        self.current_track()?
+            .status
+            .ok_or_else(|| anyhow!("No status reported"))
+    }
+
+    fn list_players(&mut self) -> Result<Vec<PlayerInfo>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .players
+            .iter()
+            .map(|(name, track)| PlayerInfo {
+                name: name.clone(),
+                status: track.status.clone(),
+            })
+            .collect())
+    }
+
+    fn set_active_player(&mut self, player: Option<&str>) {
+        self.state.lock().unwrap().active = player.map(|name| name.to_string());
+    }
+
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.players.is_empty() {
+            return Err(anyhow!("No player running"));
+        }
+        state.controls.push(control);
+        Ok(())
+    }
+
+    fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        self.state.lock().unwrap().subscribers.push(events);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn track(title: &str) -> PlayerMetadata {
+        PlayerMetadata {
+            artist: "Test Artist".to_string(),
+            title: title.to_string(),
+            status: Some("Playing".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mock_backend_reports_track_and_status() {
+        let mut backend = MockBackend::with_track(track("First"));
+
+        assert_eq!(backend.current_track().unwrap().title, "First");
+        assert_eq!(backend.status().unwrap(), "Playing");
+
+        backend.set_track(None);
+        assert!(backend.current_track().is_err());
+        assert!(backend.status().is_err());
+    }
+
+    #[test]
+    fn test_mock_backend_clones_share_state() {
+        let handle = MockBackend::new();
+        let mut boxed: Box<dyn PlayerBackend> = Box::new(handle.clone());
+        assert!(boxed.current_track().is_err());
+
+        handle.set_track(Some(track("Shared")));
+        assert_eq!(boxed.current_track().unwrap().title, "Shared");
+    }
+
+    #[test]
+    fn test_mock_backend_with_several_players() {
+        let mut backend = MockBackend::new();
+        backend.set_player_track("spotify", Some(track("Spotify Song")));
+        backend.set_player_track("mpv", Some(track("Local File")));
+
+        let names: Vec<String> = backend
+            .list_players()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["mpv".to_string(), "spotify".to_string()]);
+
+        backend.set_active_player(Some("spotify"));
+        let current = backend.current_track().unwrap();
+        assert_eq!(current.title, "Spotify Song");
+        assert_eq!(current.player, Some("spotify".to_string()));
+
+        backend.set_player_track("spotify", None);
+        assert!(backend.current_track().is_err());
+    }
+
+    #[test]
+    fn test_mock_backend_notifies_subscribers() {
+        let mut backend = MockBackend::new();
+        let (tx, rx) = mpsc::channel();
+        assert!(backend.subscribe(tx).unwrap());
+
+        backend.set_track(Some(track("Notified")));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            PlayerEvent::Changed
+        );
+    }
+}
@@ -0,0 +1,578 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+pub mod clock;
+pub mod cmus;
+pub mod mock;
+pub mod mpd;
+pub mod mpris;
+pub mod mpv;
+pub mod playerctl;
+pub mod selection;
+pub mod watcher;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlayerMetadata {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub position: Option<Duration>,
+    pub length: Option<Duration>,
+    pub streaming_source: Option<String>,
+    pub art_url: Option<String>,
+    pub status: Option<String>, // "Playing", "Paused", "Stopped"
+    pub rate: Option<f64>,      // Playback rate, 1.0 is normal speed
+    pub player: Option<String>, // Player name, e.g. "spotify" or "firefox.instance_1_42"
+    pub album_artist: Option<String>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub genre: Option<String>,
+    pub composer: Option<String>,
+    pub user_rating: Option<f64>, // 0.0 to 1.0
+    pub track_id: Option<String>, // mpris:trackid, unique per track in the player's queue
+    pub url: Option<String>,      // xesam:url, the file or stream being played
+}
+
+impl PlayerMetadata {
+    /// Whether `other` describes the same track (ignoring position and status).
+    ///
+    /// The track id tells apart consecutive tracks with the same title, e.g.
+    /// the same song queued twice.
+    pub fn is_same_track(&self, other: &PlayerMetadata) -> bool {
+        self.player == other.player
+            && self.track_id == other.track_id
+            && self.artist == other.artist
+            && self.title == other.title
+            && self.album == other.album
+    }
+
+    /// Path of the file being played, when it is a local file
+    pub fn local_path(&self) -> Option<PathBuf> {
+        let url = self.url.as_deref()?;
+        let path = match url.strip_prefix("file://") {
+            // MPRIS urls are percent-encoded
+            Some(path) => urlencoding::decode(path).ok()?.into_owned(),
+            None if url.starts_with('/') => url.to_string(),
+            None => return None,
+        };
+        Some(PathBuf::from(path))
+    }
+
+    pub fn get_progress_percentage(&self) -> Option<f32> {
+        match (self.position, self.length) {
+            (Some(pos), Some(len)) if len.as_secs() > 0 => {
+                Some((pos.as_secs_f32() / len.as_secs_f32()) * 100.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A running player as listed by a backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub status: Option<String>,
+}
+
+impl PlayerInfo {
+    pub fn is_playing(&self) -> bool {
+        self.status.as_deref() == Some("Playing")
+    }
+}
+
+/// Playback actions a backend can forward to the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackControl {
+    PlayPause,
+    Next,
+    Previous,
+    /// Relative seek in seconds
+    Seek(i64),
+    /// Jump to a position in the current track
+    SeekTo(Duration),
+    /// Relative volume change in percentage points
+    Volume(i32),
+    ToggleShuffle,
+    /// Cycle the loop status: None -> Playlist -> Track -> None
+    ToggleLoop,
+}
+
+impl PlaybackControl {
+    /// Short label for the title bar
+    pub fn describe(&self) -> String {
+        match self {
+            PlaybackControl::PlayPause => "Play/Pause".to_string(),
+            PlaybackControl::Next => "Next track".to_string(),
+            PlaybackControl::Previous => "Previous track".to_string(),
+            PlaybackControl::Seek(seconds) => format!("Seek {seconds:+}s"),
+            PlaybackControl::SeekTo(position) => {
+                let seconds = position.as_secs();
+                format!("Seek to {:02}:{:02}", seconds / 60, seconds % 60)
+            }
+            PlaybackControl::Volume(percent) => format!("Volume {percent:+}%"),
+            PlaybackControl::ToggleShuffle => "Shuffle".to_string(),
+            PlaybackControl::ToggleLoop => "Loop".to_string(),
+        }
+    }
+}
+
+/// The MPRIS loop status that follows `current`
+fn next_loop_status(current: &str) -> &'static str {
+    match current {
+        "None" => "Playlist",
+        "Playlist" => "Track",
+        _ => "None",
+    }
+}
+
+/// Requests from the UI to the background thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerCommand {
+    /// Follow the next running player
+    CyclePlayer,
+    Control(PlaybackControl),
+}
+
+/// Messages that wake up the background thread: notifications pushed by
+/// backends that can report changes themselves, and commands from the UI
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerEvent {
+    /// Track metadata or playback status changed
+    Changed,
+    /// The backend stopped delivering notifications, fall back to polling
+    SubscriptionEnded,
+    Command(PlayerCommand),
+}
+
+/// A source of now-playing information (playerctl, MPRIS, ...)
+pub trait PlayerBackend: Send {
+    /// Short name used in configuration and diagnostics
+    fn name(&self) -> &str;
+
+    fn current_track(&mut self) -> Result<PlayerMetadata>;
+
+    /// Playback status: "Playing", "Paused" or "Stopped"
+    fn status(&mut self) -> Result<String>;
+
+    /// All players this backend can see.
+    ///
+    /// Backends that only ever talk to one player return an empty list.
+    fn list_players(&mut self) -> Result<Vec<PlayerInfo>> {
+        Ok(Vec::new())
+    }
+
+    /// Read from `player` from now on. `None` lets the backend pick.
+    fn set_active_player(&mut self, player: Option<&str>) {
+        let _ = player;
+    }
+
+    /// Cover image of the current track as raw bytes, for players that serve
+    /// it themselves rather than through an art URL
+    fn cover_art(&mut self) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Forward a playback action to the active player
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let _ = control;
+        Err(anyhow!("{} backend cannot control playback", self.name()))
+    }
+
+    /// Start pushing change notifications into `events`.
+    ///
+    /// Returns `false` when the backend can only be polled.
+    fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        let _ = events;
+        Ok(false)
+    }
+}
+
+/// `mpris:trackid` as reported by the player. The spec reserves `NoTrack`
+/// for "no track", which some players send alongside real metadata.
+fn parse_track_id(track_id: &str) -> Option<String> {
+    let track_id = track_id.trim();
+    if track_id.is_empty() || track_id == "/org/mpris/MediaPlayer2/TrackList/NoTrack" {
+        None
+    } else {
+        Some(track_id.to_string())
+    }
+}
+
+// Cover file names, best first, as music players and rippers save them
+const COVER_FILE_NAMES: &[&str] = &["cover", "folder", "front", "album", "albumart"];
+const COVER_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// `file://` URL of a cover image next to a local audio file
+fn local_cover_url(audio_file: &Path) -> Option<String> {
+    let directory = audio_file.parent()?;
+    let images: Vec<PathBuf> = std::fs::read_dir(directory)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| COVER_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+
+    COVER_FILE_NAMES.iter().find_map(|wanted| {
+        images
+            .iter()
+            .find(|path| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| stem.eq_ignore_ascii_case(wanted))
+            })
+            .map(|path| format!("file://{}", path.display()))
+    })
+}
+
+fn detect_streaming_source(url: &str) -> Option<String> {
+    if url.contains("tidal.com") {
+        Some("Tidal".to_string())
+    } else if url.contains("youtube.com") || url.contains("youtu.be") {
+        Some("YouTube".to_string())
+    } else if url.contains("spotify.com") {
+        Some("Spotify".to_string())
+    } else if url.contains("soundcloud.com") {
+        Some("SoundCloud".to_string())
+    } else if url.contains("deezer.com") {
+        Some("Deezer".to_string())
+    } else if url.contains("music.apple.com") {
+        Some("Apple Music".to_string())
+    } else if url.contains("bandcamp.com") {
+        Some("Bandcamp".to_string())
+    } else if url.starts_with("file://") {
+        Some("Local File".to_string())
+    } else if !url.is_empty() {
+        Some("Web".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_progress_percentage() {
+        // Normal case - 30 seconds of 120 seconds
+        let metadata = PlayerMetadata {
+            artist: "Test".to_string(),
+            title: "Test".to_string(),
+            album: None,
+            position: Some(Duration::from_secs(30)),
+            length: Some(Duration::from_secs(120)),
+            streaming_source: None,
+            art_url: None,
+            status: None,
+            ..Default::default()
+        };
+        assert_eq!(metadata.get_progress_percentage(), Some(25.0));
+
+        // At the end
+        let metadata = PlayerMetadata {
+            artist: "Test".to_string(),
+            title: "Test".to_string(),
+            album: None,
+            position: Some(Duration::from_secs(120)),
+            length: Some(Duration::from_secs(120)),
+            streaming_source: None,
+            art_url: None,
+            status: None,
+            ..Default::default()
+        };
+        assert_eq!(metadata.get_progress_percentage(), Some(100.0));
+
+        // No position
+        let metadata = PlayerMetadata {
+            artist: "Test".to_string(),
+            title: "Test".to_string(),
+            album: None,
+            position: None,
+            length: Some(Duration::from_secs(120)),
+            streaming_source: None,
+            art_url: None,
+            status: None,
+            ..Default::default()
+        };
+        assert_eq!(metadata.get_progress_percentage(), None);
+
+        // No length
+        let metadata = PlayerMetadata {
+            artist: "Test".to_string(),
+            title: "Test".to_string(),
+            album: None,
+            position: Some(Duration::from_secs(30)),
+            length: None,
+            streaming_source: None,
+            art_url: None,
+            status: None,
+            ..Default::default()
+        };
+        assert_eq!(metadata.get_progress_percentage(), None);
+
+        // Zero length (should return None to avoid division by zero)
+        let metadata = PlayerMetadata {
+            artist: "Test".to_string(),
+            title: "Test".to_string(),
+            album: None,
+            position: Some(Duration::from_secs(30)),
+            length: Some(Duration::from_secs(0)),
+            streaming_source: None,
+            art_url: None,
+            status: None,
+            ..Default::default()
+        };
+        assert_eq!(metadata.get_progress_percentage(), None);
+
+        // Fractional seconds
+        let metadata = PlayerMetadata {
+            artist: "Test".to_string(),
+            title: "Test".to_string(),
+            album: None,
+            position: Some(Duration::from_millis(1500)),
+            length: Some(Duration::from_millis(3000)),
+            streaming_source: None,
+            art_url: None,
+            status: None,
+            ..Default::default()
+        };
+        assert_eq!(metadata.get_progress_percentage(), Some(50.0));
+    }
+
+    #[test]
+    fn test_detect_streaming_source() {
+        // Tidal
+        assert_eq!(
+            detect_streaming_source("https://tidal.com/track/12345"),
+            Some("Tidal".to_string())
+        );
+        assert_eq!(
+            detect_streaming_source("https://listen.tidal.com/album/98765"),
+            Some("Tidal".to_string())
+        );
+
+        // YouTube
+        assert_eq!(
+            detect_streaming_source("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            Some("YouTube".to_string())
+        );
+        assert_eq!(
+            detect_streaming_source("https://youtu.be/dQw4w9WgXcQ"),
+            Some("YouTube".to_string())
+        );
+        assert_eq!(
+            detect_streaming_source("https://music.youtube.com/watch?v=abc123"),
+            Some("YouTube".to_string())
+        );
+
+        // Spotify
+        assert_eq!(
+            detect_streaming_source("https://open.spotify.com/track/abc123"),
+            Some("Spotify".to_string())
+        );
+
+        // SoundCloud
+        assert_eq!(
+            detect_streaming_source("https://soundcloud.com/artist/track"),
+            Some("SoundCloud".to_string())
+        );
+
+        // Deezer
+        assert_eq!(
+            detect_streaming_source("https://www.deezer.com/track/12345"),
+            Some("Deezer".to_string())
+        );
+
+        // Apple Music
+        assert_eq!(
+            detect_streaming_source("https://music.apple.com/us/album/song/123456"),
+            Some("Apple Music".to_string())
+        );
+
+        // Bandcamp
+        assert_eq!(
+            detect_streaming_source("https://artist.bandcamp.com/track/song-name"),
+            Some("Bandcamp".to_string())
+        );
+
+        // Local file
+        assert_eq!(
+            detect_streaming_source("file:///home/user/Music/song.mp3"),
+            Some("Local File".to_string())
+        );
+
+        // Generic web URL
+        assert_eq!(
+            detect_streaming_source("https://random-music-site.com/play"),
+            Some("Web".to_string())
+        );
+
+        // Empty URL
+        assert_eq!(detect_streaming_source(""), None);
+    }
+
+    #[test]
+    fn test_player_metadata_creation() {
+        let metadata = PlayerMetadata {
+            artist: "Test Artist".to_string(),
+            title: "Test Song".to_string(),
+            album: Some("Test Album".to_string()),
+            position: Some(Duration::from_secs(60)),
+            length: Some(Duration::from_secs(180)),
+            streaming_source: Some("Tidal".to_string()),
+            art_url: Some("https://example.com/art.jpg".to_string()),
+            status: Some("Playing".to_string()),
+            rate: Some(1.0),
+            ..Default::default()
+        };
+
+        assert_eq!(metadata.artist, "Test Artist");
+        assert_eq!(metadata.title, "Test Song");
+        assert_eq!(metadata.album, Some("Test Album".to_string()));
+        assert_eq!(metadata.position, Some(Duration::from_secs(60)));
+        assert_eq!(metadata.length, Some(Duration::from_secs(180)));
+        assert_eq!(metadata.streaming_source, Some("Tidal".to_string()));
+        assert_eq!(
+            metadata.art_url,
+            Some("https://example.com/art.jpg".to_string())
+        );
+        assert_eq!(metadata.status, Some("Playing".to_string()));
+        assert_eq!(metadata.rate, Some(1.0));
+    }
+
+    #[test]
+    fn test_progress_percentage_precision() {
+        // Test various precision cases
+        let test_cases = vec![
+            (1.0, 3.0, 33.333332),      // 1/3
+            (2.0, 3.0, 66.66667),       // 2/3
+            (1.0, 7.0, 14.285714),      // 1/7
+            (0.001, 1.0, 0.1),          // Very small progress
+            (999.999, 1000.0, 99.9999), // Almost complete
+        ];
+
+        for (pos, len, expected) in test_cases {
+            let metadata = PlayerMetadata {
+                artist: "Test".to_string(),
+                title: "Test".to_string(),
+                album: None,
+                position: Some(Duration::from_secs_f64(pos)),
+                length: Some(Duration::from_secs_f64(len)),
+                streaming_source: None,
+                art_url: None,
+                status: None,
+                ..Default::default()
+            };
+
+            let progress = metadata.get_progress_percentage().unwrap();
+            assert!(
+                (progress - expected).abs() < 0.001,
+                "Expected {expected} but got {progress} for {pos}/{len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_loop_status() {
+        assert_eq!(next_loop_status("None"), "Playlist");
+        assert_eq!(next_loop_status("Playlist"), "Track");
+        assert_eq!(next_loop_status("Track"), "None");
+        assert_eq!(next_loop_status("garbage"), "None");
+    }
+
+    #[test]
+    fn test_playback_control_describe() {
+        assert_eq!(PlaybackControl::Seek(-30).describe(), "Seek -30s");
+        assert_eq!(PlaybackControl::Seek(5).describe(), "Seek +5s");
+        assert_eq!(PlaybackControl::Volume(5).describe(), "Volume +5%");
+        assert_eq!(PlaybackControl::Next.describe(), "Next track");
+    }
+
+    #[test]
+    fn test_is_same_track_uses_track_id() {
+        let first = PlayerMetadata {
+            artist: "Queen".to_string(),
+            title: "Bohemian Rhapsody".to_string(),
+            track_id: Some("/com/spotify/track/1".to_string()),
+            ..Default::default()
+        };
+        let mut repeat = first.clone();
+        repeat.position = Some(Duration::from_secs(30));
+        assert!(first.is_same_track(&repeat));
+
+        // Same song queued again right after
+        repeat.track_id = Some("/com/spotify/track/2".to_string());
+        assert!(!first.is_same_track(&repeat));
+
+        // Players without track ids still compare by title
+        let mut untracked = first.clone();
+        untracked.track_id = None;
+        assert!(untracked.is_same_track(&untracked.clone()));
+    }
+
+    #[test]
+    fn test_parse_track_id() {
+        assert_eq!(
+            parse_track_id("/org/mpd/Tracks/42"),
+            Some("/org/mpd/Tracks/42".to_string())
+        );
+        assert_eq!(
+            parse_track_id("/org/mpris/MediaPlayer2/TrackList/NoTrack"),
+            None
+        );
+        assert_eq!(parse_track_id(""), None);
+    }
+
+    #[test]
+    fn test_local_path() {
+        let with_url = |url: &str| PlayerMetadata {
+            url: Some(url.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            with_url("file:///home/me/Music/Bj%C3%B6rk/01%20Army%20of%20Me.flac").local_path(),
+            Some(PathBuf::from("/home/me/Music/Björk/01 Army of Me.flac"))
+        );
+        assert_eq!(
+            with_url("/music/song.mp3").local_path(),
+            Some(PathBuf::from("/music/song.mp3"))
+        );
+        assert_eq!(with_url("https://youtu.be/abc").local_path(), None);
+        assert_eq!(PlayerMetadata::default().local_path(), None);
+    }
+
+    #[test]
+    fn test_local_cover_url() {
+        let album =
+            std::env::temp_dir().join(format!("trackwatch-test-album-{}", std::process::id()));
+        std::fs::create_dir_all(&album).unwrap();
+        let song = album.join("01 Song.flac");
+        std::fs::write(&song, b"").unwrap();
+
+        assert_eq!(local_cover_url(&song), None);
+
+        std::fs::write(album.join("Back.jpg"), b"").unwrap();
+        std::fs::write(album.join("Folder.JPG"), b"").unwrap();
+        assert_eq!(
+            local_cover_url(&song),
+            Some(format!("file://{}", album.join("Folder.JPG").display()))
+        );
+
+        std::fs::write(album.join("cover.png"), b"").unwrap();
+        assert_eq!(
+            local_cover_url(&song),
+            Some(format!("file://{}", album.join("cover.png").display()))
+        );
+
+        std::fs::remove_dir_all(album).unwrap();
+    }
+}
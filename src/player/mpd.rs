@@ -0,0 +1,813 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use super::{
+    detect_streaming_source, next_loop_status, parse_track_id, PlaybackControl, PlayerBackend,
+    PlayerEvent, PlayerMetadata,
+};
+
+const DEFAULT_HOST: &str = "localhost";
+const DEFAULT_PORT: u16 = 6600;
+// Regular commands answer quickly; the idle connection has no timeout
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `key: value` lines of a response, in order
+type Pairs = Vec<(String, String)>;
+
+/// Where MPD listens
+#[derive(Debug, Clone, PartialEq)]
+pub enum MpdAddress {
+    Tcp(String), // "host:port"
+    Unix(PathBuf),
+}
+
+impl MpdAddress {
+    /// Resolve `MPD_HOST` / `MPD_PORT` the way other MPD clients do.
+    ///
+    /// `MPD_HOST` is a host name or a socket path, optionally prefixed with
+    /// `password@`. Returns the address and the password.
+    pub fn from_host(host: Option<&str>, port: Option<u16>) -> (Self, Option<String>) {
+        let host = host.unwrap_or(DEFAULT_HOST);
+        let (password, host) = match host.rsplit_once('@') {
+            Some((password, host)) if !password.is_empty() => (Some(password.to_string()), host),
+            _ => (None, host),
+        };
+
+        let address = if host.starts_with('/') {
+            MpdAddress::Unix(PathBuf::from(host))
+        } else {
+            MpdAddress::Tcp(format!("{host}:{}", port.unwrap_or(DEFAULT_PORT)))
+        };
+
+        (address, password)
+    }
+}
+
+trait Stream: Read + Write + Send {}
+impl Stream for TcpStream {}
+impl Stream for UnixStream {}
+
+/// One connection speaking MPD's line based text protocol
+struct MpdConnection {
+    reader: BufReader<Box<dyn Stream>>,
+}
+
+impl MpdConnection {
+    fn connect(
+        address: &MpdAddress,
+        password: Option<&str>,
+        timeout: Option<Duration>,
+    ) -> Result<Self> {
+        let stream: Box<dyn Stream> = match address {
+            MpdAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_read_timeout(timeout)?;
+                Box::new(stream)
+            }
+            MpdAddress::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(timeout)?;
+                Box::new(stream)
+            }
+        };
+
+        let mut connection = Self {
+            reader: BufReader::new(stream),
+        };
+
+        let greeting = connection.read_line()?;
+        if !greeting.starts_with("OK MPD ") {
+            return Err(anyhow!("Not an MPD server: {}", greeting));
+        }
+
+        if let Some(password) = password {
+            connection.command(&format!("password {}", quote(password)))?;
+        }
+
+        Ok(connection)
+    }
+
+    /// Run `command` and return its `key: value` pairs
+    fn command(&mut self, command: &str) -> Result<Pairs> {
+        Ok(self.binary_command(command)?.0)
+    }
+
+    /// Run `command`, also collecting the payload of `binary:` responses
+    fn binary_command(&mut self, command: &str) -> Result<(Pairs, Vec<u8>)> {
+        let stream = self.reader.get_mut();
+        stream.write_all(format!("{command}\n").as_bytes())?;
+        stream.flush()?;
+
+        let mut pairs = Vec::new();
+        let mut binary = Vec::new();
+
+        loop {
+            let line = self.read_line()?;
+
+            if line == "OK" {
+                return Ok((pairs, binary));
+            }
+            if line.starts_with("ACK ") {
+                return Err(anyhow!("MPD error: {}", line));
+            }
+
+            let Some((key, value)) = line.split_once(": ") else {
+                continue;
+            };
+
+            if key == "binary" {
+                let length: usize = value.parse()?;
+                binary.resize(length, 0);
+                self.reader.read_exact(&mut binary)?;
+                // The payload is followed by a newline
+                self.read_line()?;
+            } else {
+                pairs.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    /// Read a whole picture with `albumart` or `readpicture`, which hand it
+    /// out in chunks. `None` when the song has no such picture.
+    fn read_picture(&mut self, command: &str, uri: &str) -> Result<Option<Vec<u8>>> {
+        let mut picture = Vec::new();
+
+        loop {
+            let (pairs, chunk) =
+                self.binary_command(&format!("{command} {} {}", quote(uri), picture.len()))?;
+
+            let Some(size) = lookup(&pairs, "size").and_then(|s| s.parse::<usize>().ok()) else {
+                return Ok(None);
+            };
+
+            if chunk.is_empty() {
+                break;
+            }
+            picture.extend_from_slice(&chunk);
+            if picture.len() >= size {
+                break;
+            }
+        }
+
+        Ok(Some(picture))
+    }
+}
+
+/// Reads the current song from an MPD server, without an MPRIS bridge
+pub struct MpdBackend {
+    address: MpdAddress,
+    password: Option<String>,
+    connection: Option<MpdConnection>,
+}
+
+impl MpdBackend {
+    /// Connect right away, so a wrong address is reported at startup
+    pub fn connect(address: MpdAddress, password: Option<String>) -> Result<Self> {
+        let connection =
+            MpdConnection::connect(&address, password.as_deref(), Some(COMMAND_TIMEOUT))?;
+
+        Ok(Self {
+            address,
+            password,
+            connection: Some(connection),
+        })
+    }
+
+    /// Run `f` on the connection, reconnecting once if MPD dropped it
+    /// (it closes idle clients after a while)
+    fn with_connection<T>(
+        &mut self,
+        mut f: impl FnMut(&mut MpdConnection) -> Result<T>,
+    ) -> Result<T> {
+        for attempt in 0..2 {
+            let connection = match self.connection {
+                Some(ref mut connection) => connection,
+                None => self.connection.insert(MpdConnection::connect(
+                    &self.address,
+                    self.password.as_deref(),
+                    Some(COMMAND_TIMEOUT),
+                )?),
+            };
+
+            match f(connection) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let connection_lost = e.downcast_ref::<io::Error>().is_some();
+                    if connection_lost {
+                        self.connection = None;
+                    }
+                    if !connection_lost || attempt == 1 {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        unreachable!("the second attempt always returns")
+    }
+
+    fn mpd_status(&mut self) -> Result<Pairs> {
+        self.with_connection(|connection| connection.command("status"))
+    }
+}
+
+impl PlayerBackend for MpdBackend {
+    fn name(&self) -> &str {
+        "mpd"
+    }
+
+    fn current_track(&mut self) -> Result<PlayerMetadata> {
+        let (status, song) = self.with_connection(|connection| {
+            Ok((
+                connection.command("status")?,
+                connection.command("currentsong")?,
+            ))
+        })?;
+
+        metadata_from_mpd(&status, &song)
+    }
+
+    fn status(&mut self) -> Result<String> {
+        let status = self.mpd_status()?;
+        lookup(&status, "state")
+            .map(playback_status)
+            .ok_or_else(|| anyhow!("MPD did not report its state"))
+    }
+
+    fn cover_art(&mut self) -> Result<Option<Vec<u8>>> {
+        self.with_connection(|connection| {
+            let song = connection.command("currentsong")?;
+            let Some(uri) = lookup(&song, "file") else {
+                return Ok(None);
+            };
+
+            // Embedded pictures first, then cover files next to the song.
+            // Older servers don't know these commands and answer with ACK.
+            for command in ["readpicture", "albumart"] {
+                match connection.read_picture(command, uri) {
+                    Ok(Some(picture)) => return Ok(Some(picture)),
+                    Ok(None) => {}
+                    Err(e) if e.downcast_ref::<io::Error>().is_some() => return Err(e),
+                    Err(_) => {}
+                }
+            }
+
+            Ok(None)
+        })
+    }
+
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let status = self.mpd_status()?;
+        let flag = |key: &str| lookup(&status, key) == Some("1");
+
+        let commands: Vec<String> = match control {
+            PlaybackControl::PlayPause => {
+                if lookup(&status, "state") == Some("play") {
+                    vec!["pause 1".to_string()]
+                } else {
+                    vec!["play".to_string()]
+                }
+            }
+            PlaybackControl::Next => vec!["next".to_string()],
+            PlaybackControl::Previous => vec!["previous".to_string()],
+            PlaybackControl::Seek(seconds) => vec![format!("seekcur {seconds:+}")],
+            PlaybackControl::SeekTo(position) => {
+                vec![format!("seekcur {:.3}", position.as_secs_f64())]
+            }
+            PlaybackControl::Volume(percent) => {
+                // -1 or missing when MPD has no mixer
+                let volume: i32 = lookup(&status, "volume")
+                    .and_then(|v| v.parse().ok())
+                    .filter(|v| *v >= 0)
+                    .ok_or_else(|| anyhow!("MPD has no volume control"))?;
+                vec![format!("setvol {}", (volume + percent).clamp(0, 100))]
+            }
+            PlaybackControl::ToggleShuffle => {
+                vec![format!("random {}", u8::from(!flag("random")))]
+            }
+            PlaybackControl::ToggleLoop => {
+                let current = match (flag("repeat"), flag("single")) {
+                    (false, _) => "None",
+                    (true, false) => "Playlist",
+                    (true, true) => "Track",
+                };
+                let (repeat, single) = match next_loop_status(current) {
+                    "Playlist" => (1, 0),
+                    "Track" => (1, 1),
+                    _ => (0, 0),
+                };
+                vec![format!("repeat {repeat}"), format!("single {single}")]
+            }
+        };
+
+        self.with_connection(|connection| {
+            for command in &commands {
+                connection.command(command)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        // `idle` blocks the connection, so it gets one of its own
+        let mut connection = MpdConnection::connect(&self.address, self.password.as_deref(), None)?;
+
+        thread::spawn(move || {
+            while connection.command("idle player").is_ok() {
+                if events.send(PlayerEvent::Changed).is_err() {
+                    return;
+                }
+            }
+            let _ = events.send(PlayerEvent::SubscriptionEnded);
+        });
+
+        Ok(true)
+    }
+}
+
+/// Build `PlayerMetadata` from the responses to `status` and `currentsong`
+fn metadata_from_mpd(
+    status: &[(String, String)],
+    song: &[(String, String)],
+) -> Result<PlayerMetadata> {
+    let tags = collect_tags(song);
+    let tag = |key: &str| tags.get(key).filter(|v| !v.is_empty()).cloned();
+
+    let file = tag("file").ok_or_else(|| anyhow!("MPD has no current song"))?;
+
+    // Streams often only have a Name, local files may have no tags at all
+    let title = tag("Title")
+        .or_else(|| tag("Name"))
+        .unwrap_or_else(|| file_stem(&file));
+
+    let seconds = |value: Option<&str>| {
+        value
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(Duration::from_secs_f64)
+    };
+    // "3/12" style numbers
+    let number = |key: &str| {
+        tag(key)
+            .and_then(|v| v.split('/').next()?.trim().parse::<u32>().ok())
+            .filter(|n| *n > 0)
+    };
+
+    let length = seconds(lookup(status, "duration"))
+        .or_else(|| seconds(tag("duration").as_deref()))
+        .or_else(|| seconds(tag("Time").as_deref()));
+
+    Ok(PlayerMetadata {
+        artist: tag("Artist").unwrap_or_default(),
+        title,
+        album: tag("Album"),
+        position: seconds(lookup(status, "elapsed")),
+        length,
+        // Library songs are paths relative to the music directory
+        streaming_source: if file.contains("://") {
+            detect_streaming_source(&file)
+        } else {
+            Some("Local File".to_string())
+        },
+        art_url: None, // cover bytes come from `cover_art`
+        status: lookup(status, "state").map(playback_status),
+        rate: None,
+        player: Some("mpd".to_string()),
+        album_artist: tag("AlbumArtist"),
+        track_number: number("Track"),
+        disc_number: number("Disc"),
+        genre: tag("Genre"),
+        composer: tag("Composer"),
+        user_rating: None,
+        track_id: tag("Id").and_then(|id| parse_track_id(&format!("/org/mpd/Tracks/{id}"))),
+        // Library songs are relative to MPD's music directory, which we
+        // can't see
+        url: Some(file).filter(|file| file.starts_with('/') || file.contains("://")),
+    })
+}
+
+/// Song tags by name. Repeated tags (several artists) are joined.
+fn collect_tags(pairs: &[(String, String)]) -> HashMap<String, String> {
+    let mut tags: HashMap<String, String> = HashMap::new();
+    for (key, value) in pairs {
+        tags.entry(key.clone())
+            .and_modify(|existing| {
+                existing.push_str(", ");
+                existing.push_str(value);
+            })
+            .or_insert_with(|| value.clone());
+    }
+    tags
+}
+
+fn lookup<'a>(pairs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    pairs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value.as_str())
+}
+
+/// MPD's `play` / `pause` / `stop` as MPRIS playback status
+fn playback_status(state: &str) -> String {
+    match state {
+        "play" => "Playing",
+        "pause" => "Paused",
+        _ => "Stopped",
+    }
+    .to_string()
+}
+
+fn file_stem(uri: &str) -> String {
+    let name = uri.rsplit('/').next().unwrap_or(uri);
+    match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// Quote a command argument
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::os::unix::net::UnixListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    type Responder = Arc<dyn Fn(&str) -> Vec<u8> + Send + Sync>;
+
+    static SOCKET_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Scripted stand-in for an MPD server. Every command line is recorded
+    /// and answered by the responder.
+    pub(crate) struct FakeMpd {
+        pub address: MpdAddress,
+        commands: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeMpd {
+        pub(crate) fn tcp(responder: impl Fn(&str) -> Vec<u8> + Send + Sync + 'static) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = MpdAddress::Tcp(listener.local_addr().unwrap().to_string());
+            let fake = Self::new(address);
+
+            let (commands, responder): (_, Responder) =
+                (fake.commands.clone(), Arc::new(responder));
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    serve(stream, commands.clone(), responder.clone());
+                }
+            });
+
+            fake
+        }
+
+        pub(crate) fn unix(responder: impl Fn(&str) -> Vec<u8> + Send + Sync + 'static) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "trackwatch-test-mpd-{}-{}.sock",
+                std::process::id(),
+                SOCKET_COUNTER.fetch_add(1, Ordering::SeqCst)
+            ));
+            let _ = std::fs::remove_file(&path);
+            let listener = UnixListener::bind(&path).unwrap();
+            let fake = Self::new(MpdAddress::Unix(path));
+
+            let (commands, responder): (_, Responder) =
+                (fake.commands.clone(), Arc::new(responder));
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    serve(stream, commands.clone(), responder.clone());
+                }
+            });
+
+            fake
+        }
+
+        fn new(address: MpdAddress) -> Self {
+            Self {
+                address,
+                commands: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        /// Commands received so far, without the connection's reads of
+        /// `status`
+        pub(crate) fn commands(&self) -> Vec<String> {
+            self.commands
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|command| *command != "status")
+                .cloned()
+                .collect()
+        }
+    }
+
+    impl Drop for FakeMpd {
+        fn drop(&mut self) {
+            if let MpdAddress::Unix(ref path) = self.address {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn serve<S: Read + Write + Send + 'static>(
+        mut stream: S,
+        commands: Arc<Mutex<Vec<String>>>,
+        responder: Responder,
+    ) {
+        thread::spawn(move || {
+            if stream.write_all(b"OK MPD 0.23.5\n").is_err() {
+                return;
+            }
+
+            let mut line = Vec::new();
+            let mut byte = [0u8; 1];
+            while stream.read_exact(&mut byte).is_ok() {
+                if byte[0] != b'\n' {
+                    line.push(byte[0]);
+                    continue;
+                }
+
+                let command = String::from_utf8_lossy(&line).to_string();
+                line.clear();
+                commands.lock().unwrap().push(command.clone());
+                if stream.write_all(&responder(&command)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    pub(crate) fn ok(body: &str) -> Vec<u8> {
+        format!("{body}OK\n").into_bytes()
+    }
+
+    const STATUS: &str = "volume: 60\nrepeat: 0\nrandom: 0\nsingle: 0\nstate: play\nsong: 3\nsongid: 42\nelapsed: 30.512\nduration: 252.000\n";
+    const CURRENT_SONG: &str = "file: Fallujah/Xenotaph/03 Kaleidoscopic Waves.flac\nArtist: Fallujah\nAlbumArtist: Fallujah\nTitle: Kaleidoscopic Waves\nAlbum: Xenotaph\nTrack: 3/9\nDisc: 1\nGenre: Technical Death Metal\nTime: 252\nduration: 252.000\nPos: 3\nId: 42\n";
+
+    fn default_responder(command: &str) -> Vec<u8> {
+        match command {
+            "status" => ok(STATUS),
+            "currentsong" => ok(CURRENT_SONG),
+            _ if command.starts_with("readpicture") => ok(""),
+            _ if command.starts_with("albumart") => {
+                b"ACK [50@0] {albumart} No file exists\n".to_vec()
+            }
+            _ => ok(""),
+        }
+    }
+
+    fn pairs(text: &str) -> Pairs {
+        text.lines()
+            .filter_map(|line| line.split_once(": "))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_metadata_from_mpd() {
+        let metadata = metadata_from_mpd(&pairs(STATUS), &pairs(CURRENT_SONG)).unwrap();
+
+        assert_eq!(metadata.artist, "Fallujah");
+        assert_eq!(metadata.title, "Kaleidoscopic Waves");
+        assert_eq!(metadata.album, Some("Xenotaph".to_string()));
+        assert_eq!(metadata.position, Some(Duration::from_millis(30_512)));
+        assert_eq!(metadata.length, Some(Duration::from_secs(252)));
+        assert_eq!(metadata.status, Some("Playing".to_string()));
+        assert_eq!(metadata.player, Some("mpd".to_string()));
+        assert_eq!(metadata.track_number, Some(3));
+        assert_eq!(metadata.disc_number, Some(1));
+        assert_eq!(metadata.genre, Some("Technical Death Metal".to_string()));
+        assert_eq!(metadata.track_id, Some("/org/mpd/Tracks/42".to_string()));
+        assert_eq!(metadata.streaming_source, Some("Local File".to_string()));
+    }
+
+    #[test]
+    fn test_metadata_from_mpd_without_tags() {
+        let song = pairs("file: https://radio.example.com/stream.mp3\nName: Example Radio\nArtist: A\nArtist: B\n");
+        let metadata = metadata_from_mpd(&pairs("state: pause\n"), &song).unwrap();
+        assert_eq!(metadata.title, "Example Radio");
+        assert_eq!(metadata.artist, "A, B");
+        assert_eq!(metadata.status, Some("Paused".to_string()));
+        assert_eq!(metadata.length, None);
+
+        let song = pairs("file: music/Some Song.mp3\n");
+        assert_eq!(
+            metadata_from_mpd(&[], &song).unwrap().title,
+            "Some Song".to_string()
+        );
+
+        // Empty queue
+        assert!(metadata_from_mpd(&pairs("state: stop\n"), &[]).is_err());
+    }
+
+    #[test]
+    fn test_mpd_address_from_host() {
+        assert_eq!(
+            MpdAddress::from_host(None, None),
+            (MpdAddress::Tcp("localhost:6600".to_string()), None)
+        );
+        assert_eq!(
+            MpdAddress::from_host(Some("music.lan"), Some(6601)),
+            (MpdAddress::Tcp("music.lan:6601".to_string()), None)
+        );
+        assert_eq!(
+            MpdAddress::from_host(Some("secret@/run/mpd/socket"), None),
+            (
+                MpdAddress::Unix(PathBuf::from("/run/mpd/socket")),
+                Some("secret".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn test_current_track_over_tcp() {
+        let server = FakeMpd::tcp(default_responder);
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+
+        let metadata = backend.current_track().unwrap();
+        assert_eq!(metadata.title, "Kaleidoscopic Waves");
+        assert_eq!(backend.status().unwrap(), "Playing");
+    }
+
+    #[test]
+    fn test_current_track_over_unix_socket_with_password() {
+        let server = FakeMpd::unix(default_responder);
+        let mut backend =
+            MpdBackend::connect(server.address.clone(), Some("hunter2".to_string())).unwrap();
+
+        assert_eq!(backend.current_track().unwrap().artist, "Fallujah");
+        assert_eq!(server.commands()[0], "password \"hunter2\"");
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let server = FakeMpd::tcp(|command| match command {
+            "status" => ok("state: stop\n"),
+            _ => b"ACK [5@0] {currentsong} unknown command\n".to_vec(),
+        });
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+
+        let error = backend.current_track().unwrap_err().to_string();
+        assert!(error.contains("unknown command"), "{error}");
+        // The connection is still usable after an ACK
+        assert_eq!(backend.status().unwrap(), "Stopped");
+    }
+
+    #[test]
+    fn test_controls() {
+        let server = FakeMpd::tcp(default_responder);
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+
+        for control in [
+            PlaybackControl::PlayPause,
+            PlaybackControl::Next,
+            PlaybackControl::Previous,
+            PlaybackControl::Seek(-5),
+            PlaybackControl::SeekTo(Duration::from_millis(83_450)),
+            PlaybackControl::Volume(5),
+            PlaybackControl::Volume(-100),
+            PlaybackControl::ToggleShuffle,
+            PlaybackControl::ToggleLoop,
+        ] {
+            backend.control(control).unwrap();
+        }
+
+        assert_eq!(
+            server.commands(),
+            vec![
+                "pause 1",
+                "next",
+                "previous",
+                "seekcur -5",
+                "seekcur 83.450",
+                "setvol 65",
+                "setvol 0",
+                "random 1",
+                "repeat 1",
+                "single 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cover_art_is_read_in_chunks() {
+        let cover: Vec<u8> = (0u8..10).collect();
+        let server = FakeMpd::tcp(move |command| {
+            if let Some(args) = command.strip_prefix("readpicture ") {
+                let offset: usize = args.rsplit(' ').next().unwrap().parse().unwrap();
+                let chunk = &cover[offset..(offset + 4).min(cover.len())];
+                let mut response =
+                    format!("size: {}\nbinary: {}\n", cover.len(), chunk.len()).into_bytes();
+                response.extend_from_slice(chunk);
+                response.extend_from_slice(b"\nOK\n");
+                response
+            } else {
+                default_responder(command)
+            }
+        });
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+
+        assert_eq!(
+            backend.cover_art().unwrap(),
+            Some((0u8..10).collect::<Vec<u8>>())
+        );
+        assert_eq!(
+            server.commands()[1..],
+            [
+                "readpicture \"Fallujah/Xenotaph/03 Kaleidoscopic Waves.flac\" 0",
+                "readpicture \"Fallujah/Xenotaph/03 Kaleidoscopic Waves.flac\" 4",
+                "readpicture \"Fallujah/Xenotaph/03 Kaleidoscopic Waves.flac\" 8",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cover_art_falls_back_to_albumart() {
+        let server = FakeMpd::tcp(|command| {
+            if command.starts_with("albumart") {
+                b"size: 3\nbinary: 3\n\x01\x02\x03\nOK\n".to_vec()
+            } else {
+                default_responder(command)
+            }
+        });
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+        assert_eq!(backend.cover_art().unwrap(), Some(vec![1, 2, 3]));
+
+        // Neither embedded nor folder art
+        let server = FakeMpd::tcp(default_responder);
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+        assert_eq!(backend.cover_art().unwrap(), None);
+    }
+
+    #[test]
+    fn test_subscribe_uses_idle() {
+        let (trigger, idle_wakeups) = mpsc::channel::<()>();
+        let idle_wakeups = Mutex::new(idle_wakeups);
+        let server = FakeMpd::tcp(move |command| {
+            if command == "idle player" {
+                // Block like MPD does until the test says something changed
+                match idle_wakeups.lock().unwrap().recv() {
+                    Ok(()) => ok("changed: player\n"),
+                    Err(_) => Vec::new(),
+                }
+            } else {
+                default_responder(command)
+            }
+        });
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        assert!(backend.subscribe(tx).unwrap());
+
+        trigger.send(()).unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            PlayerEvent::Changed
+        );
+    }
+
+    #[test]
+    fn test_reconnects_after_connection_loss() {
+        let server = FakeMpd::tcp(default_responder);
+        let mut backend = MpdBackend::connect(server.address.clone(), None).unwrap();
+
+        // Pretend MPD closed the connection
+        let (stream, closed) = UnixStream::pair().unwrap();
+        drop(closed);
+        backend.connection = Some(MpdConnection {
+            reader: BufReader::new(Box::new(stream)),
+        });
+
+        assert_eq!(
+            backend.current_track().unwrap().title,
+            "Kaleidoscopic Waves"
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "\"plain\"");
+        assert_eq!(quote(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+}
@@ -0,0 +1,898 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+use zbus::blocking::fdo::{DBusProxy, PropertiesProxy};
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type as MessageType;
+use zbus::names::InterfaceName;
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+use zbus::MatchRule;
+
+use super::{
+    detect_streaming_source, next_loop_status, parse_track_id, PlaybackControl, PlayerBackend,
+    PlayerEvent, PlayerInfo, PlayerMetadata,
+};
+
+const MPRIS_BUS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Talks to MPRIS players directly over the D-Bus session bus.
+///
+/// All player properties are fetched with a single `GetAll` call, so artist,
+/// title, status and position always describe the same track.
+pub struct MprisBackend {
+    connection: Connection,
+    active: Option<String>, // player name without the bus prefix
+}
+
+impl MprisBackend {
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_connection(Connection::session()?))
+    }
+
+    pub fn with_connection(connection: Connection) -> Self {
+        Self {
+            connection,
+            active: None,
+        }
+    }
+
+    /// List the bus names of all MPRIS players, sorted by name
+    pub fn player_bus_names(&self) -> Result<Vec<String>> {
+        let dbus = DBusProxy::new(&self.connection)?;
+
+        let mut players: Vec<String> = dbus
+            .list_names()?
+            .into_iter()
+            .map(|name| name.to_string())
+            .filter(|name| name.starts_with(MPRIS_BUS_PREFIX))
+            .collect();
+        players.sort();
+
+        Ok(players)
+    }
+
+    pub fn get_current_track(&self) -> Result<PlayerMetadata> {
+        let bus_name = self.active_player()?;
+        self.get_track(&bus_name)
+    }
+
+    pub fn get_track(&self, bus_name: &str) -> Result<PlayerMetadata> {
+        let values = self
+            .properties(bus_name)?
+            .get_all(InterfaceName::from_static_str(MPRIS_PLAYER_INTERFACE)?)?;
+
+        let mut metadata = metadata_from_properties(&values)?;
+        metadata.player = Some(player_name(bus_name).to_string());
+        Ok(metadata)
+    }
+
+    pub fn get_status(&self, bus_name: &str) -> Result<String> {
+        let value = self.get_player_property(bus_name, "PlaybackStatus")?;
+
+        value_as_string(&value).ok_or_else(|| anyhow!("Invalid PlaybackStatus"))
+    }
+
+    /// Call a method of the player interface, e.g. `PlayPause`
+    fn call_player_method<B>(&self, bus_name: &str, method: &str, body: &B) -> Result<()>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        self.connection.call_method(
+            Some(bus_name),
+            MPRIS_OBJECT_PATH,
+            Some(MPRIS_PLAYER_INTERFACE),
+            method,
+            body,
+        )?;
+        Ok(())
+    }
+
+    fn get_player_property(&self, bus_name: &str, name: &str) -> Result<OwnedValue> {
+        Ok(self.properties(bus_name)?.get(
+            InterfaceName::from_static_str(MPRIS_PLAYER_INTERFACE)?,
+            name,
+        )?)
+    }
+
+    fn set_player_property(&self, bus_name: &str, name: &str, value: Value) -> Result<()> {
+        self.properties(bus_name)?.set(
+            InterfaceName::from_static_str(MPRIS_PLAYER_INTERFACE)?,
+            name,
+            value,
+        )?;
+        Ok(())
+    }
+
+    fn active_player(&self) -> Result<String> {
+        if let Some(ref name) = self.active {
+            return Ok(format!("{MPRIS_BUS_PREFIX}{name}"));
+        }
+
+        self.player_bus_names()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No MPRIS player found"))
+    }
+
+    fn properties(&self, bus_name: &str) -> Result<PropertiesProxy<'static>> {
+        Ok(PropertiesProxy::builder(&self.connection)
+            .destination(bus_name.to_string())?
+            .path(MPRIS_OBJECT_PATH)?
+            .build()?)
+    }
+}
+
+impl PlayerBackend for MprisBackend {
+    fn name(&self) -> &str {
+        "mpris"
+    }
+
+    fn current_track(&mut self) -> Result<PlayerMetadata> {
+        self.get_current_track()
+    }
+
+    fn status(&mut self) -> Result<String> {
+        let bus_name = self.active_player()?;
+        self.get_status(&bus_name)
+    }
+
+    fn list_players(&mut self) -> Result<Vec<PlayerInfo>> {
+        Ok(self
+            .player_bus_names()?
+            .into_iter()
+            .map(|bus_name| PlayerInfo {
+                status: self.get_status(&bus_name).ok(),
+                name: player_name(&bus_name).to_string(),
+            })
+            .collect())
+    }
+
+    fn set_active_player(&mut self, player: Option<&str>) {
+        self.active = player.map(|name| name.to_string());
+    }
+
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let bus_name = self.active_player()?;
+
+        match control {
+            PlaybackControl::PlayPause => self.call_player_method(&bus_name, "PlayPause", &()),
+            PlaybackControl::Next => self.call_player_method(&bus_name, "Next", &()),
+            PlaybackControl::Previous => self.call_player_method(&bus_name, "Previous", &()),
+            // Seek takes a relative offset in microseconds
+            PlaybackControl::Seek(seconds) => {
+                self.call_player_method(&bus_name, "Seek", &(seconds * 1_000_000))
+            }
+            // SetPosition is ignored unless it names the current track
+            PlaybackControl::SeekTo(position) => {
+                let track_id = self
+                    .current_track()?
+                    .track_id
+                    .ok_or_else(|| anyhow!("Player has no track id to seek in"))?;
+                let track_id = ObjectPath::try_from(track_id.as_str())?;
+                self.call_player_method(
+                    &bus_name,
+                    "SetPosition",
+                    &(track_id, position.as_micros() as i64),
+                )
+            }
+            PlaybackControl::Volume(percent) => {
+                let value = self.get_player_property(&bus_name, "Volume")?;
+                let volume = value_as_f64(&value).ok_or_else(|| anyhow!("Invalid Volume"))?;
+                let volume = (volume + f64::from(percent) / 100.0).clamp(0.0, 1.0);
+                self.set_player_property(&bus_name, "Volume", Value::from(volume))
+            }
+            PlaybackControl::ToggleShuffle => {
+                let value = self.get_player_property(&bus_name, "Shuffle")?;
+                let shuffle = value_as_bool(&value).ok_or_else(|| anyhow!("Invalid Shuffle"))?;
+                self.set_player_property(&bus_name, "Shuffle", Value::from(!shuffle))
+            }
+            PlaybackControl::ToggleLoop => {
+                let value = self.get_player_property(&bus_name, "LoopStatus")?;
+                let current =
+                    value_as_string(&value).ok_or_else(|| anyhow!("Invalid LoopStatus"))?;
+                self.set_player_property(
+                    &bus_name,
+                    "LoopStatus",
+                    Value::from(next_loop_status(&current)),
+                )
+            }
+        }
+    }
+
+    fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        // Any signal on the player object: PropertiesChanged and Seeked
+        let player_signals = MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .path(MPRIS_OBJECT_PATH)?
+            .build();
+
+        // Players appearing on or leaving the bus
+        let player_names = MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .sender("org.freedesktop.DBus")?
+            .interface("org.freedesktop.DBus")?
+            .member("NameOwnerChanged")?
+            .arg0ns("org.mpris.MediaPlayer2")?
+            .build();
+
+        forward_signals(&self.connection, player_signals, events.clone())?;
+        forward_signals(&self.connection, player_names, events)?;
+
+        Ok(true)
+    }
+}
+
+/// `org.mpris.MediaPlayer2.spotify` -> `spotify`
+fn player_name(bus_name: &str) -> &str {
+    bus_name.strip_prefix(MPRIS_BUS_PREFIX).unwrap_or(bus_name)
+}
+
+/// Turn every message matching `rule` into a change notification
+fn forward_signals(
+    connection: &Connection,
+    rule: MatchRule<'static>,
+    events: Sender<PlayerEvent>,
+) -> Result<()> {
+    let messages = MessageIterator::for_match_rule(rule, connection, None)?;
+
+    thread::spawn(move || {
+        for message in messages {
+            if message.is_err() {
+                break;
+            }
+            if events.send(PlayerEvent::Changed).is_err() {
+                return;
+            }
+        }
+        let _ = events.send(PlayerEvent::SubscriptionEnded);
+    });
+
+    Ok(())
+}
+
+/// Build `PlayerMetadata` from the property map of `org.mpris.MediaPlayer2.Player`
+fn metadata_from_properties(properties: &HashMap<String, OwnedValue>) -> Result<PlayerMetadata> {
+    let metadata = properties
+        .get("Metadata")
+        .map(|value| dict_entries(value))
+        .unwrap_or_default();
+
+    let title = metadata
+        .get("xesam:title")
+        .and_then(|v| value_as_string(v))
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| anyhow!("No title found"))?;
+
+    let artist = metadata
+        .get("xesam:artist")
+        .and_then(|v| value_as_string(v))
+        .unwrap_or_default();
+
+    let album = metadata
+        .get("xesam:album")
+        .and_then(|v| value_as_string(v))
+        .filter(|a| !a.is_empty());
+
+    // mpris:length and Position are both in microseconds
+    let length = metadata
+        .get("mpris:length")
+        .and_then(|v| value_as_i64(v))
+        .and_then(micros_to_duration);
+
+    let position = properties
+        .get("Position")
+        .and_then(|v| value_as_i64(v))
+        .and_then(micros_to_duration);
+
+    let url = metadata
+        .get("xesam:url")
+        .and_then(|v| value_as_string(v))
+        .filter(|u| !u.is_empty());
+    let streaming_source = url.as_deref().and_then(detect_streaming_source);
+
+    let art_url = metadata
+        .get("mpris:artUrl")
+        .and_then(|v| value_as_string(v))
+        .filter(|u| !u.is_empty());
+
+    let status = properties
+        .get("PlaybackStatus")
+        .and_then(|v| value_as_string(v));
+
+    let rate = properties.get("Rate").and_then(|v| value_as_f64(v));
+
+    let text = |key: &str| {
+        metadata
+            .get(key)
+            .and_then(|v| value_as_string(v))
+            .filter(|s| !s.is_empty())
+    };
+    let number = |key: &str| {
+        metadata
+            .get(key)
+            .and_then(|v| value_as_i64(v))
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|n| *n > 0)
+    };
+
+    let user_rating = metadata
+        .get("xesam:userRating")
+        .and_then(|v| value_as_f64(v));
+
+    let track_id = metadata
+        .get("mpris:trackid")
+        .and_then(|v| value_as_string(v))
+        .and_then(|id| parse_track_id(&id));
+
+    Ok(PlayerMetadata {
+        artist,
+        title,
+        album,
+        position,
+        length,
+        streaming_source,
+        art_url,
+        status,
+        rate,
+        player: None, // filled in by the caller, who knows the bus name
+        album_artist: text("xesam:albumArtist"),
+        track_number: number("xesam:trackNumber"),
+        disc_number: number("xesam:discNumber"),
+        genre: text("xesam:genre"),
+        composer: text("xesam:composer"),
+        user_rating,
+        track_id,
+        url,
+    })
+}
+
+fn micros_to_duration(micros: i64) -> Option<Duration> {
+    u64::try_from(micros).ok().map(Duration::from_micros)
+}
+
+fn dict_entries<'a>(value: &'a Value<'a>) -> HashMap<String, &'a Value<'a>> {
+    match value {
+        Value::Dict(dict) => dict
+            .iter()
+            .filter_map(|(key, value)| value_as_string(key).map(|key| (key, value)))
+            .collect(),
+        Value::Value(inner) => dict_entries(inner),
+        _ => HashMap::new(),
+    }
+}
+
+fn value_as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Str(s) => Some(s.to_string()),
+        Value::ObjectPath(path) => Some(path.to_string()),
+        // xesam:artist and friends are string lists
+        Value::Array(array) => {
+            let items: Vec<String> = array.inner().iter().filter_map(value_as_string).collect();
+            if items.is_empty() {
+                None
+            } else {
+                Some(items.join(", "))
+            }
+        }
+        Value::Value(inner) => value_as_string(inner),
+        _ => None,
+    }
+}
+
+fn value_as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::I64(v) => Some(*v),
+        Value::U64(v) => i64::try_from(*v).ok(),
+        Value::I32(v) => Some(i64::from(*v)),
+        Value::U32(v) => Some(i64::from(*v)),
+        Value::F64(v) => Some(*v as i64),
+        Value::Value(inner) => value_as_i64(inner),
+        _ => None,
+    }
+}
+
+fn value_as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(v) => Some(*v),
+        Value::Value(inner) => value_as_bool(inner),
+        _ => None,
+    }
+}
+
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::F64(v) => Some(*v),
+        Value::Value(inner) => value_as_f64(inner),
+        other => value_as_i64(other).map(|v| v as f64),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{BufRead, BufReader};
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use zbus::blocking::connection::Builder;
+    use zbus::zvariant::ObjectPath;
+
+    const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:tmpdir=/tmp</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+    static BUS_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// A private `dbus-daemon` so tests never touch the user's session bus
+    pub(crate) struct PrivateBus {
+        daemon: Child,
+        config_path: PathBuf,
+        pub address: String,
+    }
+
+    impl PrivateBus {
+        /// Returns None when `dbus-daemon` is not installed
+        pub(crate) fn start() -> Option<Self> {
+            let config_path = std::env::temp_dir().join(format!(
+                "trackwatch-test-bus-{}-{}.conf",
+                std::process::id(),
+                BUS_COUNTER.fetch_add(1, Ordering::SeqCst)
+            ));
+            fs::write(&config_path, BUS_CONFIG).ok()?;
+
+            let mut daemon = match Command::new("dbus-daemon")
+                .arg(format!("--config-file={}", config_path.display()))
+                .args(["--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+            {
+                Ok(daemon) => daemon,
+                Err(_) => {
+                    let _ = fs::remove_file(&config_path);
+                    return None;
+                }
+            };
+
+            let mut address = String::new();
+            let stdout = daemon.stdout.take()?;
+            BufReader::new(stdout).read_line(&mut address).ok()?;
+
+            Some(Self {
+                daemon,
+                config_path,
+                address: address.trim().to_string(),
+            })
+        }
+
+        pub(crate) fn connect(&self) -> Connection {
+            Builder::address(self.address.as_str())
+                .unwrap()
+                .build()
+                .unwrap()
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+            let _ = fs::remove_file(&self.config_path);
+        }
+    }
+
+    pub(crate) struct MockPlayer {
+        pub artist: Vec<String>,
+        pub title: String,
+        pub album: String,
+        pub url: String,
+        pub art_url: String,
+        pub length_us: i64,
+        pub position_us: i64,
+        pub status: String,
+        pub rate: f64,
+        pub volume: f64,
+        pub shuffle: bool,
+        pub loop_status: String,
+        pub track_id: String,
+    }
+
+    impl Default for MockPlayer {
+        fn default() -> Self {
+            Self {
+                artist: vec!["Fallujah".to_string()],
+                title: "Kaleidoscopic Waves".to_string(),
+                album: "Xenotaph".to_string(),
+                url: "https://tidal.com/track/12345".to_string(),
+                art_url: "https://example.com/cover.jpg".to_string(),
+                length_us: 252_000_000,
+                position_us: 30_500_000,
+                status: "Playing".to_string(),
+                rate: 1.0,
+                volume: 0.5,
+                shuffle: false,
+                loop_status: "None".to_string(),
+                track_id: "/org/mpris/MediaPlayer2/Track/1".to_string(),
+            }
+        }
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl MockPlayer {
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status.clone()
+        }
+
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<&str, Value<'_>> {
+            HashMap::from([
+                ("xesam:artist", Value::from(self.artist.clone())),
+                ("xesam:title", Value::from(self.title.as_str())),
+                ("xesam:album", Value::from(self.album.as_str())),
+                ("xesam:url", Value::from(self.url.as_str())),
+                ("mpris:artUrl", Value::from(self.art_url.as_str())),
+                ("mpris:length", Value::from(self.length_us)),
+                ("xesam:albumArtist", Value::from(vec!["Fallujah"])),
+                ("xesam:trackNumber", Value::from(3i32)),
+                ("xesam:discNumber", Value::from(1i32)),
+                ("xesam:genre", Value::from(vec!["Technical Death Metal"])),
+                ("xesam:composer", Value::from(vec!["Scott Carstairs"])),
+                ("xesam:userRating", Value::from(0.8f64)),
+                (
+                    "mpris:trackid",
+                    Value::from(ObjectPath::try_from(self.track_id.as_str()).unwrap()),
+                ),
+            ])
+        }
+
+        #[zbus(property)]
+        fn position(&self) -> i64 {
+            self.position_us
+        }
+
+        #[zbus(property)]
+        fn rate(&self) -> f64 {
+            self.rate
+        }
+
+        #[zbus(property)]
+        fn volume(&self) -> f64 {
+            self.volume
+        }
+
+        #[zbus(property)]
+        fn set_volume(&mut self, volume: f64) {
+            self.volume = volume;
+        }
+
+        #[zbus(property)]
+        fn shuffle(&self) -> bool {
+            self.shuffle
+        }
+
+        #[zbus(property)]
+        fn set_shuffle(&mut self, shuffle: bool) {
+            self.shuffle = shuffle;
+        }
+
+        #[zbus(property)]
+        fn loop_status(&self) -> String {
+            self.loop_status.clone()
+        }
+
+        #[zbus(property)]
+        fn set_loop_status(&mut self, loop_status: String) {
+            self.loop_status = loop_status;
+        }
+
+        fn play_pause(&mut self) {
+            self.status = if self.status == "Playing" {
+                "Paused".to_string()
+            } else {
+                "Playing".to_string()
+            };
+        }
+
+        fn next(&mut self) {
+            self.title = "Next Track".to_string();
+            self.position_us = 0;
+        }
+
+        fn previous(&mut self) {
+            self.title = "Previous Track".to_string();
+            self.position_us = 0;
+        }
+
+        fn seek(&mut self, offset: i64) {
+            self.position_us = (self.position_us + offset).max(0);
+        }
+
+        fn set_position(&mut self, track_id: ObjectPath<'_>, position: i64) {
+            if track_id.as_str() == self.track_id {
+                self.position_us = position;
+            }
+        }
+    }
+
+    /// Publish `player` on `bus` under `org.mpris.MediaPlayer2.<name>`
+    pub(crate) fn serve_mock_player(
+        bus: &PrivateBus,
+        name: &str,
+        player: MockPlayer,
+    ) -> Connection {
+        Builder::address(bus.address.as_str())
+            .unwrap()
+            .name(format!("{MPRIS_BUS_PREFIX}{name}"))
+            .unwrap()
+            .serve_at(MPRIS_OBJECT_PATH, player)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_get_current_track_from_mock_service() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let _service = serve_mock_player(&bus, "mock", MockPlayer::default());
+
+        let mut backend = MprisBackend::with_connection(bus.connect());
+        let metadata = backend.current_track().unwrap();
+
+        assert_eq!(metadata.artist, "Fallujah");
+        assert_eq!(metadata.title, "Kaleidoscopic Waves");
+        assert_eq!(metadata.album, Some("Xenotaph".to_string()));
+        assert_eq!(metadata.length, Some(Duration::from_secs(252)));
+        assert_eq!(metadata.position, Some(Duration::from_millis(30_500)));
+        assert_eq!(metadata.streaming_source, Some("Tidal".to_string()));
+        assert_eq!(
+            metadata.art_url,
+            Some("https://example.com/cover.jpg".to_string())
+        );
+        assert_eq!(metadata.status, Some("Playing".to_string()));
+        assert_eq!(metadata.rate, Some(1.0));
+        assert_eq!(metadata.album_artist, Some("Fallujah".to_string()));
+        assert_eq!(metadata.track_number, Some(3));
+        assert_eq!(metadata.disc_number, Some(1));
+        assert_eq!(metadata.genre, Some("Technical Death Metal".to_string()));
+        assert_eq!(metadata.composer, Some("Scott Carstairs".to_string()));
+        assert_eq!(metadata.user_rating, Some(0.8));
+        assert_eq!(
+            metadata.track_id,
+            Some("/org/mpris/MediaPlayer2/Track/1".to_string())
+        );
+        assert_eq!(backend.status().unwrap(), "Playing");
+    }
+
+    #[test]
+    fn test_list_players_only_returns_mpris_names() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let _spotify = serve_mock_player(&bus, "spotify", MockPlayer::default());
+        let _mpv = serve_mock_player(&bus, "mpv", MockPlayer::default());
+        let _unrelated = Builder::address(bus.address.as_str())
+            .unwrap()
+            .name("org.example.NotAPlayer")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let backend = MprisBackend::with_connection(bus.connect());
+        let players = backend.player_bus_names().unwrap();
+
+        assert_eq!(
+            players,
+            vec![
+                "org.mpris.MediaPlayer2.mpv".to_string(),
+                "org.mpris.MediaPlayer2.spotify".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_and_select_players() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let _spotify = serve_mock_player(&bus, "spotify", MockPlayer::default());
+        let _mpv = serve_mock_player(
+            &bus,
+            "mpv",
+            MockPlayer {
+                title: "Local File".to_string(),
+                status: "Paused".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let mut backend = MprisBackend::with_connection(bus.connect());
+        assert_eq!(
+            backend.list_players().unwrap(),
+            vec![
+                PlayerInfo {
+                    name: "mpv".to_string(),
+                    status: Some("Paused".to_string()),
+                },
+                PlayerInfo {
+                    name: "spotify".to_string(),
+                    status: Some("Playing".to_string()),
+                },
+            ]
+        );
+
+        backend.set_active_player(Some("spotify"));
+        let metadata = backend.current_track().unwrap();
+        assert_eq!(metadata.title, "Kaleidoscopic Waves");
+        assert_eq!(metadata.player, Some("spotify".to_string()));
+
+        backend.set_active_player(Some("mpv"));
+        assert_eq!(backend.current_track().unwrap().title, "Local File");
+        assert_eq!(backend.status().unwrap(), "Paused");
+
+        backend.set_active_player(Some("vlc"));
+        assert!(backend.current_track().is_err());
+    }
+
+    #[test]
+    fn test_get_current_track_without_players() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+
+        let mut backend = MprisBackend::with_connection(bus.connect());
+        assert!(backend.current_track().is_err());
+        assert!(backend.status().is_err());
+    }
+
+    #[test]
+    fn test_subscribe_reports_property_changes_and_new_players() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let service = serve_mock_player(&bus, "mock", MockPlayer::default());
+
+        let mut backend = MprisBackend::with_connection(bus.connect());
+        let (tx, rx) = mpsc::channel();
+        assert!(backend.subscribe(tx).unwrap());
+
+        // The player pauses
+        let player = service
+            .object_server()
+            .interface::<_, MockPlayer>(MPRIS_OBJECT_PATH)
+            .unwrap();
+        player.get_mut().status = "Paused".to_string();
+        zbus::block_on(
+            player
+                .get()
+                .playback_status_changed(player.signal_emitter()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            PlayerEvent::Changed
+        );
+        assert_eq!(backend.status().unwrap(), "Paused");
+
+        // A second player shows up
+        let _other = serve_mock_player(&bus, "other", MockPlayer::default());
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            PlayerEvent::Changed
+        );
+    }
+
+    #[test]
+    fn test_control_mock_player() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let service = serve_mock_player(&bus, "mock", MockPlayer::default());
+        let player = service
+            .object_server()
+            .interface::<_, MockPlayer>(MPRIS_OBJECT_PATH)
+            .unwrap();
+
+        let mut backend = MprisBackend::with_connection(bus.connect());
+
+        backend.control(PlaybackControl::PlayPause).unwrap();
+        assert_eq!(backend.status().unwrap(), "Paused");
+
+        backend.control(PlaybackControl::Seek(10)).unwrap();
+        backend.control(PlaybackControl::Seek(-5)).unwrap();
+        assert_eq!(player.get().position_us, 35_500_000);
+        backend
+            .control(PlaybackControl::SeekTo(Duration::from_millis(12_340)))
+            .unwrap();
+        assert_eq!(player.get().position_us, 12_340_000);
+
+        backend.control(PlaybackControl::Volume(10)).unwrap();
+        assert!((player.get().volume - 0.6).abs() < 1e-9);
+        backend.control(PlaybackControl::Volume(100)).unwrap();
+        assert_eq!(player.get().volume, 1.0);
+
+        backend.control(PlaybackControl::ToggleShuffle).unwrap();
+        assert!(player.get().shuffle);
+
+        backend.control(PlaybackControl::ToggleLoop).unwrap();
+        assert_eq!(player.get().loop_status, "Playlist");
+        backend.control(PlaybackControl::ToggleLoop).unwrap();
+        assert_eq!(player.get().loop_status, "Track");
+
+        backend.control(PlaybackControl::Next).unwrap();
+        assert_eq!(backend.current_track().unwrap().title, "Next Track");
+        backend.control(PlaybackControl::Previous).unwrap();
+        assert_eq!(backend.current_track().unwrap().title, "Previous Track");
+    }
+
+    #[test]
+    fn test_control_without_players() {
+        let Some(bus) = PrivateBus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+
+        let mut backend = MprisBackend::with_connection(bus.connect());
+        assert!(backend.control(PlaybackControl::PlayPause).is_err());
+    }
+
+    #[test]
+    fn test_metadata_from_properties_requires_title() {
+        let properties = HashMap::from([(
+            "PlaybackStatus".to_string(),
+            OwnedValue::try_from(Value::from("Playing")).unwrap(),
+        )]);
+
+        assert!(metadata_from_properties(&properties).is_err());
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        // Artist lists are joined
+        let artists = Value::from(vec!["Simon", "Garfunkel"]);
+        assert_eq!(
+            value_as_string(&artists),
+            Some("Simon, Garfunkel".to_string())
+        );
+
+        // Some players report mpris:length as an unsigned integer
+        assert_eq!(
+            value_as_i64(&Value::from(180_000_000u64)),
+            Some(180_000_000)
+        );
+        assert_eq!(value_as_i64(&Value::from(42i32)), Some(42));
+
+        // Values may arrive wrapped in a variant
+        let wrapped = Value::Value(Box::new(Value::from(1.5f64)));
+        assert_eq!(value_as_f64(&wrapped), Some(1.5));
+
+        // Negative positions are not valid durations
+        assert_eq!(micros_to_duration(-1), None);
+        assert_eq!(micros_to_duration(1_000_000), Some(Duration::from_secs(1)));
+    }
+}
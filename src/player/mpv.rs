@@ -0,0 +1,855 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use super::{
+    detect_streaming_source, next_loop_status, PlaybackControl, PlayerBackend, PlayerEvent,
+    PlayerMetadata,
+};
+use crate::tags;
+
+pub const DEFAULT_SOCKET: &str = "/tmp/mpvsocket";
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+// How often the event thread looks for a newly started mpv
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+// Properties that change when the track, its state or its position jumps.
+// `time-pos` is left out on purpose, the playback clock takes care of it.
+const OBSERVED_PROPERTIES: &[&str] = &[
+    "path",
+    "metadata",
+    "duration",
+    "pause",
+    "speed",
+    "chapter",
+    "idle-active",
+];
+
+/// One client connection to mpv's `--input-ipc-server` socket
+struct MpvConnection {
+    reader: BufReader<UnixStream>,
+    next_request_id: u64,
+}
+
+impl MpvConnection {
+    fn connect(socket: &Path, timeout: Option<Duration>) -> Result<Self> {
+        let stream = UnixStream::connect(socket)?;
+        stream.set_read_timeout(timeout)?;
+
+        Ok(Self {
+            reader: BufReader::new(stream),
+            next_request_id: 1,
+        })
+    }
+
+    fn send(&mut self, message: &Value) -> Result<()> {
+        let stream = self.reader.get_mut();
+        stream.write_all(format!("{message}\n").as_bytes())?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    /// Next message from mpv, a reply or an event
+    fn read_message(&mut self) -> Result<Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    /// Run a command and return mpv's error string and data
+    fn request(&mut self, command: Value) -> Result<(String, Value)> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        self.send(&json!({ "command": command, "request_id": request_id }))?;
+
+        loop {
+            let mut message = self.read_message()?;
+            // Events are sent to every client, skip them while waiting
+            if message.get("request_id").and_then(Value::as_u64) != Some(request_id) {
+                continue;
+            }
+
+            let error = message
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("success")
+                .to_string();
+            let data = message.get_mut("data").map(Value::take).unwrap_or_default();
+            return Ok((error, data));
+        }
+    }
+
+    fn command(&mut self, command: Value) -> Result<Value> {
+        let (error, data) = self.request(command.clone())?;
+        if error == "success" {
+            Ok(data)
+        } else {
+            Err(anyhow!("mpv rejected {}: {}", command, error))
+        }
+    }
+
+    /// Read a property, `None` while mpv has nothing to report for it
+    fn get_property(&mut self, name: &str) -> Result<Option<Value>> {
+        let (error, data) = self.request(json!(["get_property", name]))?;
+        match error.as_str() {
+            "success" => Ok(Some(data).filter(|value| !value.is_null())),
+            "property unavailable" => Ok(None),
+            _ => Err(anyhow!("mpv could not read {}: {}", name, error)),
+        }
+    }
+}
+
+/// Reads mpv through its JSON IPC socket, without needing mpv-mpris.
+///
+/// mpv has to be started with `--input-ipc-server=<socket>`. The backend
+/// connects lazily, so mpv may be started and quit while trackwatch runs.
+pub struct MpvBackend {
+    socket: PathBuf,
+    connection: Option<MpvConnection>,
+}
+
+impl MpvBackend {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        Self {
+            socket: socket.into(),
+            connection: None,
+        }
+    }
+
+    /// Run `f` on the connection, reconnecting once if mpv went away
+    fn with_connection<T>(
+        &mut self,
+        mut f: impl FnMut(&mut MpvConnection) -> Result<T>,
+    ) -> Result<T> {
+        for attempt in 0..2 {
+            let connection = match self.connection {
+                Some(ref mut connection) => connection,
+                None => self
+                    .connection
+                    .insert(MpvConnection::connect(&self.socket, Some(COMMAND_TIMEOUT))?),
+            };
+
+            match f(connection) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let connection_lost = e.downcast_ref::<io::Error>().is_some();
+                    if connection_lost {
+                        self.connection = None;
+                    }
+                    if !connection_lost || attempt == 1 {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        unreachable!("the second attempt always returns")
+    }
+
+    fn properties(&mut self, names: &[&str]) -> Result<Map<String, Value>> {
+        self.with_connection(|connection| {
+            let mut properties = Map::new();
+            for name in names {
+                if let Some(value) = connection.get_property(name)? {
+                    properties.insert(name.to_string(), value);
+                }
+            }
+            Ok(properties)
+        })
+    }
+
+    fn commands(&mut self, commands: &[Value]) -> Result<()> {
+        self.with_connection(|connection| {
+            for command in commands {
+                connection.command(command.clone())?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl PlayerBackend for MpvBackend {
+    fn name(&self) -> &str {
+        "mpv"
+    }
+
+    fn current_track(&mut self) -> Result<PlayerMetadata> {
+        let properties = self.properties(&[
+            "path",
+            "media-title",
+            "metadata",
+            "time-pos",
+            "duration",
+            "pause",
+            "speed",
+            "chapter",
+            "chapter-list",
+            "working-directory",
+        ])?;
+
+        metadata_from_properties(&properties)
+    }
+
+    fn status(&mut self) -> Result<String> {
+        let properties = self.properties(&["path", "pause"])?;
+        Ok(playback_status(&properties).to_string())
+    }
+
+    fn cover_art(&mut self) -> Result<Option<Vec<u8>>> {
+        let properties = self.properties(&["path", "working-directory"])?;
+        match local_file(&properties) {
+            Some(path) => tags::embedded_picture(&path),
+            // Streams have no tags to read
+            None => Ok(None),
+        }
+    }
+
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let commands = match control {
+            PlaybackControl::PlayPause => vec![json!(["cycle", "pause"])],
+            PlaybackControl::Next => vec![json!(["playlist-next"])],
+            PlaybackControl::Previous => vec![json!(["playlist-prev"])],
+            PlaybackControl::Seek(seconds) => vec![json!(["seek", seconds, "relative"])],
+            // Positions within a chapter count from its start
+            PlaybackControl::SeekTo(position) => {
+                let properties = self.properties(&["chapter", "chapter-list"])?;
+                let start = current_chapter(&properties)
+                    .map(|(_, chapter)| chapter.start)
+                    .unwrap_or_default();
+                vec![json!([
+                    "seek",
+                    (start + position).as_secs_f64(),
+                    "absolute"
+                ])]
+            }
+            // mpv clamps to its own volume-max
+            PlaybackControl::Volume(percent) => vec![json!(["add", "volume", percent])],
+            PlaybackControl::ToggleShuffle => {
+                let shuffled = self
+                    .properties(&["shuffle"])?
+                    .get("shuffle")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                if shuffled {
+                    vec![
+                        json!(["set", "shuffle", "no"]),
+                        json!(["playlist-unshuffle"]),
+                    ]
+                } else {
+                    vec![
+                        json!(["set", "shuffle", "yes"]),
+                        json!(["playlist-shuffle"]),
+                    ]
+                }
+            }
+            PlaybackControl::ToggleLoop => {
+                let properties = self.properties(&["loop-file", "loop-playlist"])?;
+                let looping = |name: &str| {
+                    properties
+                        .get(name)
+                        .is_some_and(|value| value != &json!(false) && value != &json!("no"))
+                };
+                let current = if looping("loop-file") {
+                    "Track"
+                } else if looping("loop-playlist") {
+                    "Playlist"
+                } else {
+                    "None"
+                };
+                let (loop_file, loop_playlist) = match next_loop_status(current) {
+                    "Playlist" => ("no", "inf"),
+                    "Track" => ("inf", "no"),
+                    _ => ("no", "no"),
+                };
+                vec![
+                    json!(["set", "loop-file", loop_file]),
+                    json!(["set", "loop-playlist", loop_playlist]),
+                ]
+            }
+        };
+
+        self.commands(&commands)
+    }
+
+    fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        let socket = self.socket.clone();
+
+        // mpv comes and goes, so the thread keeps reconnecting instead of
+        // ever ending the subscription
+        thread::spawn(move || loop {
+            if let Ok(connection) = MpvConnection::connect(&socket, None) {
+                if events.send(PlayerEvent::Changed).is_err() {
+                    return;
+                }
+                let _ = forward_events(connection, &events);
+                // mpv quit, report it
+                if events.send(PlayerEvent::Changed).is_err() {
+                    return;
+                }
+            }
+            thread::sleep(RECONNECT_INTERVAL);
+        });
+
+        Ok(true)
+    }
+}
+
+/// Observe the interesting properties and wake the watcher on every change
+fn forward_events(mut connection: MpvConnection, events: &Sender<PlayerEvent>) -> Result<()> {
+    for (id, name) in OBSERVED_PROPERTIES.iter().enumerate() {
+        connection.send(&json!({ "command": ["observe_property", id + 1, name] }))?;
+    }
+
+    loop {
+        let message = connection.read_message()?;
+        let wake = matches!(
+            message.get("event").and_then(Value::as_str),
+            Some("property-change" | "playback-restart" | "end-file")
+        );
+        if wake {
+            events.send(PlayerEvent::Changed)?;
+        }
+    }
+}
+
+/// Build `PlayerMetadata` from mpv properties.
+///
+/// When the file has chapters (a whole album in one file, a DJ set), the
+/// current chapter is treated as the track.
+fn metadata_from_properties(properties: &Map<String, Value>) -> Result<PlayerMetadata> {
+    let path = properties
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("mpv has no file loaded"))?;
+
+    let empty = Map::new();
+    let tags = properties
+        .get("metadata")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let tag = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| lookup_tag(tags, name))
+            .map(|value| value.to_string())
+    };
+    let number = |names: &[&str]| {
+        tag(names)
+            .and_then(|v| v.split('/').next()?.trim().parse::<u32>().ok())
+            .filter(|n| *n > 0)
+    };
+    let seconds = |name: &str| {
+        properties
+            .get(name)
+            .and_then(Value::as_f64)
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(Duration::from_secs_f64)
+    };
+
+    // media-title falls back to the file name when there are no tags
+    let media_title = properties
+        .get("media-title")
+        .and_then(Value::as_str)
+        .map(|title| title.to_string());
+    let mut title = tag(&["title"]).or(media_title).unwrap_or_default();
+    let mut album = tag(&["album"]);
+    let mut position = seconds("time-pos");
+    let mut length = seconds("duration");
+    let mut track_id = path.to_string();
+
+    if let Some((index, chapter)) = current_chapter(properties) {
+        let next_start = properties
+            .get("chapter-list")
+            .and_then(Value::as_array)
+            .and_then(|chapters| chapters.get(index + 1))
+            .and_then(|next| next.get("time"))
+            .and_then(Value::as_f64)
+            .map(|time| Duration::from_secs_f64(time.max(0.0)));
+
+        if album.is_none() {
+            album = Some(title.clone());
+        }
+        if let Some(chapter_title) = chapter.title {
+            title = chapter_title;
+        }
+        position = position.map(|p| p.saturating_sub(chapter.start));
+        length = next_start
+            .or(length)
+            .map(|end| end.saturating_sub(chapter.start));
+        track_id = format!("{path}#chapter{index}");
+    }
+
+    Ok(PlayerMetadata {
+        artist: tag(&["artist", "album_artist", "albumartist"]).unwrap_or_default(),
+        title,
+        album,
+        position,
+        length,
+        streaming_source: if path.contains("://") {
+            detect_streaming_source(path)
+        } else {
+            Some("Local File".to_string())
+        },
+        art_url: None, // embedded covers come from `cover_art`
+        status: Some(playback_status(properties).to_string()),
+        rate: properties.get("speed").and_then(Value::as_f64),
+        player: Some("mpv".to_string()),
+        album_artist: tag(&["album_artist", "albumartist", "album artist"]),
+        track_number: number(&["track", "tracknumber"]),
+        disc_number: number(&["disc", "discnumber"]),
+        genre: tag(&["genre"]),
+        composer: tag(&["composer"]),
+        user_rating: None,
+        track_id: Some(track_id),
+        url: match local_file(properties) {
+            Some(file) => Some(file.display().to_string()),
+            None => Some(path.to_string()),
+        },
+    })
+}
+
+/// Absolute path of the file mpv is playing, `None` for streams
+fn local_file(properties: &Map<String, Value>) -> Option<PathBuf> {
+    let path = properties.get("path").and_then(Value::as_str)?;
+    let path = match path.strip_prefix("file://") {
+        Some(path) => PathBuf::from(path),
+        None if path.contains("://") => return None,
+        None => PathBuf::from(path),
+    };
+
+    // mpv reports paths as given on its command line
+    match properties.get("working-directory").and_then(Value::as_str) {
+        Some(directory) if path.is_relative() => Some(Path::new(directory).join(path)),
+        _ => Some(path),
+    }
+}
+
+struct Chapter {
+    title: Option<String>,
+    start: Duration,
+}
+
+fn current_chapter(properties: &Map<String, Value>) -> Option<(usize, Chapter)> {
+    // -1 before the first chapter
+    let index = usize::try_from(properties.get("chapter")?.as_i64()?).ok()?;
+    let chapter = properties.get("chapter-list")?.as_array()?.get(index)?;
+
+    Some((
+        index,
+        Chapter {
+            title: chapter
+                .get("title")
+                .and_then(Value::as_str)
+                .filter(|title| !title.is_empty())
+                .map(|title| title.to_string()),
+            start: Duration::from_secs_f64(chapter.get("time")?.as_f64()?.max(0.0)),
+        },
+    ))
+}
+
+/// Tag names in mpv's `metadata` keep the file's spelling (`TITLE`, `Title`)
+fn lookup_tag<'a>(tags: &'a Map<String, Value>, name: &str) -> Option<&'a str> {
+    tags.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+fn playback_status(properties: &Map<String, Value>) -> &'static str {
+    if !properties.contains_key("path") {
+        "Stopped"
+    } else if properties.get("pause").and_then(Value::as_bool) == Some(true) {
+        "Paused"
+    } else {
+        "Playing"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::TagLike;
+    use std::io::Read;
+    use std::os::unix::net::UnixListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    static SOCKET_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    // Recorded from `mpv --input-ipc-server` while playing a file with chapters
+    const RECORDED_EVENTS: &[&str] = &[
+        r#"{"event":"start-file","playlist_entry_id":1}"#,
+        r#"{"event":"property-change","id":1,"name":"path","data":"album.opus"}"#,
+        r#"{"event":"audio-reconfig"}"#,
+        r#"{"event":"file-loaded"}"#,
+        r#"{"event":"property-change","id":5,"name":"chapter","data":0}"#,
+        r#"{"event":"playback-restart"}"#,
+    ];
+
+    /// Stand-in for mpv's IPC server: answers `get_property` from a map,
+    /// records everything else and replays recorded events to observers
+    struct FakeMpv {
+        socket: PathBuf,
+        properties: Arc<Mutex<Map<String, Value>>>,
+        commands: Arc<Mutex<Vec<Value>>>,
+        observed: Arc<Mutex<Vec<Value>>>,
+        observers: Arc<Mutex<Vec<UnixStream>>>,
+    }
+
+    impl FakeMpv {
+        fn start(properties: Value) -> Self {
+            let socket = std::env::temp_dir().join(format!(
+                "trackwatch-test-mpv-{}-{}.sock",
+                std::process::id(),
+                SOCKET_COUNTER.fetch_add(1, Ordering::SeqCst)
+            ));
+            let _ = std::fs::remove_file(&socket);
+            let listener = UnixListener::bind(&socket).unwrap();
+
+            let fake = Self {
+                socket,
+                properties: Arc::new(Mutex::new(properties.as_object().unwrap().clone())),
+                commands: Arc::new(Mutex::new(Vec::new())),
+                observed: Arc::new(Mutex::new(Vec::new())),
+                observers: Arc::new(Mutex::new(Vec::new())),
+            };
+
+            let state = (
+                fake.properties.clone(),
+                fake.commands.clone(),
+                fake.observed.clone(),
+                fake.observers.clone(),
+            );
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let state = state.clone();
+                    thread::spawn(move || serve(stream, state.0, state.1, state.2, state.3));
+                }
+            });
+
+            fake
+        }
+
+        fn set_property(&self, name: &str, value: Value) {
+            self.properties
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value);
+        }
+
+        fn commands(&self) -> Vec<Value> {
+            self.commands.lock().unwrap().clone()
+        }
+
+        fn replay(&self, events: &[&str]) {
+            for observer in self.observers.lock().unwrap().iter_mut() {
+                for event in events {
+                    let _ = observer.write_all(format!("{event}\n").as_bytes());
+                }
+            }
+        }
+    }
+
+    impl Drop for FakeMpv {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.socket);
+        }
+    }
+
+    fn serve(
+        stream: UnixStream,
+        properties: Arc<Mutex<Map<String, Value>>>,
+        commands: Arc<Mutex<Vec<Value>>>,
+        observed: Arc<Mutex<Vec<Value>>>,
+        observers: Arc<Mutex<Vec<UnixStream>>>,
+    ) {
+        let mut writer = stream.try_clone().unwrap();
+        let mut observing = false;
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { return };
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let command = request["command"].clone();
+
+            let (error, data) = match command[0].as_str() {
+                Some("get_property") => {
+                    match properties.lock().unwrap().get(command[1].as_str().unwrap()) {
+                        Some(value) => ("success", value.clone()),
+                        None => ("property unavailable", Value::Null),
+                    }
+                }
+                Some("observe_property") => {
+                    if !observing {
+                        observers.lock().unwrap().push(writer.try_clone().unwrap());
+                        observing = true;
+                    }
+                    observed.lock().unwrap().push(command[2].clone());
+                    ("success", Value::Null)
+                }
+                _ => {
+                    commands.lock().unwrap().push(command);
+                    ("success", Value::Null)
+                }
+            };
+
+            // Real mpv interleaves unrelated events with replies
+            let mut reply = format!("{}\n", RECORDED_EVENTS[2]);
+            reply.push_str(&format!(
+                "{}\n",
+                json!({ "data": data, "error": error, "request_id": request["request_id"] })
+            ));
+            if writer.write_all(reply.as_bytes()).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn album_properties() -> Value {
+        json!({
+            "path": "/music/Album.opus",
+            "media-title": "Album",
+            "metadata": {
+                "ARTIST": "Windows96",
+                "Album": "One Hundred Mornings",
+                "title": "Album",
+                "GENRE": "Vaporwave",
+                "TRACK": "1/1"
+            },
+            "time-pos": 250.5,
+            "duration": 600.0,
+            "pause": false,
+            "speed": 1.25,
+            "chapter": 1,
+            "chapter-list": [
+                { "title": "Intro", "time": 0.0 },
+                { "title": "Glass Pyramid", "time": 200.0 },
+                { "title": "", "time": 420.0 }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_metadata_from_properties() {
+        let properties = json!({
+            "path": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "media-title": "Never Gonna Give You Up",
+            "time-pos": 12.5,
+            "duration": 213.0,
+            "pause": true,
+            "speed": 1.0
+        });
+        let metadata = metadata_from_properties(properties.as_object().unwrap()).unwrap();
+
+        assert_eq!(metadata.title, "Never Gonna Give You Up");
+        assert_eq!(metadata.artist, "");
+        assert_eq!(metadata.position, Some(Duration::from_millis(12_500)));
+        assert_eq!(metadata.length, Some(Duration::from_secs(213)));
+        assert_eq!(metadata.status, Some("Paused".to_string()));
+        assert_eq!(metadata.streaming_source, Some("YouTube".to_string()));
+        assert_eq!(metadata.player, Some("mpv".to_string()));
+
+        assert!(metadata_from_properties(&Map::new()).is_err());
+    }
+
+    #[test]
+    fn test_chapter_is_the_track() {
+        let properties = album_properties();
+        let metadata = metadata_from_properties(properties.as_object().unwrap()).unwrap();
+
+        assert_eq!(metadata.artist, "Windows96");
+        assert_eq!(metadata.title, "Glass Pyramid");
+        assert_eq!(metadata.album, Some("One Hundred Mornings".to_string()));
+        assert_eq!(metadata.position, Some(Duration::from_millis(50_500)));
+        assert_eq!(metadata.length, Some(Duration::from_secs(220)));
+        assert_eq!(metadata.rate, Some(1.25));
+        assert_eq!(metadata.genre, Some("Vaporwave".to_string()));
+        assert_eq!(metadata.track_number, Some(1));
+        assert_eq!(
+            metadata.track_id,
+            Some("/music/Album.opus#chapter1".to_string())
+        );
+
+        // Last chapter ends with the file, an untitled one keeps the file title
+        let mut properties = album_properties();
+        properties["chapter"] = json!(2);
+        properties["time-pos"] = json!(430.0);
+        let metadata = metadata_from_properties(properties.as_object().unwrap()).unwrap();
+        assert_eq!(metadata.title, "Album");
+        assert_eq!(metadata.position, Some(Duration::from_secs(10)));
+        assert_eq!(metadata.length, Some(Duration::from_secs(180)));
+
+        // Files with a start offset can have chapters before zero
+        let mut properties = album_properties();
+        properties["chapter"] = json!(0);
+        properties["chapter-list"] = json!([
+            { "title": "Cold open", "time": -3.0 },
+            { "title": "Intro", "time": -1.0 }
+        ]);
+        let metadata = metadata_from_properties(properties.as_object().unwrap()).unwrap();
+        assert_eq!(metadata.title, "Cold open");
+        assert_eq!(metadata.length, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_current_track_over_ipc() {
+        let mpv = FakeMpv::start(album_properties());
+        let mut backend = MpvBackend::new(&mpv.socket);
+
+        let metadata = backend.current_track().unwrap();
+        assert_eq!(metadata.title, "Glass Pyramid");
+        assert_eq!(backend.status().unwrap(), "Playing");
+
+        mpv.set_property("pause", json!(true));
+        assert_eq!(backend.status().unwrap(), "Paused");
+    }
+
+    #[test]
+    fn test_mpv_not_running() {
+        let mut backend = MpvBackend::new("/nonexistent/mpvsocket");
+        assert!(backend.current_track().is_err());
+    }
+
+    #[test]
+    fn test_idle_mpv_has_no_track() {
+        let mpv = FakeMpv::start(json!({ "idle-active": true }));
+        let mut backend = MpvBackend::new(&mpv.socket);
+
+        assert!(backend.current_track().is_err());
+        assert_eq!(backend.status().unwrap(), "Stopped");
+    }
+
+    #[test]
+    fn test_controls() {
+        let mpv = FakeMpv::start(json!({
+            "path": "song.flac",
+            "shuffle": false,
+            "loop-file": false,
+            "loop-playlist": "inf"
+        }));
+        let mut backend = MpvBackend::new(&mpv.socket);
+
+        for control in [
+            PlaybackControl::PlayPause,
+            PlaybackControl::Next,
+            PlaybackControl::Previous,
+            PlaybackControl::Seek(-30),
+            PlaybackControl::SeekTo(Duration::from_millis(1_500)),
+            PlaybackControl::Volume(5),
+            PlaybackControl::ToggleShuffle,
+            PlaybackControl::ToggleLoop,
+        ] {
+            backend.control(control).unwrap();
+        }
+
+        assert_eq!(
+            mpv.commands(),
+            vec![
+                json!(["cycle", "pause"]),
+                json!(["playlist-next"]),
+                json!(["playlist-prev"]),
+                json!(["seek", -30, "relative"]),
+                json!(["seek", 1.5, "absolute"]),
+                json!(["add", "volume", 5]),
+                json!(["set", "shuffle", "yes"]),
+                json!(["playlist-shuffle"]),
+                json!(["set", "loop-file", "inf"]),
+                json!(["set", "loop-playlist", "no"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cover_art_from_relative_path() {
+        let directory = std::env::temp_dir();
+        let file_name = format!("trackwatch-test-mpv-{}.mp3", std::process::id());
+        std::fs::write(directory.join(&file_name), b"").unwrap();
+        let mut tag = id3::Tag::new();
+        tag.add_frame(id3::frame::Picture {
+            mime_type: "image/jpeg".to_string(),
+            picture_type: id3::frame::PictureType::CoverFront,
+            description: String::new(),
+            data: b"jpeg bytes".to_vec(),
+        });
+        tag.write_to_path(directory.join(&file_name), id3::Version::Id3v24)
+            .unwrap();
+
+        let mpv = FakeMpv::start(json!({
+            "path": file_name,
+            "working-directory": directory.to_str().unwrap()
+        }));
+        let mut backend = MpvBackend::new(&mpv.socket);
+        assert_eq!(backend.cover_art().unwrap(), Some(b"jpeg bytes".to_vec()));
+
+        // Streams have no embedded art
+        mpv.set_property("path", json!("https://example.com/stream.mp3"));
+        assert_eq!(backend.cover_art().unwrap(), None);
+
+        std::fs::remove_file(directory.join(file_name)).unwrap();
+    }
+
+    #[test]
+    fn test_subscribe_replays_events() {
+        let mpv = FakeMpv::start(album_properties());
+        let mut backend = MpvBackend::new(&mpv.socket);
+
+        let (tx, rx) = mpsc::channel();
+        assert!(backend.subscribe(tx).unwrap());
+
+        // Connecting wakes the watcher once
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            PlayerEvent::Changed
+        );
+
+        // Wait until every property is observed, then replay
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while mpv.observed.lock().unwrap().len() < OBSERVED_PROPERTIES.len() {
+            assert!(std::time::Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(10));
+        }
+        while rx.try_recv().is_ok() {}
+        mpv.replay(RECORDED_EVENTS);
+
+        // path, chapter and playback-restart wake it, the others don't
+        let mut woken = 0;
+        while rx.recv_timeout(Duration::from_millis(500)).is_ok() {
+            woken += 1;
+        }
+        assert_eq!(woken, 3);
+    }
+
+    #[test]
+    fn test_reading_skips_events() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+        let mut connection = MpvConnection {
+            reader: BufReader::new(client),
+            next_request_id: 7,
+        };
+        server
+            .write_all(
+                b"{\"event\":\"seek\"}\n{\"request_id\":3,\"error\":\"success\",\"data\":1}\n\
+                  {\"request_id\":7,\"error\":\"success\",\"data\":\"mpv 0.38.0\"}\n",
+            )
+            .unwrap();
+
+        assert_eq!(
+            connection.get_property("mpv-version").unwrap(),
+            Some(json!("mpv 0.38.0"))
+        );
+
+        let mut sent = String::new();
+        drop(connection);
+        server.read_to_string(&mut sent).unwrap();
+        assert_eq!(
+            sent,
+            "{\"command\":[\"get_property\",\"mpv-version\"],\"request_id\":7}\n"
+        );
+    }
+}
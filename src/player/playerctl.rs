@@ -0,0 +1,461 @@
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use super::{
+    detect_streaming_source, next_loop_status, parse_track_id, PlaybackControl, PlayerBackend,
+    PlayerEvent, PlayerInfo, PlayerMetadata,
+};
+
+// Everything that should wake the background thread. Position is left out on
+// purpose, otherwise playerctl prints a line every second while playing.
+const FOLLOW_FORMAT: &str = "{{playerInstance}}|{{status}}|{{xesam:artist}}|{{xesam:title}}|{{xesam:album}}|{{mpris:length}}";
+
+// The less common tags in one call rather than one process per tag
+const EXTENDED_FORMAT: &str = "{{xesam:albumArtist}}\t{{xesam:trackNumber}}\t{{xesam:discNumber}}\t{{xesam:genre}}\t{{xesam:composer}}\t{{xesam:userRating}}\t{{mpris:trackid}}";
+
+const LIST_FORMAT: &str = "{{playerInstance}}\t{{status}}";
+
+/// Reads players by shelling out to `playerctl`
+#[derive(Debug, Default)]
+pub struct PlayerctlBackend {
+    player: Option<String>, // passed as --player, playerctl picks when None
+}
+
+impl PlayerctlBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PlayerBackend for PlayerctlBackend {
+    fn name(&self) -> &str {
+        "playerctl"
+    }
+
+    fn current_track(&mut self) -> Result<PlayerMetadata> {
+        get_current_track(self.player.as_deref())
+    }
+
+    fn status(&mut self) -> Result<String> {
+        player_status(self.player.as_deref())
+    }
+
+    fn list_players(&mut self) -> Result<Vec<PlayerInfo>> {
+        let output = Command::new("playerctl")
+            .args(["--all-players", "status", "--format", LIST_FORMAT])
+            .stderr(Stdio::null())
+            .output()?;
+
+        // playerctl exits with an error when no players are running
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        Ok(parse_player_list(&String::from_utf8(output.stdout)?))
+    }
+
+    fn set_active_player(&mut self, player: Option<&str>) {
+        self.player = player.map(|name| name.to_string());
+    }
+
+    fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        let player = self.player.as_deref();
+
+        let args = match control {
+            // playerctl can only set the loop status, so read it first
+            PlaybackControl::ToggleLoop => {
+                let current = run_playerctl(player, &control_args(control))?;
+                vec!["loop".to_string(), next_loop_status(&current).to_string()]
+            }
+            other => control_args(other),
+        };
+
+        run_playerctl(player, &args).map(|_| ())
+    }
+
+    fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        // Follow every player so switching between them is noticed as well
+        let mut child = Command::new("playerctl")
+            .args([
+                "--all-players",
+                "metadata",
+                "--follow",
+                "--format",
+                FOLLOW_FORMAT,
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("playerctl --follow has no output"))?;
+
+        thread::spawn(move || {
+            if forward_lines(BufReader::new(stdout), &events) {
+                let _ = events.send(PlayerEvent::SubscriptionEnded);
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+
+        Ok(true)
+    }
+}
+
+/// Send a change notification for every line `playerctl --follow` prints.
+///
+/// Returns `false` if the receiver went away, `true` when the output ended.
+fn forward_lines(reader: impl BufRead, events: &Sender<PlayerEvent>) -> bool {
+    for line in reader.lines() {
+        if line.is_err() {
+            break;
+        }
+        if events.send(PlayerEvent::Changed).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Parse `playerctl --all-players status --format LIST_FORMAT` output
+fn parse_player_list(output: &str) -> Vec<PlayerInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, status) = line.split_once('\t').unwrap_or((line, ""));
+            let name = name.trim();
+            let status = status.trim();
+
+            (!name.is_empty()).then(|| PlayerInfo {
+                name: name.to_string(),
+                status: (!status.is_empty()).then(|| status.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// playerctl arguments for `control`. For `ToggleLoop` these only read the
+/// current loop status.
+fn control_args(control: PlaybackControl) -> Vec<String> {
+    let relative =
+        |amount: String, negative: bool| format!("{amount}{}", if negative { "-" } else { "+" });
+
+    match control {
+        PlaybackControl::PlayPause => vec!["play-pause".to_string()],
+        PlaybackControl::Next => vec!["next".to_string()],
+        PlaybackControl::Previous => vec!["previous".to_string()],
+        PlaybackControl::Seek(seconds) => vec![
+            "position".to_string(),
+            relative(seconds.unsigned_abs().to_string(), seconds < 0),
+        ],
+        PlaybackControl::SeekTo(position) => vec![
+            "position".to_string(),
+            format!("{:.2}", position.as_secs_f64()),
+        ],
+        PlaybackControl::Volume(percent) => vec![
+            "volume".to_string(),
+            relative(
+                format!("{:.2}", f64::from(percent.unsigned_abs()) / 100.0),
+                percent < 0,
+            ),
+        ],
+        PlaybackControl::ToggleShuffle => vec!["shuffle".to_string(), "Toggle".to_string()],
+        PlaybackControl::ToggleLoop => vec!["loop".to_string()],
+    }
+}
+
+fn run_playerctl(player: Option<&str>, args: &[String]) -> Result<String> {
+    let output = playerctl(player).args(args).output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("playerctl {} failed", args.join(" ")));
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// A `playerctl` invocation aimed at `player`, if one was selected
+fn playerctl(player: Option<&str>) -> Command {
+    let mut command = Command::new("playerctl");
+    if let Some(player) = player {
+        command.arg(format!("--player={player}"));
+    }
+    command
+}
+
+fn get_current_track(player: Option<&str>) -> Result<PlayerMetadata> {
+    // Get metadata from playerctl
+    let artist = get_playerctl_property(player, "artist")?;
+    let title = get_playerctl_property(player, "title")?;
+    let album = get_playerctl_property(player, "album").ok();
+
+    // Get position and length
+    let position = get_playerctl_position(player).ok();
+    let length = get_playerctl_length(player).ok();
+
+    // Get streaming source from URL
+    let url = get_playerctl_property(player, "url")
+        .ok()
+        .filter(|url| !url.is_empty());
+    let streaming_source = url.as_deref().and_then(detect_streaming_source);
+
+    // Get album art URL
+    let art_url = get_playerctl_mpris_property(player, "artUrl").ok();
+
+    // Get player status
+    let status = player_status(player).ok();
+
+    let mut metadata = PlayerMetadata {
+        artist,
+        title,
+        album,
+        position,
+        length,
+        streaming_source,
+        art_url,
+        status,
+        player: player.map(|name| name.to_string()),
+        url,
+        ..Default::default()
+    };
+
+    // Album artist, track number, genre and friends
+    let extended = ["metadata", "--format", EXTENDED_FORMAT].map(String::from);
+    if let Ok(output) = run_playerctl(player, &extended) {
+        apply_extended_metadata(&output, &mut metadata);
+    }
+
+    Ok(metadata)
+}
+
+/// Fill in the tags printed with `EXTENDED_FORMAT`
+fn apply_extended_metadata(output: &str, metadata: &mut PlayerMetadata) {
+    let fields: Vec<&str> = output.split('\t').map(|field| field.trim()).collect();
+    let text = |index: usize| {
+        fields
+            .get(index)
+            .filter(|field| !field.is_empty())
+            .map(|field| field.to_string())
+    };
+    let number = |index: usize| {
+        fields
+            .get(index)
+            .and_then(|field| field.parse::<u32>().ok())
+            .filter(|n| *n > 0)
+    };
+
+    metadata.album_artist = text(0);
+    metadata.track_number = number(1);
+    metadata.disc_number = number(2);
+    metadata.genre = text(3);
+    metadata.composer = text(4);
+    metadata.user_rating = fields.get(5).and_then(|field| field.parse().ok());
+    metadata.track_id = fields.get(6).and_then(|field| parse_track_id(field));
+}
+
+fn get_playerctl_property(player: Option<&str>, property: &str) -> Result<String> {
+    let output = playerctl(player)
+        .args(["metadata", &format!("xesam:{property}")])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("playerctl failed to get {}", property));
+    }
+
+    let value = String::from_utf8(output.stdout)?.trim().to_string();
+
+    if value.is_empty() {
+        return Err(anyhow!("No {} found", property));
+    }
+
+    Ok(value)
+}
+
+fn get_playerctl_position(player: Option<&str>) -> Result<Duration> {
+    let output = playerctl(player).arg("position").output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("playerctl failed to get position"));
+    }
+
+    let seconds: f64 = String::from_utf8(output.stdout)?.trim().parse()?;
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+fn get_playerctl_length(player: Option<&str>) -> Result<Duration> {
+    let output = playerctl(player)
+        .args(["metadata", "mpris:length"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("playerctl failed to get length"));
+    }
+
+    // mpris:length returns microseconds
+    let microseconds: u64 = String::from_utf8(output.stdout)?.trim().parse()?;
+
+    Ok(Duration::from_micros(microseconds))
+}
+
+fn get_playerctl_mpris_property(player: Option<&str>, property: &str) -> Result<String> {
+    let output = playerctl(player)
+        .args(["metadata", &format!("mpris:{property}")])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("playerctl failed to get mpris property"));
+    }
+
+    let value = String::from_utf8(output.stdout)?.trim().to_string();
+
+    if value.is_empty() {
+        return Err(anyhow!("No {} found", property));
+    }
+
+    Ok(value)
+}
+
+pub fn is_player_available() -> bool {
+    Command::new("playerctl")
+        .arg("status")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+pub fn is_playerctl_installed() -> bool {
+    Command::new("playerctl")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+pub fn get_player_status() -> Result<String> {
+    player_status(None)
+}
+
+fn player_status(player: Option<&str>) -> Result<String> {
+    let output = playerctl(player).arg("status").output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("playerctl failed to get status"));
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_forward_lines_sends_one_event_per_line() {
+        let (tx, rx) = mpsc::channel();
+        let output = Cursor::new("Playing|Queen|Bohemian Rhapsody|A Night at the Opera|354000000\nPaused|Queen|Bohemian Rhapsody|A Night at the Opera|354000000\n\n");
+
+        assert!(forward_lines(output, &tx));
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_parse_player_list() {
+        let output = "spotify\tPlaying\nfirefox.instance_1_42\tPaused\nmpv\n\n";
+
+        assert_eq!(
+            parse_player_list(output),
+            vec![
+                PlayerInfo {
+                    name: "spotify".to_string(),
+                    status: Some("Playing".to_string()),
+                },
+                PlayerInfo {
+                    name: "firefox.instance_1_42".to_string(),
+                    status: Some("Paused".to_string()),
+                },
+                PlayerInfo {
+                    name: "mpv".to_string(),
+                    status: None,
+                },
+            ]
+        );
+        assert!(parse_player_list("").is_empty());
+    }
+
+    #[test]
+    fn test_apply_extended_metadata() {
+        let mut metadata = PlayerMetadata::default();
+        apply_extended_metadata(
+            "Various Artists\t7\t2\tJazz\tMiles Davis\t0.6\t/org/mpd/Tracks/42",
+            &mut metadata,
+        );
+
+        assert_eq!(metadata.album_artist, Some("Various Artists".to_string()));
+        assert_eq!(metadata.track_number, Some(7));
+        assert_eq!(metadata.disc_number, Some(2));
+        assert_eq!(metadata.genre, Some("Jazz".to_string()));
+        assert_eq!(metadata.composer, Some("Miles Davis".to_string()));
+        assert_eq!(metadata.user_rating, Some(0.6));
+        assert_eq!(metadata.track_id, Some("/org/mpd/Tracks/42".to_string()));
+
+        // Players that leave tags out
+        let mut metadata = PlayerMetadata::default();
+        apply_extended_metadata(
+            "\t\t\t\t\t\t/org/mpris/MediaPlayer2/TrackList/NoTrack",
+            &mut metadata,
+        );
+        assert_eq!(metadata.album_artist, None);
+        assert_eq!(metadata.track_number, None);
+        assert_eq!(metadata.user_rating, None);
+        assert_eq!(metadata.track_id, None);
+    }
+
+    #[test]
+    fn test_control_args() {
+        assert_eq!(control_args(PlaybackControl::PlayPause), vec!["play-pause"]);
+        assert_eq!(control_args(PlaybackControl::Next), vec!["next"]);
+        assert_eq!(control_args(PlaybackControl::Previous), vec!["previous"]);
+        assert_eq!(
+            control_args(PlaybackControl::Seek(5)),
+            vec!["position", "5+"]
+        );
+        assert_eq!(
+            control_args(PlaybackControl::Seek(-30)),
+            vec!["position", "30-"]
+        );
+        assert_eq!(
+            control_args(PlaybackControl::SeekTo(Duration::from_millis(83_450))),
+            vec!["position", "83.45"]
+        );
+        assert_eq!(
+            control_args(PlaybackControl::Volume(5)),
+            vec!["volume", "0.05+"]
+        );
+        assert_eq!(
+            control_args(PlaybackControl::Volume(-5)),
+            vec!["volume", "0.05-"]
+        );
+        assert_eq!(
+            control_args(PlaybackControl::ToggleShuffle),
+            vec!["shuffle", "Toggle"]
+        );
+        assert_eq!(control_args(PlaybackControl::ToggleLoop), vec!["loop"]);
+    }
+
+    #[test]
+    fn test_forward_lines_stops_without_receiver() {
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+
+        assert!(!forward_lines(Cursor::new("Playing|||\n"), &tx));
+    }
+}
@@ -0,0 +1,238 @@
+use super::PlayerInfo;
+
+/// Decides which of several running players trackwatch follows.
+///
+/// Order of precedence:
+/// 1. a pinned player (`--player`), and nothing else
+/// 2. a player picked by hand with `cycle`, while it keeps running
+/// 3. a player that is `Playing`, preferring the priority list and then the
+///    player already followed, so two playing players don't take turns
+/// 4. the player already followed, then the priority list, then by name
+#[derive(Debug, Clone, Default)]
+pub struct PlayerSelector {
+    pinned: Option<String>,
+    priority: Vec<String>,
+    manual: Option<String>,
+    active: Option<String>,
+}
+
+impl PlayerSelector {
+    pub fn new(pinned: Option<String>, priority: Vec<String>) -> Self {
+        Self {
+            pinned,
+            priority,
+            ..Default::default()
+        }
+    }
+
+    /// The player chosen by the last call to `choose` or `cycle`
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Pick the player to follow among `players`
+    pub fn choose(&mut self, players: &[PlayerInfo]) -> Option<String> {
+        let chosen = self.pick(players).map(|player| player.name.clone());
+        self.active = chosen.clone();
+        chosen
+    }
+
+    /// Switch to the next player by name and stick with it
+    pub fn cycle(&mut self, players: &[PlayerInfo]) -> Option<String> {
+        if self.pinned.is_some() || players.is_empty() {
+            return self.choose(players);
+        }
+
+        let mut names: Vec<&str> = players.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+
+        let next = match self
+            .active
+            .as_deref()
+            .and_then(|active| names.iter().position(|name| *name == active))
+        {
+            Some(index) => names[(index + 1) % names.len()],
+            None => names[0],
+        };
+
+        self.manual = Some(next.to_string());
+        self.choose(players)
+    }
+
+    fn pick<'a>(&mut self, players: &'a [PlayerInfo]) -> Option<&'a PlayerInfo> {
+        if let Some(ref pinned) = self.pinned {
+            return players.iter().find(|p| matches_name(&p.name, pinned));
+        }
+
+        if let Some(ref manual) = self.manual {
+            match players.iter().find(|p| p.name == *manual) {
+                Some(player) => return Some(player),
+                // The chosen player went away, back to automatic selection
+                None => self.manual = None,
+            }
+        }
+
+        let playing: Vec<&PlayerInfo> = players.iter().filter(|p| p.is_playing()).collect();
+        let nothing_playing = playing.is_empty();
+        let candidates = if nothing_playing {
+            players.iter().collect()
+        } else {
+            playing
+        };
+
+        candidates.into_iter().min_by_key(|player| {
+            (
+                // With everything paused, stay on the player we were following
+                !(nothing_playing && self.is_active(player)),
+                self.priority_of(player),
+                !self.is_active(player),
+                player.name.clone(),
+            )
+        })
+    }
+
+    fn is_active(&self, player: &PlayerInfo) -> bool {
+        self.active.as_deref() == Some(player.name.as_str())
+    }
+
+    fn priority_of(&self, player: &PlayerInfo) -> usize {
+        self.priority
+            .iter()
+            .position(|wanted| matches_name(&player.name, wanted))
+            .unwrap_or(self.priority.len())
+    }
+}
+
+/// `firefox` matches `firefox.instance_1_42`, like `playerctl --player` does
+fn matches_name(name: &str, wanted: &str) -> bool {
+    name == wanted
+        || name
+            .strip_prefix(wanted)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, status: &str) -> PlayerInfo {
+        PlayerInfo {
+            name: name.to_string(),
+            status: Some(status.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_prefers_the_playing_player() {
+        let mut selector = PlayerSelector::default();
+        let players = [
+            player("firefox.instance_1_42", "Paused"),
+            player("mpv", "Stopped"),
+            player("spotify", "Playing"),
+        ];
+
+        assert_eq!(selector.choose(&players), Some("spotify".to_string()));
+    }
+
+    #[test]
+    fn test_priority_breaks_ties_between_playing_players() {
+        let mut selector = PlayerSelector::new(None, vec!["mpv".to_string()]);
+        let players = [player("spotify", "Playing"), player("mpv", "Playing")];
+
+        assert_eq!(selector.choose(&players), Some("mpv".to_string()));
+    }
+
+    #[test]
+    fn test_sticks_with_the_active_player() {
+        let mut selector = PlayerSelector::default();
+        let players = [player("spotify", "Playing")];
+        selector.choose(&players);
+
+        // Another player starts, spotify keeps playing
+        let players = [player("firefox", "Playing"), player("spotify", "Playing")];
+        assert_eq!(selector.choose(&players), Some("spotify".to_string()));
+
+        // Spotify pauses: follow the one that is playing
+        let players = [player("firefox", "Playing"), player("spotify", "Paused")];
+        assert_eq!(selector.choose(&players), Some("firefox".to_string()));
+
+        // Everything paused: stay on firefox
+        let players = [player("firefox", "Paused"), player("spotify", "Paused")];
+        assert_eq!(selector.choose(&players), Some("firefox".to_string()));
+    }
+
+    #[test]
+    fn test_nothing_playing_uses_priority_then_name() {
+        let mut selector = PlayerSelector::new(None, vec!["spotify".to_string()]);
+        let players = [player("mpv", "Paused"), player("spotify", "Paused")];
+        assert_eq!(selector.choose(&players), Some("spotify".to_string()));
+
+        let mut selector = PlayerSelector::default();
+        assert_eq!(selector.choose(&players), Some("mpv".to_string()));
+        assert_eq!(selector.choose(&[]), None);
+    }
+
+    #[test]
+    fn test_pinned_player_is_the_only_choice() {
+        let mut selector = PlayerSelector::new(Some("firefox".to_string()), Vec::new());
+        let players = [
+            player("firefox.instance_1_42", "Paused"),
+            player("spotify", "Playing"),
+        ];
+        assert_eq!(
+            selector.choose(&players),
+            Some("firefox.instance_1_42".to_string())
+        );
+
+        // Cycling does not leave the pinned player
+        assert_eq!(
+            selector.cycle(&players),
+            Some("firefox.instance_1_42".to_string())
+        );
+
+        // Not running means no player at all
+        assert_eq!(selector.choose(&[player("spotify", "Playing")]), None);
+    }
+
+    #[test]
+    fn test_cycle_walks_players_by_name_and_sticks() {
+        let mut selector = PlayerSelector::default();
+        let players = [
+            player("spotify", "Playing"),
+            player("firefox", "Paused"),
+            player("mpv", "Paused"),
+        ];
+        assert_eq!(selector.choose(&players), Some("spotify".to_string()));
+
+        assert_eq!(selector.cycle(&players), Some("firefox".to_string()));
+        // The manual choice wins over the playing player
+        assert_eq!(selector.choose(&players), Some("firefox".to_string()));
+
+        assert_eq!(selector.cycle(&players), Some("mpv".to_string()));
+        assert_eq!(selector.cycle(&players), Some("spotify".to_string()));
+        assert_eq!(selector.active(), Some("spotify"));
+    }
+
+    #[test]
+    fn test_manual_choice_is_dropped_when_player_exits() {
+        let mut selector = PlayerSelector::default();
+        let players = [player("firefox", "Paused"), player("spotify", "Playing")];
+        selector.choose(&players);
+        assert_eq!(selector.cycle(&players), Some("firefox".to_string()));
+
+        let players = [player("mpv", "Paused"), player("spotify", "Playing")];
+        assert_eq!(selector.choose(&players), Some("spotify".to_string()));
+
+        // Back to automatic selection afterwards
+        let players = [player("firefox", "Playing"), player("spotify", "Paused")];
+        assert_eq!(selector.choose(&players), Some("firefox".to_string()));
+    }
+
+    #[test]
+    fn test_matches_name() {
+        assert!(matches_name("firefox", "firefox"));
+        assert!(matches_name("firefox.instance_1_42", "firefox"));
+        assert!(!matches_name("firefoxy", "firefox"));
+        assert!(!matches_name("spotify", "firefox"));
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::Result;
+use std::sync::mpsc::Sender;
+
+use super::selection::PlayerSelector;
+use super::{PlaybackControl, PlayerBackend, PlayerEvent, PlayerMetadata};
+
+/// Outcome of polling the backend once
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)] // short-lived, one per poll
+pub enum PlayerUpdate {
+    /// A player is active. `track_changed` is set on the first poll of a new track.
+    Playing {
+        metadata: PlayerMetadata,
+        track_changed: bool,
+    },
+    /// The player went away since the last poll
+    Stopped,
+    /// Still no player running
+    Idle,
+}
+
+/// Polls a `PlayerBackend` and decides when the track has changed
+pub struct TrackWatcher {
+    backend: Box<dyn PlayerBackend>,
+    selector: PlayerSelector,
+    last_track: Option<PlayerMetadata>,
+}
+
+impl TrackWatcher {
+    pub fn new(backend: Box<dyn PlayerBackend>) -> Self {
+        Self::with_selector(backend, PlayerSelector::default())
+    }
+
+    pub fn with_selector(backend: Box<dyn PlayerBackend>, selector: PlayerSelector) -> Self {
+        Self {
+            backend,
+            selector,
+            last_track: None,
+        }
+    }
+
+    pub fn backend_name(&self) -> &str {
+        self.backend.name()
+    }
+
+    pub fn subscribe(&mut self, events: Sender<PlayerEvent>) -> Result<bool> {
+        self.backend.subscribe(events)
+    }
+
+    /// Cover image bytes from the backend, if it provides them
+    pub fn cover_art(&mut self) -> Result<Option<Vec<u8>>> {
+        self.backend.cover_art()
+    }
+
+    /// Forward a playback action to the player being followed
+    pub fn control(&mut self, control: PlaybackControl) -> Result<()> {
+        self.backend.control(control)
+    }
+
+    /// Move on to the next running player. Takes effect on the next `poll`.
+    pub fn cycle_player(&mut self) -> Option<String> {
+        let players = self.backend.list_players().unwrap_or_default();
+        self.selector.cycle(&players)
+    }
+
+    pub fn poll(&mut self) -> PlayerUpdate {
+        // Backends that see several players get told which one to read
+        let players = self.backend.list_players().unwrap_or_default();
+        if !players.is_empty() {
+            let chosen = self.selector.choose(&players);
+            if chosen.is_none() {
+                return self.no_track();
+            }
+            self.backend.set_active_player(chosen.as_deref());
+        }
+
+        match self.backend.current_track() {
+            Ok(metadata) => {
+                let track_changed = match &self.last_track {
+                    None => true,
+                    Some(last) => !last.is_same_track(&metadata),
+                };
+                self.last_track = Some(metadata.clone());
+
+                PlayerUpdate::Playing {
+                    metadata,
+                    track_changed,
+                }
+            }
+            // Errors are normal when no player is running
+            Err(_) => self.no_track(),
+        }
+    }
+
+    fn no_track(&mut self) -> PlayerUpdate {
+        if self.last_track.take().is_some() {
+            PlayerUpdate::Stopped
+        } else {
+            PlayerUpdate::Idle
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::mock::MockBackend;
+    use std::time::Duration;
+
+    fn track(artist: &str, title: &str, album: Option<&str>) -> PlayerMetadata {
+        PlayerMetadata {
+            artist: artist.to_string(),
+            title: title.to_string(),
+            album: album.map(|a| a.to_string()),
+            position: Some(Duration::from_secs(0)),
+            status: Some("Playing".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn poll_title(watcher: &mut TrackWatcher) -> Option<String> {
+        match watcher.poll() {
+            PlayerUpdate::Playing { metadata, .. } => Some(metadata.title),
+            _ => None,
+        }
+    }
+
+    fn poll_changed(watcher: &mut TrackWatcher) -> Option<bool> {
+        match watcher.poll() {
+            PlayerUpdate::Playing { track_changed, .. } => Some(track_changed),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_first_poll_is_a_track_change() {
+        let backend = MockBackend::with_track(track("Queen", "Bohemian Rhapsody", None));
+        let mut watcher = TrackWatcher::new(Box::new(backend));
+
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+        assert_eq!(poll_changed(&mut watcher), Some(false));
+    }
+
+    #[test]
+    fn test_position_and_status_do_not_change_track() {
+        let backend = MockBackend::with_track(track("Queen", "Bohemian Rhapsody", None));
+        let mut watcher = TrackWatcher::new(Box::new(backend.clone()));
+        poll_changed(&mut watcher);
+
+        let mut paused = track("Queen", "Bohemian Rhapsody", None);
+        paused.position = Some(Duration::from_secs(42));
+        paused.status = Some("Paused".to_string());
+        backend.set_track(Some(paused));
+
+        assert_eq!(poll_changed(&mut watcher), Some(false));
+    }
+
+    #[test]
+    fn test_title_artist_or_album_change_is_detected() {
+        let backend = MockBackend::with_track(track("Queen", "Bohemian Rhapsody", Some("A")));
+        let mut watcher = TrackWatcher::new(Box::new(backend.clone()));
+        poll_changed(&mut watcher);
+
+        backend.set_track(Some(track("Queen", "We Will Rock You", Some("A"))));
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+
+        backend.set_track(Some(track("David Bowie", "We Will Rock You", Some("A"))));
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+
+        backend.set_track(Some(track("David Bowie", "We Will Rock You", Some("B"))));
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+    }
+
+    #[test]
+    fn test_player_stopping_and_restarting() {
+        let backend = MockBackend::new();
+        let mut watcher = TrackWatcher::new(Box::new(backend.clone()));
+
+        // No player yet
+        assert!(matches!(watcher.poll(), PlayerUpdate::Idle));
+
+        backend.set_track(Some(track("Queen", "Bohemian Rhapsody", None)));
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+
+        // Player goes away: reported once, then idle
+        backend.set_track(None);
+        assert!(matches!(watcher.poll(), PlayerUpdate::Stopped));
+        assert!(matches!(watcher.poll(), PlayerUpdate::Idle));
+
+        // Same track coming back counts as a new track
+        backend.set_track(Some(track("Queen", "Bohemian Rhapsody", None)));
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+    }
+
+    #[test]
+    fn test_follows_the_playing_player() {
+        let backend = MockBackend::new();
+        let mut watcher = TrackWatcher::new(Box::new(backend.clone()));
+
+        let mut paused = track("Local", "Paused Song", None);
+        paused.status = Some("Paused".to_string());
+        backend.set_player_track("mpv", Some(paused));
+        backend.set_player_track("spotify", Some(track("Queen", "Bohemian Rhapsody", None)));
+
+        assert_eq!(
+            poll_title(&mut watcher),
+            Some("Bohemian Rhapsody".to_string())
+        );
+        assert_eq!(backend.active_player(), Some("spotify".to_string()));
+
+        // Spotify goes away, mpv is the only one left
+        backend.set_player_track("spotify", None);
+        assert_eq!(poll_title(&mut watcher), Some("Paused Song".to_string()));
+    }
+
+    #[test]
+    fn test_cycle_and_pin_players() {
+        let backend = MockBackend::new();
+        backend.set_player_track("mpv", Some(track("Local", "Local File", None)));
+        backend.set_player_track("spotify", Some(track("Queen", "Bohemian Rhapsody", None)));
+
+        let mut watcher = TrackWatcher::new(Box::new(backend.clone()));
+        assert_eq!(poll_title(&mut watcher), Some("Local File".to_string()));
+
+        assert_eq!(watcher.cycle_player(), Some("spotify".to_string()));
+        assert_eq!(poll_changed(&mut watcher), Some(true));
+        assert_eq!(backend.active_player(), Some("spotify".to_string()));
+
+        // A pinned player that isn't running means no track
+        let selector = PlayerSelector::new(Some("vlc".to_string()), Vec::new());
+        let mut pinned = TrackWatcher::with_selector(Box::new(backend), selector);
+        assert!(matches!(pinned.poll(), PlayerUpdate::Idle));
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::Config;
+use crate::player::cmus::{self, CmusBackend, CmusTransport};
+use crate::player::mpd::{MpdAddress, MpdBackend};
+use crate::player::mpris::MprisBackend;
+use crate::player::mpv::{self, MpvBackend};
+use crate::player::playerctl::{self, PlayerctlBackend};
+use crate::player::PlayerBackend;
+
+/// Pick the player backend named in the config.
+///
+/// Without an explicit choice, MPRIS over D-Bus is preferred and playerctl
+/// is used when no session bus is reachable.
+pub fn create_player_backend(config: &Config) -> Result<Box<dyn PlayerBackend>> {
+    match config.player_backend.as_deref().unwrap_or("auto") {
+        "auto" => {
+            if let Ok(mpris) = MprisBackend::new() {
+                Ok(Box::new(mpris))
+            } else if playerctl::is_playerctl_installed() {
+                Ok(Box::new(PlayerctlBackend::new()))
+            } else {
+                Err(anyhow!(
+                    "Could not connect to the D-Bus session bus and playerctl is not installed. \
+                     Please install playerctl to use this application."
+                ))
+            }
+        }
+        "mpris" => Ok(Box::new(MprisBackend::new()?)),
+        "playerctl" => {
+            if playerctl::is_playerctl_installed() {
+                Ok(Box::new(PlayerctlBackend::new()))
+            } else {
+                Err(anyhow!("playerctl is not installed"))
+            }
+        }
+        "mpd" => {
+            let (address, password) =
+                MpdAddress::from_host(config.mpd_host.as_deref(), config.mpd_port);
+            MpdBackend::connect(address.clone(), password)
+                .map(|mpd| Box::new(mpd) as Box<dyn PlayerBackend>)
+                .map_err(|e| anyhow!("Could not connect to MPD at {:?}: {}", address, e))
+        }
+        "mpv" => Ok(Box::new(MpvBackend::new(
+            config.mpv_socket.as_deref().unwrap_or(mpv::DEFAULT_SOCKET),
+        ))),
+        "cmus" => Ok(Box::new(CmusBackend::new(cmus_transport(config)?))),
+        other => Err(anyhow!("Unknown player backend: {}", other)),
+    }
+}
+
+/// Talk to the cmus socket directly when we know where it is, otherwise
+/// let `cmus-remote` find it
+fn cmus_transport(config: &Config) -> Result<CmusTransport> {
+    if let Some(ref socket) = config.cmus_socket {
+        return Ok(CmusTransport::Socket(socket.into()));
+    }
+
+    let default_socket = cmus::default_socket();
+    match default_socket {
+        Some(socket) if socket.exists() => Ok(CmusTransport::Socket(socket)),
+        _ if cmus::is_cmus_remote_installed() => Ok(CmusTransport::Remote),
+        // cmus may simply not be running yet
+        Some(socket) => Ok(CmusTransport::Socket(socket)),
+        None => Err(anyhow!("Could not find the cmus socket, set CMUS_SOCKET")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_player_backend_unknown() {
+        let config = Config {
+            player_backend: Some("winamp".to_string()),
+            ..Default::default()
+        };
+
+        let result = create_player_backend(&config);
+        assert!(result.is_err());
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Unknown player backend"));
+    }
+
+    #[test]
+    fn test_create_player_backend_mpd() {
+        let server = crate::player::mpd::tests::FakeMpd::tcp(|command| match command {
+            "status" => crate::player::mpd::tests::ok("state: stop\n"),
+            _ => crate::player::mpd::tests::ok(""),
+        });
+        let MpdAddress::Tcp(ref address) = server.address else {
+            unreachable!()
+        };
+        let (host, port) = address.rsplit_once(':').unwrap();
+
+        let config = Config {
+            player_backend: Some("mpd".to_string()),
+            mpd_host: Some(host.to_string()),
+            mpd_port: port.parse().ok(),
+            ..Default::default()
+        };
+        let mut backend = create_player_backend(&config).unwrap();
+        assert_eq!(backend.name(), "mpd");
+        assert_eq!(backend.status().unwrap(), "Stopped");
+    }
+
+    #[test]
+    fn test_create_player_backend_mpv() {
+        // mpv does not have to be running yet
+        let config = Config {
+            player_backend: Some("mpv".to_string()),
+            mpv_socket: Some("/nonexistent/mpvsocket".to_string()),
+            ..Default::default()
+        };
+        let backend = create_player_backend(&config).unwrap();
+        assert_eq!(backend.name(), "mpv");
+    }
+
+    #[test]
+    fn test_create_player_backend_cmus() {
+        let config = Config {
+            player_backend: Some("cmus".to_string()),
+            cmus_socket: Some("/tmp/cmus-socket".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            cmus_transport(&config).unwrap(),
+            CmusTransport::Socket("/tmp/cmus-socket".into())
+        );
+        assert_eq!(create_player_backend(&config).unwrap().name(), "cmus");
+    }
+
+    #[test]
+    fn test_create_player_backend_playerctl() {
+        let config = Config {
+            player_backend: Some("playerctl".to_string()),
+            ..Default::default()
+        };
+
+        // Only succeeds where playerctl is installed
+        match create_player_backend(&config) {
+            Ok(backend) => assert_eq!(backend.name(), "playerctl"),
+            Err(e) => assert!(!playerctl::is_playerctl_installed(), "{e}"),
+        }
+    }
+}
@@ -21,6 +21,7 @@ mod tests {
         let config = Config {
             tidal_client_id: Some("test_id".to_string()),
             tidal_client_secret: Some("test_secret".to_string()),
+            ..Default::default()
         };
 
         let provider = create_tidal_provider(&config);
@@ -32,6 +33,7 @@ mod tests {
         let config = Config {
             tidal_client_id: None,
             tidal_client_secret: None,
+            ..Default::default()
         };
 
         let provider = create_tidal_provider(&config);
@@ -43,6 +45,7 @@ mod tests {
         let config = Config {
             tidal_client_id: Some("test_id".to_string()),
             tidal_client_secret: None,
+            ..Default::default()
         };
 
         let provider = create_tidal_provider(&config);
@@ -54,6 +57,7 @@ mod tests {
         let config = Config {
             tidal_client_id: None,
             tidal_client_secret: Some("test_secret".to_string()),
+            ..Default::default()
         };
 
         let provider = create_tidal_provider(&config);
@@ -1,9 +1,24 @@
-use crate::models::AlbumMetadata;
+use crate::models::{AlbumMetadata, ArtistDetails, Credit, TrackMetadata};
 use anyhow::Result;
+use std::time::Duration;
 
 pub mod tidal;
 
-// For watch mode, we only need album metadata
+/// Metadata lookups for the playing track, all blocking
 pub trait MusicProvider {
     fn get_album_metadata(&mut self, artist: &str, album: &str) -> Result<AlbumMetadata>;
+
+    /// The track by artist and title, the duration picks between versions
+    /// of the same song. Works for players that send no album tag.
+    fn get_track_metadata(
+        &mut self,
+        artist: &str,
+        title: &str,
+        duration: Option<Duration>,
+    ) -> Result<TrackMetadata>;
+
+    fn get_artist_info(&mut self, artist: &str) -> Result<ArtistDetails>;
+
+    /// Producers, writers and the rest, by the id from `get_track_metadata`
+    fn get_credits(&mut self, track_id: &str) -> Result<Vec<Credit>>;
 }
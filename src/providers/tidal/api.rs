@@ -1,52 +1,106 @@
 use super::auth::TidalAuth;
-use crate::models::{AlbumMetadata, ArtistInfo};
+use crate::models::{AlbumMetadata, ArtistDetails, ArtistInfo, Credit, TrackMetadata};
 use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::time::Duration;
 
 const API_BASE_URL: &str = "https://openapi.tidal.com/v2";
 
-// These structs are kept for potential future use (track search, track number display)
-#[allow(dead_code)]
+// Credits are only served by the older API
+const CREDITS_URL: &str = "https://api.tidal.com/v1/tracks";
+// Versions of a song on different releases differ by a second or two
+const MAX_DURATION_DELTA_SECS: u32 = 5;
+
+/// JSON:API search results, the matches come in `included`
 #[derive(Debug, Deserialize)]
 pub struct SearchResponse {
-    pub tracks: Option<TracksWrapper>,
+    #[serde(default)]
+    pub included: Vec<Resource>,
+}
+
+/// A single resource with the ones pulled in by `include`
+#[derive(Debug, Deserialize)]
+pub struct Document<T> {
+    pub data: T,
+    #[serde(default)]
+    pub included: Vec<Resource>,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
-pub struct TracksWrapper {
-    pub items: Vec<Track>,
+#[serde(tag = "type")]
+pub enum Resource {
+    #[serde(rename = "tracks")]
+    Track(Track),
+    #[serde(rename = "artists")]
+    Artist(Artist),
+    #[serde(other)]
+    Other,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct Track {
     pub id: String,
+    pub attributes: TrackAttributes,
+    #[serde(default)]
+    pub relationships: TrackRelationships,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackAttributes {
     pub title: String,
-    pub duration: u32, // seconds
-    #[serde(rename = "trackNumber")]
-    pub track_number: Option<u32>,
-    pub artists: Vec<Artist>,
-    pub album: Album,
-    #[serde(rename = "audioQuality")]
-    pub audio_quality: Option<String>,
+    pub version: Option<String>, // "Radio Edit", "Live"...
+    pub isrc: Option<String>,
+    pub duration: Option<String>, // ISO 8601, PT3M45S
+    #[serde(default)]
+    pub explicit: bool,
+    pub popularity: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TrackRelationships {
+    #[serde(default)]
+    pub albums: Relationship,
+    #[serde(default)]
+    pub artists: Relationship,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Relationship {
+    #[serde(default)]
+    pub data: Vec<ResourceRef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResourceRef {
+    pub id: String,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct Artist {
     pub id: String,
+    pub attributes: ArtistAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArtistAttributes {
     pub name: String,
+    pub popularity: Option<f64>,
 }
 
-#[allow(dead_code)]
+/// One role in the credits of a track, `/v1/tracks/{id}/credits`
 #[derive(Debug, Deserialize)]
-pub struct Album {
-    pub id: String,
-    pub title: String,
-    pub cover: Option<String>, // UUID for cover art
-    #[serde(rename = "releaseDate")]
-    pub release_date: Option<String>,
+pub struct CreditGroup {
+    #[serde(rename = "type")]
+    pub role: String,
+    #[serde(default)]
+    pub contributors: Vec<Contributor>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Contributor {
+    pub name: String,
 }
 
 pub struct TidalApi {
@@ -73,7 +127,9 @@ impl TidalApi {
         )
     }
 
-    pub fn search_album(&mut self, artist: &str, album: &str) -> Result<AlbumMetadata> {
+    /// The album matching the name, `None` when the search finds nothing
+    /// that matches
+    pub fn search_album(&mut self, artist: &str, album: &str) -> Result<Option<AlbumMetadata>> {
         let token = self.auth.get_access_token()?;
 
         // Build search query - search for album by artist and album name
@@ -132,7 +188,7 @@ impl TidalApi {
                                // For long album names, try matching the simplified version
                                (album.len() > 50 && title_lower.contains(&simplified_album.to_lowercase()))
                             {
-                                return self.extract_album_metadata(item, included);
+                                return self.extract_album_metadata(item, included).map(Some);
                             }
                         }
                     }
@@ -140,7 +196,120 @@ impl TidalApi {
             }
         }
 
-        Err(anyhow!("No album found for: {} - {}", artist, album))
+        Ok(None)
+    }
+
+    /// Id of the track that best fits the title and duration, `None` when
+    /// the search finds nothing that fits
+    pub fn search_track(
+        &mut self,
+        artist: &str,
+        title: &str,
+        duration: Option<Duration>,
+    ) -> Result<Option<String>> {
+        let query = format!("{artist} {title}");
+        let url = format!(
+            "{API_BASE_URL}/searchResults/{}",
+            urlencoding::encode(&query)
+        );
+        let results: SearchResponse = self.get_json(&url, &[("include", "tracks")])?;
+
+        Ok(pick_track(&results.included, title, duration).map(|track| track.id.clone()))
+    }
+
+    /// Track details, its artists and the album it is on
+    pub fn get_track(&mut self, id: &str) -> Result<TrackMetadata> {
+        let url = format!("{API_BASE_URL}/tracks/{id}");
+        let document: Document<Track> = self.get_json(&url, &[("include", "albums,artists")])?;
+        let mut track = track_metadata(&document.data, &document.included);
+
+        // The album gives the cover and where the track sits on it
+        if let Some(album) = document.data.relationships.albums.data.first() {
+            match self.get_album(&album.id, id) {
+                Ok((album, position)) => {
+                    (track.track_number, track.volume_number) = position.unzip();
+                    track.album = Some(album);
+                }
+                Err(e) => eprintln!("Failed to fetch the album of {}: {e}", track.title),
+            }
+        }
+        Ok(track)
+    }
+
+    /// Album metadata and the (track, volume) number of `track_id` on it
+    fn get_album(
+        &mut self,
+        album_id: &str,
+        track_id: &str,
+    ) -> Result<(AlbumMetadata, Option<(u32, u32)>)> {
+        let url = format!("{API_BASE_URL}/albums/{album_id}");
+        let json: serde_json::Value =
+            self.get_json(&url, &[("include", "coverArt,artists,items")])?;
+        let album_item = json
+            .get("data")
+            .ok_or_else(|| anyhow!("Album response has no data"))?;
+        let included = json
+            .get("included")
+            .and_then(|v| v.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let metadata = self.extract_album_metadata(album_item, included)?;
+        Ok((metadata, track_position(album_item, track_id)))
+    }
+
+    /// The artist whose name matches, otherwise the best search result
+    pub fn search_artist(&mut self, name: &str) -> Result<ArtistDetails> {
+        let url = format!("{API_BASE_URL}/searchResults/{}", urlencoding::encode(name));
+        let results: SearchResponse = self.get_json(&url, &[("include", "artists")])?;
+
+        let artists: Vec<&Artist> = results
+            .included
+            .iter()
+            .filter_map(|resource| match resource {
+                Resource::Artist(artist) => Some(artist),
+                _ => None,
+            })
+            .collect();
+        let artist = artists
+            .iter()
+            .find(|artist| artist.attributes.name.eq_ignore_ascii_case(name))
+            .or(artists.first())
+            .ok_or_else(|| anyhow!("No artist found for: {}", name))?;
+
+        Ok(ArtistDetails {
+            id: artist.id.clone(),
+            name: artist.attributes.name.clone(),
+            popularity: artist.attributes.popularity,
+        })
+    }
+
+    pub fn get_credits(&mut self, track_id: &str) -> Result<Vec<Credit>> {
+        let url = format!("{CREDITS_URL}/{track_id}/credits");
+        let groups: Vec<CreditGroup> = self.get_json(&url, &[])?;
+        Ok(credits(groups))
+    }
+
+    /// GET with the access token, failing on any error status
+    fn get_json<T: DeserializeOwned>(&mut self, url: &str, query: &[(&str, &str)]) -> Result<T> {
+        let token = self.auth.get_access_token()?;
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(&token)
+            .header("Accept", "application/vnd.api+json")
+            .query(&[("countryCode", "US")])
+            .query(query)
+            .send()?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(anyhow!("Request failed: {} - {}", status, error_text));
+        }
+        Ok(response.json()?)
     }
 
     fn extract_album_metadata(
@@ -342,6 +511,107 @@ impl TidalApi {
     }
 }
 
+/// The first search result with the same title and, when both are known,
+/// about the same duration
+fn pick_track<'a>(
+    included: &'a [Resource],
+    title: &str,
+    duration: Option<Duration>,
+) -> Option<&'a Track> {
+    included
+        .iter()
+        .filter_map(|resource| match resource {
+            Resource::Track(track) => Some(track),
+            _ => None,
+        })
+        .filter(|track| track.attributes.title.eq_ignore_ascii_case(title))
+        .find(|track| {
+            let track_secs = track
+                .attributes
+                .duration
+                .as_deref()
+                .map(AlbumMetadata::parse_iso8601_duration);
+            match (track_secs, duration) {
+                (Some(track_secs), Some(duration)) => {
+                    track_secs.abs_diff(duration.as_secs() as u32) <= MAX_DURATION_DELTA_SECS
+                }
+                _ => true,
+            }
+        })
+}
+
+/// Track metadata from its resource, the album is looked up separately
+fn track_metadata(track: &Track, included: &[Resource]) -> TrackMetadata {
+    let attributes = &track.attributes;
+    let artists = track
+        .relationships
+        .artists
+        .data
+        .iter()
+        .filter_map(|artist_ref| {
+            included.iter().find_map(|resource| match resource {
+                Resource::Artist(artist) if artist.id == artist_ref.id => Some(ArtistInfo {
+                    id: artist.id.clone(),
+                    name: artist.attributes.name.clone(),
+                }),
+                _ => None,
+            })
+        })
+        .collect();
+    let title = match attributes.version {
+        Some(ref version) if !version.is_empty() => format!("{} ({version})", attributes.title),
+        _ => attributes.title.clone(),
+    };
+
+    TrackMetadata {
+        id: track.id.clone(),
+        title,
+        artists,
+        album: None,
+        track_number: None,
+        volume_number: None,
+        duration: attributes
+            .duration
+            .as_deref()
+            .map(AlbumMetadata::parse_iso8601_duration),
+        isrc: attributes.isrc.clone(),
+        explicit: attributes.explicit,
+        popularity: attributes.popularity,
+        credits: Vec::new(),
+    }
+}
+
+/// (track, volume) number from the `meta` of the album's items
+fn track_position(album_item: &serde_json::Value, track_id: &str) -> Option<(u32, u32)> {
+    let items = album_item
+        .get("relationships")?
+        .get("items")?
+        .get("data")?
+        .as_array()?;
+    let meta = items
+        .iter()
+        .find(|item| item.get("id").and_then(|id| id.as_str()) == Some(track_id))?
+        .get("meta")?;
+    let number = |key: &str| meta.get(key).and_then(|n| n.as_u64()).map(|n| n as u32);
+    Some((number("trackNumber")?, number("volumeNumber").unwrap_or(1)))
+}
+
+/// Roles without anyone credited are left out
+fn credits(groups: Vec<CreditGroup>) -> Vec<Credit> {
+    groups
+        .into_iter()
+        .filter(|group| !group.contributors.is_empty())
+        .map(|group| Credit {
+            role: group.role,
+            names: group
+                .contributors
+                .into_iter()
+                .map(|contributor| contributor.name)
+                .collect(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -521,6 +791,104 @@ mod tests {
         assert_eq!(AlbumMetadata::parse_iso8601_duration("PT52M30S"), 3150); // 52:30
     }
 
+    /// Trimmed `searchResults/Daft Punk One More Time?include=tracks`: the
+    /// radio edit, the album version and a live one
+    const SEARCH_ONE_MORE_TIME: &str = r#"{
+        "data": {"id": "Daft Punk One More Time", "type": "searchResults"},
+        "included": [
+            {"id": "1", "type": "tracks", "attributes": {"title": "One More Time", "version": "Radio Edit", "isrc": "GBDUW0000058", "duration": "PT3M51S", "explicit": false}},
+            {"id": "2", "type": "tracks", "attributes": {"title": "One More Time", "isrc": "GBDUW0000052", "duration": "PT5M20S", "explicit": false, "popularity": 0.81}},
+            {"id": "3", "type": "tracks", "attributes": {"title": "One More Time (Live)", "duration": "PT10M11S"}},
+            {"id": "9", "type": "videos", "attributes": {"title": "One More Time"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_pick_track() {
+        let results: SearchResponse = serde_json::from_str(SEARCH_ONE_MORE_TIME).unwrap();
+        let picked = |seconds: Option<u64>| {
+            pick_track(
+                &results.included,
+                "one more time",
+                seconds.map(Duration::from_secs),
+            )
+            .map(|track| track.id.as_str())
+        };
+
+        assert_eq!(picked(Some(320)), Some("2"));
+        assert_eq!(picked(Some(233)), Some("1"));
+        // Without a duration the most relevant result wins
+        assert_eq!(picked(None), Some("1"));
+        assert_eq!(picked(Some(611)), None);
+    }
+
+    #[test]
+    fn test_track_metadata() {
+        let document: Document<Track> = serde_json::from_value(json!({
+            "data": {
+                "id": "1",
+                "type": "tracks",
+                "attributes": {"title": "One More Time", "version": "Radio Edit", "isrc": "GBDUW0000058", "duration": "PT3M51S", "explicit": true},
+                "relationships": {
+                    "albums": {"data": [{"id": "77", "type": "albums"}]},
+                    "artists": {"data": [{"id": "8847", "type": "artists"}]}
+                }
+            },
+            "included": [
+                {"id": "8847", "type": "artists", "attributes": {"name": "Daft Punk", "popularity": 0.9}},
+                {"id": "77", "type": "albums", "attributes": {"title": "One More Time"}}
+            ]
+        }))
+        .unwrap();
+
+        let track = track_metadata(&document.data, &document.included);
+        assert_eq!(track.title, "One More Time (Radio Edit)");
+        assert_eq!(track.artists.len(), 1);
+        assert_eq!(track.artists[0].name, "Daft Punk");
+        assert_eq!(track.duration, Some(231));
+        assert_eq!(track.isrc.as_deref(), Some("GBDUW0000058"));
+        assert!(track.explicit);
+        assert_eq!(document.data.relationships.albums.data[0].id, "77");
+    }
+
+    #[test]
+    fn test_track_position() {
+        let album = json!({
+            "id": "77",
+            "type": "albums",
+            "relationships": {
+                "items": {"data": [
+                    {"id": "5", "type": "tracks", "meta": {"trackNumber": 1, "volumeNumber": 1}},
+                    {"id": "2", "type": "tracks", "meta": {"trackNumber": 3, "volumeNumber": 2}}
+                ]}
+            }
+        });
+        assert_eq!(track_position(&album, "2"), Some((3, 2)));
+        assert_eq!(track_position(&album, "8"), None);
+        assert_eq!(track_position(&json!({"id": "77"}), "2"), None);
+    }
+
+    #[test]
+    fn test_credits() {
+        let groups: Vec<CreditGroup> = serde_json::from_str(
+            r#"[
+                {"type": "Producer", "contributors": [{"name": "Thomas Bangalter", "id": 1}, {"name": "Guy-Manuel de Homem-Christo", "id": 2}]},
+                {"type": "Lyricist", "contributors": []},
+                {"type": "Composer", "contributors": [{"name": "Anthony Moore"}]}
+            ]"#,
+        )
+        .unwrap();
+
+        let credits = credits(groups);
+        assert_eq!(credits.len(), 2);
+        assert_eq!(credits[0].role, "Producer");
+        assert_eq!(
+            credits[0].names,
+            vec!["Thomas Bangalter", "Guy-Manuel de Homem-Christo"]
+        );
+        assert_eq!(credits[1].role, "Composer");
+    }
+
     #[test]
     fn test_album_search_query_simplification() {
         // Test that long album names are simplified
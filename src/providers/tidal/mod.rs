@@ -2,9 +2,11 @@ mod api;
 mod auth;
 
 use self::api::TidalApi;
-use crate::models::AlbumMetadata;
+use crate::models::{AlbumMetadata, ArtistDetails, Credit, TrackMetadata};
+use crate::normalize::{album_query_variants, CanonicalTrack};
 use crate::providers::MusicProvider;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::time::Duration;
 
 pub struct TidalProvider {
     api: TidalApi,
@@ -19,7 +21,38 @@ impl TidalProvider {
 }
 
 impl MusicProvider for TidalProvider {
+    /// Searches again with looser names until an album is found. A failing
+    /// request is not retried, looser names would fail the same way.
     fn get_album_metadata(&mut self, artist: &str, album: &str) -> Result<AlbumMetadata> {
-        self.api.search_album(artist, album)
+        for (artist, album) in album_query_variants(artist, album) {
+            if let Some(metadata) = self.api.search_album(&artist, &album)? {
+                return Ok(metadata);
+            }
+        }
+        Err(anyhow!("No album found for: {} - {}", artist, album))
+    }
+
+    /// Searches again with looser names until a track is found. A failing
+    /// request is not retried, looser names would fail the same way.
+    fn get_track_metadata(
+        &mut self,
+        artist: &str,
+        title: &str,
+        duration: Option<Duration>,
+    ) -> Result<TrackMetadata> {
+        for (artist, title) in CanonicalTrack::new(artist, title).query_variants() {
+            if let Some(id) = self.api.search_track(&artist, &title, duration)? {
+                return self.api.get_track(&id);
+            }
+        }
+        Err(anyhow!("No track found for: {} - {}", artist, title))
+    }
+
+    fn get_artist_info(&mut self, artist: &str) -> Result<ArtistDetails> {
+        self.api.search_artist(artist)
+    }
+
+    fn get_credits(&mut self, track_id: &str) -> Result<Vec<Credit>> {
+        self.api.get_credits(track_id)
     }
 }
@@ -0,0 +1,535 @@
+//! Reading tags embedded in local audio files
+
+use anyhow::{anyhow, Result};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const FLAC_MAGIC: &[u8; 4] = b"fLaC";
+const FLAC_VORBIS_COMMENT_BLOCK: u8 = 4;
+const FLAC_PICTURE_BLOCK: u8 = 6;
+const MP4_FILE_TYPE: &[u8; 4] = b"ftyp";
+// ID3 and FLAC both number the front cover 3
+const FRONT_COVER: u32 = 3;
+
+/// Cover picture embedded in the file, preferring the front cover.
+///
+/// FLAC pictures and ID3 tags (MP3, AIFF, WAV) are supported, other files
+/// give `None`.
+pub fn embedded_picture(path: &Path) -> Result<Option<Vec<u8>>> {
+    if file_kind(path)? == FileKind::Flac {
+        return flac_picture(path);
+    }
+
+    let Some(tag) = id3::no_tag_ok(id3::Tag::read_from_path(path))? else {
+        return Ok(None);
+    };
+
+    let picture = tag
+        .pictures()
+        .find(|picture| picture.picture_type == id3::frame::PictureType::CoverFront)
+        .or_else(|| tag.pictures().next())
+        .map(|picture| picture.data.clone());
+
+    Ok(picture)
+}
+
+/// Lyrics stored in a file's tags
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmbeddedLyrics {
+    /// ID3 `SYLT` frame: start time in milliseconds and text
+    Synced(Vec<(u64, String)>),
+    /// Plain text, which may itself be LRC
+    Text(String),
+}
+
+/// Lyrics embedded in the file.
+///
+/// Reads ID3 `SYLT` (preferred) and `USLT` frames, FLAC Vorbis comments
+/// `LYRICS` / `UNSYNCEDLYRICS` and the MP4 `©lyr` atom.
+pub fn embedded_lyrics(path: &Path) -> Result<Option<EmbeddedLyrics>> {
+    let text = match file_kind(path)? {
+        FileKind::Flac => flac_lyrics(path)?,
+        FileKind::Mp4 => mp4_lyrics(path)?,
+        FileKind::Other => return id3_lyrics(path),
+    };
+    Ok(text.map(EmbeddedLyrics::Text))
+}
+
+#[derive(Debug, PartialEq)]
+enum FileKind {
+    Flac,
+    Mp4,
+    Other,
+}
+
+fn file_kind(path: &Path) -> Result<FileKind> {
+    let mut magic = [0u8; 8];
+    let read = File::open(path)?.read(&mut magic)?;
+    if read >= 4 && &magic[..4] == FLAC_MAGIC {
+        Ok(FileKind::Flac)
+    } else if read == magic.len() && &magic[4..] == MP4_FILE_TYPE {
+        Ok(FileKind::Mp4)
+    } else {
+        Ok(FileKind::Other)
+    }
+}
+
+fn flac_picture(path: &Path) -> Result<Option<Vec<u8>>> {
+    let mut fallback = None;
+    for block in flac_blocks(path, FLAC_PICTURE_BLOCK)? {
+        let (picture_type, data) = parse_flac_picture(&block)?;
+        if picture_type == FRONT_COVER {
+            return Ok(Some(data));
+        }
+        fallback.get_or_insert(data);
+    }
+    Ok(fallback)
+}
+
+fn flac_lyrics(path: &Path) -> Result<Option<String>> {
+    for block in flac_blocks(path, FLAC_VORBIS_COMMENT_BLOCK)? {
+        let comments = parse_vorbis_comments(&block)?;
+        for key in ["LYRICS", "UNSYNCEDLYRICS"] {
+            let lyrics = comments
+                .iter()
+                .find(|(name, value)| name.eq_ignore_ascii_case(key) && !value.trim().is_empty());
+            if let Some((_, value)) = lyrics {
+                return Ok(Some(value.clone()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Contents of every FLAC metadata block of the given type
+fn flac_blocks(path: &Path, wanted_type: u8) -> Result<Vec<Vec<u8>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    reader.read_exact(&mut [0u8; 4])?;
+
+    let mut blocks = Vec::new();
+    loop {
+        // 1 bit "last block", 7 bits type, 24 bits length
+        let mut header = [0u8; 4];
+        reader.read_exact(&mut header)?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7f;
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+
+        if block_type == wanted_type {
+            let mut block = vec![0u8; length];
+            reader.read_exact(&mut block)?;
+            blocks.push(block);
+        } else {
+            reader.seek_relative(length as i64)?;
+        }
+
+        if is_last {
+            return Ok(blocks);
+        }
+    }
+}
+
+/// Picture type and image data of a FLAC `PICTURE` block
+fn parse_flac_picture(block: &[u8]) -> Result<(u32, Vec<u8>)> {
+    let mut block = ByteReader(block);
+
+    let picture_type = block.u32()?;
+    let mime_length = block.u32()? as usize;
+    block.take(mime_length)?;
+    let description_length = block.u32()? as usize;
+    block.take(description_length)?;
+    // Width, height, colour depth, palette size
+    block.take(16)?;
+    let data_length = block.u32()? as usize;
+    let data = block.take(data_length)?.to_vec();
+
+    Ok((picture_type, data))
+}
+
+/// `NAME=value` pairs of a Vorbis comment block
+fn parse_vorbis_comments(block: &[u8]) -> Result<Vec<(String, String)>> {
+    let mut block = ByteReader(block);
+
+    // Unlike the rest of FLAC, Vorbis comments are little-endian
+    let vendor_length = block.u32_le()? as usize;
+    block.take(vendor_length)?;
+    let count = block.u32_le()?;
+
+    let mut comments = Vec::new();
+    for _ in 0..count {
+        let length = block.u32_le()? as usize;
+        let comment = String::from_utf8_lossy(block.take(length)?);
+        if let Some((name, value)) = comment.split_once('=') {
+            comments.push((name.to_string(), value.to_string()));
+        }
+    }
+    Ok(comments)
+}
+
+fn id3_lyrics(path: &Path) -> Result<Option<EmbeddedLyrics>> {
+    let Some(tag) = id3::no_tag_ok(id3::Tag::read_from_path(path))? else {
+        return Ok(None);
+    };
+
+    // MPEG frame timestamps would need the frame rate, only take milliseconds
+    let synced = tag
+        .synchronised_lyrics()
+        .find(|lyrics| {
+            lyrics.timestamp_format == id3::frame::TimestampFormat::Ms && !lyrics.content.is_empty()
+        })
+        .map(|lyrics| {
+            let lines = lyrics
+                .content
+                .iter()
+                .map(|(timestamp, text)| (u64::from(*timestamp), text.trim().to_string()))
+                .collect();
+            EmbeddedLyrics::Synced(lines)
+        });
+    if synced.is_some() {
+        return Ok(synced);
+    }
+
+    let text = tag
+        .lyrics()
+        .find(|lyrics| !lyrics.text.trim().is_empty())
+        .map(|lyrics| EmbeddedLyrics::Text(lyrics.text.clone()));
+    Ok(text)
+}
+
+fn mp4_lyrics(path: &Path) -> Result<Option<String>> {
+    let Some(moov) = mp4_moov(path)? else {
+        return Ok(None);
+    };
+
+    let lyrics = find_atom(&moov, b"udta")
+        .and_then(|udta| find_atom(udta, b"meta"))
+        // meta is a full atom with version and flags first, except in
+        // some QuickTime files
+        .map(|meta| match meta.get(4..8) {
+            Some(b"hdlr") => meta,
+            _ => meta.get(4..).unwrap_or_default(),
+        })
+        .and_then(|meta| find_atom(meta, b"ilst"))
+        .and_then(|ilst| find_atom(ilst, b"\xa9lyr"))
+        .and_then(|lyr| find_atom(lyr, b"data"))
+        // Type and locale come before the text
+        .and_then(|data| data.get(8..))
+        .map(|text| String::from_utf8_lossy(text).into_owned())
+        .filter(|text| !text.trim().is_empty());
+    Ok(lyrics)
+}
+
+/// The `moov` atom, holding the metadata, read without touching the audio
+fn mp4_moov(path: &Path) -> Result<Option<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    let file_length = file.metadata()?.len();
+
+    let mut offset = 0;
+    while offset + 8 <= file_length {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header)?;
+        let (header_length, size) =
+            match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+                // 64-bit size after the name
+                1 => {
+                    let mut size = [0u8; 8];
+                    file.read_exact(&mut size)?;
+                    (16, u64::from_be_bytes(size))
+                }
+                // Runs to the end of the file
+                0 => (8, file_length - offset),
+                size => (8, u64::from(size)),
+            };
+        // A corrupt size would allocate whatever it claims
+        if size < header_length || size > file_length - offset {
+            return Err(anyhow!("Invalid MP4 atom size"));
+        }
+
+        if &header[4..] == b"moov" {
+            let mut moov = vec![0u8; (size - header_length) as usize];
+            file.read_exact(&mut moov)?;
+            return Ok(Some(moov));
+        }
+        offset = offset
+            .checked_add(size)
+            .ok_or_else(|| anyhow!("Invalid MP4 atom size"))?;
+    }
+    Ok(None)
+}
+
+/// Body of the first child atom with this name
+fn find_atom<'a>(mut data: &'a [u8], name: &[u8; 4]) -> Option<&'a [u8]> {
+    while data.len() >= 8 {
+        let size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let (header_length, size) = match size {
+            1 => (
+                16,
+                u64::from_be_bytes(data.get(8..16)?.try_into().ok()?) as usize,
+            ),
+            0 => (8, data.len()),
+            size => (8, size),
+        };
+        if size < header_length || size > data.len() {
+            return None;
+        }
+        if &data[4..8] == name {
+            return Some(&data[header_length..size]);
+        }
+        data = &data[size..];
+    }
+    None
+}
+
+/// Big-endian reader over a metadata block
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.0.len() < count {
+            return Err(anyhow!("Truncated metadata block"));
+        }
+        let (head, tail) = self.0.split_at(count);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u32_le(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::TagLike;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "trackwatch-test-{}-{}-{name}",
+            std::process::id(),
+            FILE_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn flac_block(block_type: u8, last: bool, body: &[u8]) -> Vec<u8> {
+        let length = (body.len() as u32).to_be_bytes();
+        let mut block = vec![block_type | if last { 0x80 } else { 0 }];
+        block.extend_from_slice(&length[1..]);
+        block.extend_from_slice(body);
+        block
+    }
+
+    fn flac_picture_body(picture_type: u32, data: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&picture_type.to_be_bytes());
+        body.extend_from_slice(&10u32.to_be_bytes());
+        body.extend_from_slice(b"image/jpeg");
+        body.extend_from_slice(&0u32.to_be_bytes());
+        for _ in 0..4 {
+            body.extend_from_slice(&0u32.to_be_bytes());
+        }
+        body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        body.extend_from_slice(data);
+        body
+    }
+
+    #[test]
+    fn test_flac_prefers_front_cover() {
+        let mut flac = FLAC_MAGIC.to_vec();
+        flac.extend(flac_block(0, false, &[0u8; 34])); // STREAMINFO
+        flac.extend(flac_block(6, false, &flac_picture_body(4, b"back")));
+        flac.extend(flac_block(6, true, &flac_picture_body(3, b"front")));
+        flac.extend_from_slice(b"audio frames");
+        let path = temp_file("cover.flac", &flac);
+
+        assert_eq!(embedded_picture(&path).unwrap(), Some(b"front".to_vec()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_flac_without_pictures() {
+        let mut flac = FLAC_MAGIC.to_vec();
+        flac.extend(flac_block(0, true, &[0u8; 34]));
+        let path = temp_file("plain.flac", &flac);
+
+        assert_eq!(embedded_picture(&path).unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_id3_picture() {
+        let path = temp_file("cover.mp3", &[]);
+        let mut tag = id3::Tag::new();
+        tag.set_title("Song");
+        tag.add_frame(id3::frame::Picture {
+            mime_type: "image/png".to_string(),
+            picture_type: id3::frame::PictureType::CoverFront,
+            description: String::new(),
+            data: b"png bytes".to_vec(),
+        });
+        tag.write_to_path(&path, id3::Version::Id3v24).unwrap();
+
+        assert_eq!(
+            embedded_picture(&path).unwrap(),
+            Some(b"png bytes".to_vec())
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_untagged_file() {
+        let path = temp_file("untagged.mp3", b"\xff\xfbnot really audio");
+        assert_eq!(embedded_picture(&path).unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+
+        assert!(embedded_picture(Path::new("/nonexistent/song.mp3")).is_err());
+    }
+
+    fn vorbis_comment_body(comments: &[&str]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&6u32.to_le_bytes());
+        body.extend_from_slice(b"vendor");
+        body.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for comment in comments {
+            body.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            body.extend_from_slice(comment.as_bytes());
+        }
+        body
+    }
+
+    fn mp4_atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut atom = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        atom.extend_from_slice(name);
+        atom.extend_from_slice(body);
+        atom
+    }
+
+    #[test]
+    fn test_flac_vorbis_lyrics() {
+        let mut flac = FLAC_MAGIC.to_vec();
+        flac.extend(flac_block(0, false, &[0u8; 34]));
+        flac.extend(flac_block(6, false, &flac_picture_body(3, b"front")));
+        let comments = vorbis_comment_body(&["TITLE=Song", "lyrics=[00:01.00]Hello"]);
+        flac.extend(flac_block(4, true, &comments));
+        let path = temp_file("lyrics.flac", &flac);
+
+        assert_eq!(
+            embedded_lyrics(&path).unwrap(),
+            Some(EmbeddedLyrics::Text("[00:01.00]Hello".to_string()))
+        );
+        // Reading lyrics doesn't get in the way of the picture
+        assert_eq!(embedded_picture(&path).unwrap(), Some(b"front".to_vec()));
+        std::fs::remove_file(path).unwrap();
+
+        let mut flac = FLAC_MAGIC.to_vec();
+        let comments = vorbis_comment_body(&["LYRICS=", "UNSYNCEDLYRICS=Plain words"]);
+        flac.extend(flac_block(4, true, &comments));
+        let path = temp_file("unsynced.flac", &flac);
+        assert_eq!(
+            embedded_lyrics(&path).unwrap(),
+            Some(EmbeddedLyrics::Text("Plain words".to_string()))
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_id3_lyrics() {
+        let path = temp_file("lyrics.mp3", &[]);
+        let mut tag = id3::Tag::new();
+        tag.add_frame(id3::frame::Lyrics {
+            lang: "eng".to_string(),
+            description: String::new(),
+            text: "Unsynced words".to_string(),
+        });
+        tag.write_to_path(&path, id3::Version::Id3v24).unwrap();
+        assert_eq!(
+            embedded_lyrics(&path).unwrap(),
+            Some(EmbeddedLyrics::Text("Unsynced words".to_string()))
+        );
+
+        // SYLT wins over USLT
+        tag.add_frame(id3::frame::SynchronisedLyrics {
+            lang: "eng".to_string(),
+            timestamp_format: id3::frame::TimestampFormat::Ms,
+            content_type: id3::frame::SynchronisedLyricsType::Lyrics,
+            description: String::new(),
+            content: vec![
+                (1_500, "First".to_string()),
+                (4_000, "\nSecond".to_string()),
+            ],
+        });
+        tag.write_to_path(&path, id3::Version::Id3v24).unwrap();
+        assert_eq!(
+            embedded_lyrics(&path).unwrap(),
+            Some(EmbeddedLyrics::Synced(vec![
+                (1_500, "First".to_string()),
+                (4_000, "Second".to_string())
+            ]))
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mp4_lyrics() {
+        let mut data_body = vec![0, 0, 0, 1, 0, 0, 0, 0];
+        data_body.extend_from_slice(b"[00:02.00]Hi");
+        let ilst = mp4_atom(
+            b"ilst",
+            &mp4_atom(b"\xa9lyr", &mp4_atom(b"data", &data_body)),
+        );
+        let mut meta_body = vec![0u8; 4];
+        meta_body.extend(mp4_atom(b"hdlr", &[0u8; 25]));
+        meta_body.extend(ilst);
+        let moov = mp4_atom(b"moov", &mp4_atom(b"udta", &mp4_atom(b"meta", &meta_body)));
+
+        let mut mp4 = mp4_atom(b"ftyp", b"M4A \0\0\0\0");
+        mp4.extend(mp4_atom(b"mdat", b"audio"));
+        mp4.extend(moov);
+        let path = temp_file("lyrics.m4a", &mp4);
+
+        assert_eq!(
+            embedded_lyrics(&path).unwrap(),
+            Some(EmbeddedLyrics::Text("[00:02.00]Hi".to_string()))
+        );
+        std::fs::remove_file(path).unwrap();
+
+        let path = temp_file("bare.m4a", &mp4_atom(b"ftyp", b"M4A \0\0\0\0"));
+        assert_eq!(embedded_lyrics(&path).unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mp4_bad_atom_sizes() {
+        // moov claims more than the file holds
+        let mut mp4 = mp4_atom(b"ftyp", b"M4A \0\0\0\0");
+        let moov = mp4_atom(b"moov", &[0u8; 64]);
+        mp4.extend_from_slice(&moov[..32]);
+        let path = temp_file("truncated.m4a", &mp4);
+        assert!(embedded_lyrics(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+
+        // 64-bit size near u64::MAX
+        let mut mp4 = mp4_atom(b"ftyp", b"M4A \0\0\0\0");
+        mp4.extend_from_slice(&1u32.to_be_bytes());
+        mp4.extend_from_slice(b"free");
+        mp4.extend_from_slice(&(u64::MAX - 4).to_be_bytes());
+        mp4.extend(mp4_atom(b"moov", &[0u8; 8]));
+        let path = temp_file("huge.m4a", &mp4);
+        assert!(embedded_lyrics(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+//! Candidate picker: every LRCLIB search result for the track, to pin the
+//! right lyrics when automatic selection got it wrong
+
+use anyhow::Result;
+
+use crate::lyrics::api::{rank_candidates, LrcLibClient};
+use crate::lyrics::source::TrackQuery;
+use crate::lyrics::{parser, LyricsResponse};
+
+// Lines of the selected candidate shown next to the list
+pub const PREVIEW_LINES: usize = 12;
+
+#[derive(Debug, Clone)]
+pub struct LyricsPicker {
+    pub track: TrackQuery, // the playing track the pick is pinned to
+    pub query: String,     // search field, free text
+    pub editing: bool,     // keys go to the search field until Enter
+    pub results: Vec<LyricsResponse>,
+    pub selected: usize,
+    pub pending: Option<CandidateSearch>, // search waiting for its results
+    pub error: Option<String>,
+}
+
+/// What the picker asks LRCLIB for
+#[derive(Debug, Clone, PartialEq)]
+pub enum CandidateSearch {
+    Track(TrackQuery), // the track's own artist and title
+    Keywords(String),  // whatever was typed in the search field
+}
+
+impl CandidateSearch {
+    /// Results ranked against the playing track
+    pub async fn run(
+        &self,
+        client: &LrcLibClient,
+        track: &TrackQuery,
+    ) -> Result<Vec<LyricsResponse>> {
+        let results = match self {
+            CandidateSearch::Track(query) => {
+                client.search_lyrics(&query.title, &query.artist).await?
+            }
+            CandidateSearch::Keywords(keywords) => client.search_keywords(keywords).await?,
+        };
+        Ok(rank_candidates(results, track))
+    }
+}
+
+impl LyricsPicker {
+    /// Picker for `track`, with the search that fills it
+    pub fn new(track: TrackQuery) -> (Self, CandidateSearch) {
+        let search = CandidateSearch::Track(track.clone());
+        let picker = Self {
+            query: format!("{} {}", track.artist, track.title)
+                .trim()
+                .to_string(),
+            track,
+            editing: false,
+            results: Vec::new(),
+            selected: 0,
+            pending: Some(search.clone()),
+            error: None,
+        };
+        (picker, search)
+    }
+
+    /// Search for what is in the search field
+    pub fn search(&mut self) -> Option<CandidateSearch> {
+        self.editing = false;
+        let keywords = self.query.trim();
+        if keywords.is_empty() {
+            return None;
+        }
+        let search = CandidateSearch::Keywords(keywords.to_string());
+        self.pending = Some(search.clone());
+        Some(search)
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Take the results of `search`, unless a newer search was started
+    pub fn set_results(&mut self, search: &CandidateSearch, results: Result<Vec<LyricsResponse>>) {
+        if self.pending.as_ref() != Some(search) {
+            return;
+        }
+        self.pending = None;
+        match results {
+            Ok(results) => {
+                self.results = results;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.selected = 0;
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> Option<&LyricsResponse> {
+        self.results.get(self.selected)
+    }
+
+    /// How much longer the candidate is than the track, in seconds
+    pub fn duration_delta(&self, candidate: &LyricsResponse) -> Option<f64> {
+        Some(candidate.duration? - self.track.duration?.as_secs_f64())
+    }
+}
+
+/// "+2s", "-14s" or "±0s"
+pub fn format_delta(delta_secs: f64) -> String {
+    let rounded = delta_secs.round() as i64;
+    if rounded == 0 {
+        "±0s".to_string()
+    } else {
+        format!("{rounded:+}s")
+    }
+}
+
+/// "synced", "plain" or "instrumental"
+pub fn lyrics_kind(candidate: &LyricsResponse) -> &'static str {
+    if candidate.instrumental {
+        "instrumental"
+    } else if candidate.has_synced_lyrics() {
+        "synced"
+    } else {
+        "plain"
+    }
+}
+
+/// The first lines of the candidate's lyrics, without timestamps
+pub fn preview(candidate: &LyricsResponse) -> Vec<String> {
+    let Some(text) = candidate.get_best_lyrics() else {
+        return Vec::new();
+    };
+    parser::parse_lrc(text)
+        .lines
+        .into_iter()
+        .map(|line| line.text)
+        .filter(|text| !text.trim().is_empty())
+        .take(PREVIEW_LINES)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::source::tests::response;
+    use anyhow::anyhow;
+    use std::time::Duration;
+
+    fn track() -> TrackQuery {
+        TrackQuery {
+            artist: "Daft Punk".to_string(),
+            title: "Digital Love".to_string(),
+            duration: Some(Duration::from_secs(301)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_picker_searches() {
+        let (mut picker, search) = LyricsPicker::new(track());
+        assert_eq!(search, CandidateSearch::Track(track()));
+        assert_eq!(picker.query, "Daft Punk Digital Love");
+        assert!(picker.is_searching());
+
+        // Typing a new query makes the first results stale
+        picker.query = "digital love live".to_string();
+        let keywords = picker.search().unwrap();
+        picker.set_results(&search, Ok(vec![response(None, Some("Old"))]));
+        assert!(picker.results.is_empty());
+
+        let candidates = vec![
+            response(Some("[00:01.00]Last night"), None),
+            response(None, Some("I had a dream about you")),
+        ];
+        picker.set_results(&keywords, Ok(candidates));
+        assert!(!picker.is_searching());
+        picker.select_next();
+        picker.select_next();
+        assert_eq!(picker.selected, 1);
+        picker.select_previous();
+        assert_eq!(lyrics_kind(picker.selected().unwrap()), "synced");
+
+        picker.query = "  ".to_string();
+        assert_eq!(picker.search(), None);
+        picker.pending = Some(keywords.clone());
+        picker.set_results(&keywords, Err(anyhow!("offline")));
+        assert_eq!(picker.error.as_deref(), Some("offline"));
+    }
+
+    #[test]
+    fn test_candidate_details() {
+        let (picker, _) = LyricsPicker::new(track());
+        let mut candidate = response(
+            Some("[00:01.00]Last night\n[00:03.00]\n[00:04.00]I had"),
+            None,
+        );
+        assert_eq!(picker.duration_delta(&candidate), None);
+        candidate.duration = Some(299.4);
+        assert_eq!(
+            format_delta(picker.duration_delta(&candidate).unwrap()),
+            "-2s"
+        );
+        assert_eq!(format_delta(0.3), "±0s");
+        assert_eq!(format_delta(12.0), "+12s");
+
+        assert_eq!(preview(&candidate), vec!["Last night", "I had"]);
+        candidate.instrumental = true;
+        assert_eq!(lyrics_kind(&candidate), "instrumental");
+        assert!(preview(&response(None, None)).is_empty());
+    }
+}
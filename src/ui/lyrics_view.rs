@@ -0,0 +1,322 @@
+//! Viewer state of the lyrics pane: manual scrolling, search, and where
+//! each lyric ended up on screen so clicks can be mapped back to it
+
+use ratatui::layout::Rect;
+
+use crate::lyrics::{LyricLine, ParsedLyrics};
+
+// Rows moved per mouse wheel step
+pub const WHEEL_STEP: isize = 3;
+
+#[derive(Debug, Clone, Default)]
+pub struct LyricsView {
+    pub scroll: Option<usize>, // first row shown, None follows the current line
+    pub search: Option<Search>,
+    // Layout of the last frame
+    pub area: Rect,
+    pub top: usize,
+    pub rows: Vec<Option<u64>>, // timestamp of the lyric drawn on each row
+    pub line_rows: Vec<usize>,  // first row of each lyric
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Search {
+    pub query: String,
+    pub editing: bool, // keys go to the query until Enter
+}
+
+impl LyricsView {
+    /// Rows of lyrics that fit in the pane, below its top padding
+    pub fn visible_height(&self) -> usize {
+        self.area.height.saturating_sub(2) as usize
+    }
+
+    pub fn max_scroll(&self) -> usize {
+        self.rows.len().saturating_sub(self.visible_height())
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.scroll.is_none()
+    }
+
+    /// Remember the layout of the frame being drawn and pick its top row,
+    /// `follow_top` when following
+    pub fn layout(
+        &mut self,
+        area: Rect,
+        rows: Vec<Option<u64>>,
+        line_rows: Vec<usize>,
+        follow_top: usize,
+    ) -> usize {
+        self.area = area;
+        self.rows = rows;
+        self.line_rows = line_rows;
+        self.top = self.scroll.unwrap_or(follow_top).min(self.max_scroll());
+        self.top
+    }
+
+    /// Scroll by `delta` rows, leaving auto-follow
+    pub fn scroll_by(&mut self, delta: isize) {
+        let from = self.scroll.unwrap_or(self.top);
+        self.scroll = Some(from.saturating_add_signed(delta).min(self.max_scroll()));
+    }
+
+    pub fn page(&mut self, forward: bool) {
+        let page = self.visible_height().max(1) as isize;
+        self.scroll_by(if forward { page } else { -page });
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = Some(0);
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = Some(self.max_scroll());
+    }
+
+    pub fn follow(&mut self) {
+        self.scroll = None;
+    }
+
+    /// Timestamp of the synced lyric drawn at a terminal cell
+    pub fn timestamp_at(&self, column: u16, row: u16) -> Option<u64> {
+        let area = self.area;
+        if column < area.x || column >= area.x + area.width {
+            return None;
+        }
+        if row < area.y || row >= area.y + area.height {
+            return None;
+        }
+        let content_row = self.top + (row - area.y) as usize;
+        self.rows.get(content_row).copied().flatten()
+    }
+
+    pub fn start_search(&mut self) {
+        let query = self.search.take().map(|s| s.query).unwrap_or_default();
+        self.search = Some(Search {
+            query,
+            editing: true,
+        });
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.as_ref().is_some_and(|search| search.editing)
+    }
+
+    /// The query while it is being typed or its matches are highlighted
+    pub fn query(&self) -> Option<&str> {
+        self.search
+            .as_ref()
+            .map(|search| search.query.as_str())
+            .filter(|query| !query.is_empty())
+    }
+
+    /// Stop typing, the matches stay highlighted
+    pub fn finish_search(&mut self) {
+        if let Some(ref mut search) = self.search {
+            search.editing = false;
+        }
+        if self.query().is_none() {
+            self.search = None;
+        }
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search = None;
+    }
+
+    /// Type into the query and show the first match from the top of the pane
+    pub fn edit_query(&mut self, lyrics: Option<&ParsedLyrics>, edit: impl FnOnce(&mut String)) {
+        if let Some(ref mut search) = self.search {
+            edit(&mut search.query);
+        }
+        let from = self.scroll.unwrap_or(self.top);
+        if let Some(row) = self.match_rows(lyrics).into_iter().find(|row| *row >= from) {
+            self.show_row(row);
+        }
+    }
+
+    /// Move to the next or previous match, wrapping around
+    pub fn jump_to_match(&mut self, lyrics: Option<&ParsedLyrics>, forward: bool) {
+        let rows = self.match_rows(lyrics);
+        let from = self.scroll.unwrap_or(self.top) + self.visible_height() / 3;
+        let row = if forward {
+            rows.iter().find(|row| **row > from).or(rows.first())
+        } else {
+            rows.iter().rev().find(|row| **row < from).or(rows.last())
+        };
+        if let Some(&row) = row {
+            self.show_row(row);
+        }
+    }
+
+    /// Rows of the lyrics that match the query
+    fn match_rows(&self, lyrics: Option<&ParsedLyrics>) -> Vec<usize> {
+        let (Some(query), Some(lyrics)) = (self.query(), lyrics) else {
+            return Vec::new();
+        };
+        lyrics
+            .lines
+            .iter()
+            .zip(&self.line_rows)
+            .filter(|(line, _)| line_matches(line, query))
+            .map(|(_, row)| *row)
+            .collect()
+    }
+
+    /// Scroll so `row` sits a third of the way down the pane
+    fn show_row(&mut self, row: usize) {
+        let top = row.saturating_sub(self.visible_height() / 3);
+        self.scroll = Some(top.min(self.max_scroll()));
+    }
+}
+
+/// Whether the lyric or its translation contains `query`, ignoring case
+pub fn line_matches(line: &LyricLine, query: &str) -> bool {
+    let query = query.to_lowercase();
+    std::iter::once(&line.text)
+        .chain(&line.secondary)
+        .any(|text| text.to_lowercase().contains(&query))
+}
+
+/// Byte ranges of `query` in `text`, ignoring case
+pub fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // Lowercasing can change the length of a letter, so remember which char
+    // of `text` each lowercased byte came from
+    let mut lower = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (start, c) in text.char_indices() {
+        let lower_start = lower.len();
+        lower.extend(c.to_lowercase());
+        let origin = (start, start + c.len_utf8());
+        origins.extend(std::iter::repeat_n(origin, lower.len() - lower_start));
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (lower_start, found) in lower.match_indices(&query) {
+        let (start, _) = origins[lower_start];
+        let (_, end) = origins[lower_start + found.len() - 1];
+        // Two matches inside the lowercase form of one letter
+        if ranges.last().is_some_and(|&(_, last_end)| start < last_end) {
+            continue;
+        }
+        ranges.push((start, end));
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::parser;
+
+    fn view(rows: usize, height: u16) -> LyricsView {
+        let mut view = LyricsView::default();
+        view.layout(
+            Rect::new(100, 10, 30, height),
+            (0..rows).map(|row| Some(row as u64 * 1000)).collect(),
+            (0..rows).collect(),
+            0,
+        );
+        view
+    }
+
+    #[test]
+    fn test_scrolling_detaches_and_follow_relocks() {
+        let mut view = view(40, 12);
+        assert_eq!(view.max_scroll(), 30);
+
+        view.scroll_by(3);
+        assert_eq!(view.scroll, Some(3));
+        view.scroll_by(-10);
+        assert_eq!(view.scroll, Some(0));
+        view.page(true);
+        assert_eq!(view.scroll, Some(10));
+        view.scroll_to_bottom();
+        view.scroll_by(5);
+        assert_eq!(view.scroll, Some(30));
+
+        // Manual scroll wins over the followed line
+        let rows = view.rows.clone();
+        assert_eq!(
+            view.layout(view.area, rows.clone(), view.line_rows.clone(), 7),
+            30
+        );
+        view.follow();
+        assert!(view.is_following());
+        assert_eq!(view.layout(view.area, rows, view.line_rows.clone(), 7), 7);
+    }
+
+    #[test]
+    fn test_timestamp_at() {
+        let mut view = view(40, 12);
+        view.scroll = Some(5);
+        view.layout(view.area, view.rows.clone(), view.line_rows.clone(), 0);
+
+        assert_eq!(view.timestamp_at(100, 10), Some(5_000));
+        assert_eq!(view.timestamp_at(129, 12), Some(7_000));
+        assert_eq!(view.timestamp_at(130, 12), None);
+        assert_eq!(view.timestamp_at(100, 9), None);
+
+        view.rows[6] = None;
+        assert_eq!(view.timestamp_at(100, 11), None);
+    }
+
+    #[test]
+    fn test_search_jumps_to_matches() {
+        let text: String = (0..30)
+            .map(|n| {
+                let word = if n % 10 == 7 { "Celebrate" } else { "La" };
+                format!("[00:{n:02}.00]{word} {n}\n")
+            })
+            .collect();
+        let lyrics = parser::parse_lrc(&text);
+        let mut view = view(30, 8);
+
+        view.start_search();
+        assert!(view.is_searching());
+        view.edit_query(Some(&lyrics), |query| query.push_str("CELEB"));
+        // The first match is shown a third of the way down
+        assert_eq!(view.scroll, Some(5));
+        view.layout(view.area, view.rows.clone(), view.line_rows.clone(), 0);
+
+        view.jump_to_match(Some(&lyrics), true);
+        assert_eq!(view.scroll, Some(15));
+        view.layout(view.area, view.rows.clone(), view.line_rows.clone(), 0);
+        view.jump_to_match(Some(&lyrics), false);
+        assert_eq!(view.scroll, Some(5));
+
+        view.finish_search();
+        assert!(!view.is_searching());
+        assert_eq!(view.query(), Some("CELEB"));
+        view.cancel_search();
+        assert_eq!(view.query(), None);
+
+        // An empty search leaves nothing behind
+        view.start_search();
+        view.finish_search();
+        assert!(view.search.is_none());
+    }
+
+    #[test]
+    fn test_match_ranges() {
+        assert_eq!(match_ranges("Na na NA", "na"), vec![(0, 2), (3, 5), (6, 8)]);
+        assert_eq!(match_ranges("Ça va", "ça"), vec![(0, 3)]);
+        assert!(match_ranges("Hello", "").is_empty());
+
+        // Letters whose lowercase form is longer or shorter keep their offsets
+        assert_eq!(match_ranges("İẞ", "ß"), vec![(2, 5)]);
+        assert_eq!(match_ranges("İstanbul", "stan"), vec![(2, 6)]);
+        assert_eq!(match_ranges("STRAẞE", "ße"), vec![(4, 8)]);
+
+        let line = parser::parse_lrc("[00:01.00]君の名前\n[00:01.00]Your name").lines[0].clone();
+        assert!(line_matches(&line, "your"));
+        assert!(line_matches(&line, "名前"));
+        assert!(!line_matches(&line, "mine"));
+    }
+}
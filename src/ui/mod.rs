@@ -1,6 +1,9 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+        MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -13,26 +16,68 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::io;
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, Instant};
 
+pub mod lyrics_picker;
+pub mod lyrics_view;
+
+use self::lyrics_picker::{format_delta, lyrics_kind, preview, CandidateSearch, LyricsPicker};
+use self::lyrics_view::{match_ranges, LyricsView, WHEEL_STEP};
 use crate::colors::ColorPalette;
 use crate::display::{PixelatedImage, RatatuiImage};
-use crate::lyrics::{parser, ParsedLyrics};
-use crate::models::AlbumMetadata;
-use crate::player::PlayerMetadata;
+use crate::lyrics::api::{LrcLibClient, PublishRequest};
+use crate::lyrics::cache::LyricsCache;
+use crate::lyrics::local::LocalLyrics;
+use crate::lyrics::recorder::SyncRecorder;
+use crate::lyrics::romanize::romanize;
+use crate::lyrics::source::{FoundLyrics, TrackQuery, PINNED_SOURCE};
+use crate::lyrics::{parser, LyricLine, LyricsGap, ParsedLyrics};
+use crate::models::{AlbumMetadata, TrackMetadata};
+use crate::player::clock::PlaybackClock;
+use crate::player::{PlaybackControl, PlayerCommand, PlayerEvent, PlayerMetadata};
+
+// Credits shown in the metadata pane
+const SHOWN_CREDITS: [&str; 5] = ["Producer", "Composer", "Lyricist", "Writer", "Songwriter"];
+
+// Notes of the gap indicator, and how fast the last one pulses
+const GAP_NOTES: usize = 3;
+const PULSE_MS: u64 = 500;
+
+// Redraw often enough that synced lyrics switch lines on time
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+// How long feedback for a key press stays in the title bar
+const FEEDBACK_DURATION: Duration = Duration::from_secs(2);
+// Lyrics offset steps, Shift-ed keys take the bigger one
+const OFFSET_STEP_MS: i64 = 100;
+const OFFSET_BIG_STEP_MS: i64 = 500;
 
 pub struct App {
     pub should_quit: bool,
     pub album_art: Option<PixelatedImage>,
     pub album_art_ratatui: Option<RatatuiImage>,
     pub album_metadata: Option<AlbumMetadata>,
+    pub track_metadata: Option<TrackMetadata>, // ISRC, track number and credits
     pub player_metadata: PlayerMetadata,
     pub progress: f32,
+    pub clock: PlaybackClock,
     pub color_palette: Option<ColorPalette>,
     pub lyrics: Option<ParsedLyrics>,
     pub lyrics_loading: bool,
+    pub lyrics_request: u64, // bumped whenever a running lyrics fetch goes stale
+    pub lyrics_offset_ms: i64, // positive shows lyrics sooner, like [offset:]
+    pub lyrics_track: Option<(String, String)>, // artist and title the offset is saved for
+    pub lyrics_cache: Option<LyricsCache>,
+    pub local_lyrics: LocalLyrics, // where recorded lyrics are saved
+    pub recorder: Option<SyncRecorder>,
+    pub publish_prompt: Option<PublishRequest>, // waiting for the user to confirm
+    pub show_romanization: bool,
+    pub lyrics_view: LyricsView, // scrolling and search in the lyrics pane
+    pub picker: Option<LyricsPicker>,
     pub waiting_for_player: bool,
+    pub feedback: Option<(String, Instant)>,
 }
 
 impl Default for App {
@@ -48,21 +93,25 @@ impl App {
             album_art: None,
             album_art_ratatui: None,
             album_metadata: None,
-            player_metadata: PlayerMetadata {
-                artist: String::new(),
-                title: String::new(),
-                album: None,
-                position: None,
-                length: None,
-                streaming_source: None,
-                art_url: None,
-                status: None,
-            },
+            track_metadata: None,
+            player_metadata: PlayerMetadata::default(),
             progress: 0.0,
+            clock: PlaybackClock::new(),
             color_palette: None,
             lyrics: None,
             lyrics_loading: false,
+            lyrics_request: 0,
+            lyrics_offset_ms: 0,
+            lyrics_track: None,
+            lyrics_cache: None,
+            local_lyrics: LocalLyrics::default(),
+            recorder: None,
+            publish_prompt: None,
+            show_romanization: false,
+            lyrics_view: LyricsView::default(),
+            picker: None,
             waiting_for_player: true,
+            feedback: None,
         }
     }
 
@@ -78,13 +127,42 @@ impl App {
         self.album_art = album_art;
         self.album_art_ratatui = album_art_ratatui;
         self.album_metadata = album_metadata;
+        self.clock.sync(
+            player_metadata.position,
+            player_metadata.status.as_deref() == Some("Playing"),
+            player_metadata.rate,
+        );
         self.player_metadata = player_metadata;
         self.progress = progress;
         self.color_palette = color_palette;
         self.waiting_for_player = false;
     }
 
-    pub fn update_lyrics(&mut self, lyrics: Option<ParsedLyrics>) {
+    /// Playback position, advanced locally since the last player sample
+    pub fn current_position(&self) -> Option<Duration> {
+        let position = self.clock.position()?;
+        Some(match self.player_metadata.length {
+            Some(length) => position.min(length),
+            None => position,
+        })
+    }
+
+    /// Progress percentage matching `current_position`
+    pub fn current_progress(&self) -> f32 {
+        match (self.current_position(), self.player_metadata.length) {
+            (Some(position), Some(length)) if length.as_secs() > 0 => {
+                (position.as_secs_f32() / length.as_secs_f32()) * 100.0
+            }
+            _ => self.progress,
+        }
+    }
+
+    /// Lyrics fetched for `request`, dropped when the track changed or
+    /// lyrics were picked by hand since the fetch started
+    pub fn update_lyrics(&mut self, request: u64, lyrics: Option<ParsedLyrics>) {
+        if request != self.lyrics_request {
+            return;
+        }
         self.lyrics = lyrics;
         self.lyrics_loading = false;
     }
@@ -92,11 +170,330 @@ impl App {
     pub fn set_lyrics_loading(&mut self, loading: bool) {
         self.lyrics_loading = loading;
     }
+
+    /// Switch to the track's saved lyrics offset
+    pub fn set_lyrics_track(&mut self, artist: &str, title: &str) {
+        self.lyrics_offset_ms = self
+            .lyrics_cache
+            .as_ref()
+            .and_then(|cache| cache.get_offset(artist, title))
+            .unwrap_or(0)
+            .clamp(-parser::MAX_OFFSET_MS, parser::MAX_OFFSET_MS);
+        self.lyrics_track = Some((artist.to_string(), title.to_string()));
+        self.lyrics_request += 1;
+        // Stamps only make sense for the track they were recorded on
+        self.recorder = None;
+        self.lyrics_view = LyricsView::default();
+        // A pick is pinned to the track the picker was opened for
+        self.picker = None;
+    }
+
+    /// Move the lyrics by `delta_ms` and remember it for the track
+    pub fn nudge_lyrics_offset(&mut self, delta_ms: i64) {
+        self.set_lyrics_offset(self.lyrics_offset_ms + delta_ms);
+    }
+
+    pub fn set_lyrics_offset(&mut self, offset_ms: i64) {
+        let offset_ms = offset_ms.clamp(-parser::MAX_OFFSET_MS, parser::MAX_OFFSET_MS);
+        self.lyrics_offset_ms = offset_ms;
+
+        let saved = match (&self.lyrics_cache, &self.lyrics_track) {
+            (Some(cache), Some((artist, title))) => cache.set_offset(artist, title, offset_ms),
+            _ => Ok(()),
+        };
+        match saved {
+            Ok(()) => self.show_feedback(format!("Lyrics offset {}", format_offset(offset_ms))),
+            Err(e) => self.show_feedback(format!("Saving lyrics offset failed: {e}")),
+        }
+    }
+
+    /// Start recording line timings for the current lyrics
+    pub fn start_recording(&mut self) {
+        let recorder = match self.lyrics {
+            Some(ref lyrics) => SyncRecorder::new(lyrics),
+            None => return self.show_feedback("No lyrics to sync"),
+        };
+        if recorder.is_empty() {
+            return self.show_feedback("No lyrics to sync");
+        }
+        self.recorder = Some(recorder);
+        self.show_feedback("Recording sync, press Space as each line starts");
+    }
+
+    /// Stamp the next line with the playback position
+    pub fn stamp_line(&mut self) {
+        let Some(position) = self.current_position() else {
+            return;
+        };
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.stamp(position.as_millis() as u64);
+        }
+    }
+
+    pub fn undo_stamp(&mut self) {
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.undo();
+        }
+    }
+
+    pub fn cancel_recording(&mut self) {
+        if self.recorder.take().is_some() {
+            self.show_feedback("Sync recording discarded");
+        }
+    }
+
+    /// Save the recorded timings as a local LRC file and show them
+    pub fn finish_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        if recorder.stamped() == 0 {
+            return self.show_feedback("Sync recording discarded");
+        }
+
+        let query = TrackQuery::from_metadata(&self.player_metadata);
+        let lrc = recorder.to_lrc(&query);
+        match self.local_lyrics.save(&query, &lrc) {
+            Ok(path) => {
+                self.lyrics = Some(ParsedLyrics {
+                    source: Some("local".to_string()),
+                    ..recorder.to_parsed()
+                });
+                // The stamps were taken against the player's own position
+                self.lyrics_offset_ms = 0;
+                if let (Some(cache), Some((artist, title))) =
+                    (&self.lyrics_cache, &self.lyrics_track)
+                {
+                    let _ = cache.set_offset(artist, title, 0);
+                }
+                self.show_feedback(format!("Saved {}", path.display()));
+            }
+            Err(e) => {
+                self.show_feedback(format!("Saving lyrics failed: {e}"));
+                // Keep the stamps so saving can be retried
+                self.recorder = Some(recorder);
+            }
+        }
+    }
+
+    pub fn toggle_romanization(&mut self) {
+        self.show_romanization = !self.show_romanization;
+        self.show_feedback(if self.show_romanization {
+            "Romanization on"
+        } else {
+            "Romanization off"
+        });
+    }
+
+    /// Ask to publish the lyrics synced or corrected here to LRCLIB
+    pub fn request_publish(&mut self) {
+        let Some(ref lyrics) = self.lyrics else {
+            return self.show_feedback("No lyrics to publish");
+        };
+        if lyrics.source.as_deref() != Some("local") && self.lyrics_offset_ms == 0 {
+            return self.show_feedback("Only lyrics synced or corrected here can be published");
+        }
+
+        let query = TrackQuery::from_metadata(&self.player_metadata);
+        match PublishRequest::new(&query, lyrics, self.lyrics_offset_ms) {
+            Ok(request) => self.publish_prompt = Some(request),
+            Err(e) => self.show_feedback(format!("Can't publish: {e}")),
+        }
+    }
+
+    /// Open the candidate picker for the playing track, returns the search
+    /// that fills it
+    pub fn open_picker(&mut self) -> Option<CandidateSearch> {
+        let track = TrackQuery::from_metadata(&self.player_metadata);
+        if track.title.is_empty() {
+            self.show_feedback("Nothing playing to pick lyrics for");
+            return None;
+        }
+        let (picker, search) = LyricsPicker::new(track);
+        self.picker = Some(picker);
+        Some(search)
+    }
+
+    /// Keys while the picker is open, returns a search to run
+    pub fn picker_key(&mut self, key: KeyEvent) -> Option<CandidateSearch> {
+        let picker = self.picker.as_mut()?;
+        if picker.editing {
+            match key.code {
+                KeyCode::Enter => return picker.search(),
+                KeyCode::Esc => picker.editing = false,
+                KeyCode::Backspace => {
+                    picker.query.pop();
+                }
+                KeyCode::Char(c) => picker.query.push(c),
+                _ => {}
+            }
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') => self.picker = None,
+            KeyCode::Down | KeyCode::Char('j') => picker.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => picker.select_previous(),
+            KeyCode::Char('/') => picker.editing = true,
+            KeyCode::Enter => self.pick_candidate(),
+            KeyCode::Char('x') => self.unpin_lyrics(),
+            _ => {}
+        }
+        None
+    }
+
+    /// Show the selected candidate and pin it to the track
+    pub fn pick_candidate(&mut self) {
+        let Some(picker) = self.picker.take() else {
+            return;
+        };
+        let Some(candidate) = picker.selected().cloned() else {
+            self.picker = Some(picker);
+            return;
+        };
+
+        let found = FoundLyrics {
+            source: PINNED_SOURCE.to_string(),
+            response: candidate,
+        };
+        let pinned = match self.lyrics_cache {
+            Some(ref cache) => cache.set_pinned(
+                &picker.track.artist,
+                &picker.track.title,
+                Some(&found.response),
+            ),
+            None => Err(anyhow::anyhow!("no lyrics cache")),
+        };
+        match pinned {
+            Ok(()) => self.show_feedback("Pinned lyrics for this track"),
+            Err(e) => self.show_feedback(format!("Showing lyrics, but pinning failed: {e}")),
+        }
+        // The pick wins over lyrics still being fetched
+        self.lyrics_request += 1;
+        self.update_lyrics(self.lyrics_request, found.parse());
+        self.lyrics_view = LyricsView::default();
+    }
+
+    /// Forget the pinned lyrics, the sources are asked again on the next play
+    pub fn unpin_lyrics(&mut self) {
+        let Some(ref picker) = self.picker else {
+            return;
+        };
+        let unpinned = self
+            .lyrics_cache
+            .as_ref()
+            .map(|cache| cache.set_pinned(&picker.track.artist, &picker.track.title, None));
+        match unpinned {
+            Some(Ok(())) => {
+                self.show_feedback("Unpinned, lyrics are picked automatically next time")
+            }
+            Some(Err(e)) => self.show_feedback(format!("Unpinning failed: {e}")),
+            None => {}
+        }
+    }
+
+    /// Go back to following the current line
+    pub fn follow_lyrics(&mut self) {
+        if !self.lyrics_view.is_following() {
+            self.lyrics_view.follow();
+            self.show_feedback("Following lyrics");
+        }
+    }
+
+    /// Type into the lyrics search, Up and Down move between matches
+    pub fn search_key(&mut self, key: KeyEvent) {
+        let lyrics = self.lyrics.as_ref();
+        let view = &mut self.lyrics_view;
+        match key.code {
+            KeyCode::Enter => view.finish_search(),
+            KeyCode::Esc => view.cancel_search(),
+            KeyCode::Down => view.jump_to_match(lyrics, true),
+            KeyCode::Up => view.jump_to_match(lyrics, false),
+            KeyCode::Backspace => view.edit_query(lyrics, |query| {
+                query.pop();
+            }),
+            KeyCode::Char(c) => view.edit_query(lyrics, |query| query.push(c)),
+            _ => {}
+        }
+    }
+
+    /// Seek to the synced lyric drawn at a terminal cell, the lyrics offset
+    /// is undone so the line starts as it is shown
+    pub fn seek_to_lyric_at(&mut self, column: u16, row: u16) -> Option<PlayerCommand> {
+        if self.recorder.is_some() {
+            return None;
+        }
+        let timestamp = self.lyrics_view.timestamp_at(column, row)?;
+        let position = timestamp.saturating_add_signed(-self.lyrics_offset_ms);
+        self.lyrics_view.follow();
+        Some(PlayerCommand::Control(PlaybackControl::SeekTo(
+            Duration::from_millis(position),
+        )))
+    }
+
+    /// Position the lyrics are matched against, with the offset applied
+    pub fn lyrics_position(&self) -> Option<Duration> {
+        let position = self.current_position()?.as_millis() as u64;
+        Some(Duration::from_millis(
+            position.saturating_add_signed(self.lyrics_offset_ms),
+        ))
+    }
+
+    /// Flash a short message in the title bar
+    pub fn show_feedback(&mut self, message: impl Into<String>) {
+        self.feedback = Some((message.into(), Instant::now()));
+    }
+
+    /// The feedback message, while it is still fresh
+    pub fn current_feedback(&self) -> Option<&str> {
+        self.feedback
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < FEEDBACK_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+}
+
+/// Map a key press to a command for the background thread
+pub fn command_for_key(key: KeyEvent) -> Option<PlayerCommand> {
+    // Shift makes seeking take bigger steps
+    let seek_step = if key.modifiers.contains(KeyModifiers::SHIFT) {
+        30
+    } else {
+        5
+    };
+
+    let control = match key.code {
+        KeyCode::Tab => return Some(PlayerCommand::CyclePlayer),
+        KeyCode::Char(' ') => PlaybackControl::PlayPause,
+        KeyCode::Char('n') => PlaybackControl::Next,
+        KeyCode::Char('p') => PlaybackControl::Previous,
+        KeyCode::Right => PlaybackControl::Seek(seek_step),
+        KeyCode::Left => PlaybackControl::Seek(-seek_step),
+        KeyCode::Char('+') | KeyCode::Char('=') => PlaybackControl::Volume(5),
+        KeyCode::Char('-') => PlaybackControl::Volume(-5),
+        KeyCode::Char('s') => PlaybackControl::ToggleShuffle,
+        KeyCode::Char('r') => PlaybackControl::ToggleLoop,
+        _ => return None,
+    };
+
+    Some(PlayerCommand::Control(control))
+}
+
+/// How far a key moves the lyrics, in milliseconds
+pub fn lyrics_offset_for_key(key: KeyEvent) -> Option<i64> {
+    match key.code {
+        KeyCode::Char('.') => Some(OFFSET_STEP_MS),
+        KeyCode::Char(',') => Some(-OFFSET_STEP_MS),
+        KeyCode::Char('>') => Some(OFFSET_BIG_STEP_MS),
+        KeyCode::Char('<') => Some(-OFFSET_BIG_STEP_MS),
+        _ => None,
+    }
 }
 
 pub fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: Arc<Mutex<App>>,
+    commands: Sender<PlayerEvent>,
 ) -> Result<()> {
     // Clear the terminal once at the start
     terminal.clear()?;
@@ -104,25 +501,165 @@ pub fn run_app<B: ratatui::backend::Backend>(
     loop {
         // Draw UI with current state
         terminal.draw(|f| {
-            let app = app.lock().unwrap();
-            ui(f, &app)
+            let mut app = app.lock().unwrap();
+            ui(f, &mut app)
         })?;
 
         // Check for input events
-        if event::poll(Duration::from_millis(250))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        return Ok(());
-                    }
-                    _ => {}
+        if event::poll(FRAME_INTERVAL)? {
+            let keep_running = match event::read()? {
+                Event::Key(key) => handle_key(&app, &commands, key),
+                Event::Mouse(mouse) => {
+                    handle_mouse(&app, &commands, mouse);
+                    true
                 }
+                _ => true,
+            };
+            if !keep_running {
+                return Ok(());
             }
         }
     }
 }
 
-fn ui(f: &mut Frame, app: &App) {
+/// Act on a key press, returns false when the app should exit
+fn handle_key(app: &Arc<Mutex<App>>, commands: &Sender<PlayerEvent>, key: KeyEvent) -> bool {
+    // The publish prompt takes the next key: y confirms, anything else cancels
+    let prompt = app.lock().unwrap().publish_prompt.take();
+    if let Some(request) = prompt {
+        if key.code == KeyCode::Char('y') {
+            publish_lyrics(Arc::clone(app), request);
+        } else {
+            app.lock().unwrap().show_feedback("Publishing cancelled");
+        }
+        return true;
+    }
+
+    // The candidate picker takes every key while it is open
+    if app.lock().unwrap().picker.is_some() {
+        let search = app.lock().unwrap().picker_key(key);
+        if let Some(search) = search {
+            search_candidates(Arc::clone(app), search);
+        }
+        return true;
+    }
+
+    // Keys go to the search query while it is being typed
+    if app.lock().unwrap().lyrics_view.is_searching() {
+        app.lock().unwrap().search_key(key);
+        return true;
+    }
+
+    match key.code {
+        KeyCode::Char('q') => return false,
+        KeyCode::Esc => {
+            let mut app = app.lock().unwrap();
+            if app.recorder.is_some() {
+                app.cancel_recording();
+            } else if app.lyrics_view.search.is_some() {
+                app.lyrics_view.cancel_search();
+            } else {
+                return false;
+            }
+        }
+        KeyCode::Char('R') => {
+            let mut app = app.lock().unwrap();
+            if app.recorder.is_some() {
+                app.finish_recording();
+            } else {
+                app.start_recording();
+            }
+        }
+        // While recording, Space stamps lines instead of pausing
+        KeyCode::Char(' ') if app.lock().unwrap().recorder.is_some() => {
+            app.lock().unwrap().stamp_line();
+        }
+        KeyCode::Backspace => app.lock().unwrap().undo_stamp(),
+        KeyCode::Char('P') => app.lock().unwrap().request_publish(),
+        KeyCode::Char('t') => app.lock().unwrap().toggle_romanization(),
+        KeyCode::Char('0') => app.lock().unwrap().set_lyrics_offset(0),
+        KeyCode::Char('j') | KeyCode::Down => app.lock().unwrap().lyrics_view.scroll_by(1),
+        KeyCode::Char('k') | KeyCode::Up => app.lock().unwrap().lyrics_view.scroll_by(-1),
+        KeyCode::PageDown => app.lock().unwrap().lyrics_view.page(true),
+        KeyCode::PageUp => app.lock().unwrap().lyrics_view.page(false),
+        KeyCode::Char('g') | KeyCode::Home => app.lock().unwrap().lyrics_view.scroll_to_top(),
+        KeyCode::Char('G') | KeyCode::End => app.lock().unwrap().lyrics_view.scroll_to_bottom(),
+        KeyCode::Char('f') => app.lock().unwrap().follow_lyrics(),
+        KeyCode::Char('/') => app.lock().unwrap().lyrics_view.start_search(),
+        KeyCode::Char('L') => {
+            let search = app.lock().unwrap().open_picker();
+            if let Some(search) = search {
+                search_candidates(Arc::clone(app), search);
+            }
+        }
+        _ => {
+            if let Some(delta_ms) = lyrics_offset_for_key(key) {
+                app.lock().unwrap().nudge_lyrics_offset(delta_ms);
+            } else if let Some(command) = command_for_key(key) {
+                // The background thread only stops when the app exits
+                let _ = commands.send(PlayerEvent::Command(command));
+            }
+        }
+    }
+    true
+}
+
+/// The wheel scrolls the lyrics, a click on a synced line seeks to it
+fn handle_mouse(app: &Arc<Mutex<App>>, commands: &Sender<PlayerEvent>, mouse: MouseEvent) {
+    let mut app = app.lock().unwrap();
+    match mouse.kind {
+        MouseEventKind::ScrollDown => app.lyrics_view.scroll_by(WHEEL_STEP),
+        MouseEventKind::ScrollUp => app.lyrics_view.scroll_by(-WHEEL_STEP),
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(command) = app.seek_to_lyric_at(mouse.column, mouse.row) {
+                let _ = commands.send(PlayerEvent::Command(command));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Search LRCLIB for the picker on a thread of its own, the results are
+/// dropped if the picker was closed or searched again meanwhile
+fn search_candidates(app: Arc<Mutex<App>>, search: CandidateSearch) {
+    let Some(track) = app.lock().unwrap().picker.as_ref().map(|p| p.track.clone()) else {
+        return;
+    };
+
+    thread::spawn(move || {
+        let results = tokio::runtime::Runtime::new()
+            .map_err(anyhow::Error::from)
+            .and_then(|runtime| runtime.block_on(search.run(&LrcLibClient::new(), &track)));
+        if let Ok(mut app) = app.lock() {
+            if let Some(ref mut picker) = app.picker {
+                picker.set_results(&search, results);
+            }
+        }
+    });
+}
+
+/// Solving the challenge and uploading take a while, so publishing runs on
+/// a thread of its own and reports back through the title bar
+fn publish_lyrics(app: Arc<Mutex<App>>, request: PublishRequest) {
+    app.lock()
+        .unwrap()
+        .show_feedback("Publishing to LRCLIB, this can take a minute...");
+
+    thread::spawn(move || {
+        let published = tokio::runtime::Runtime::new()
+            .map_err(anyhow::Error::from)
+            .and_then(|runtime| runtime.block_on(LrcLibClient::new().publish(&request)));
+        let feedback = match published {
+            Ok(()) => format!("Published {} to LRCLIB", request.track_name),
+            Err(e) => format!("Publishing failed: {e}"),
+        };
+        if let Ok(mut app) = app.lock() {
+            app.show_feedback(feedback);
+        }
+    });
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
     // Clear the entire area first
     f.render_widget(Clear, f.area());
 
@@ -200,6 +737,22 @@ fn ui(f: &mut Frame, app: &App) {
         ));
     }
 
+    // Which player this is, Tab switches to the next one
+    if let Some(ref player) = app.player_metadata.player {
+        title_spans.push(Span::styled(
+            format!(" [{player}]"),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    // Acknowledge the last key press
+    if let Some(feedback) = app.current_feedback() {
+        title_spans.push(Span::styled(
+            format!("  {feedback}"),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
     let title = Paragraph::new(Line::from(title_spans))
         .style(Style::default().fg(Color::White))
         .block(Block::default().borders(Borders::BOTTOM));
@@ -289,30 +842,32 @@ fn ui(f: &mut Frame, app: &App) {
     }
 
     // Progress bar
-    let progress_label = if let (Some(position), Some(length)) =
-        (app.player_metadata.position, app.player_metadata.length)
-    {
-        let time_label = format!(
-            "{} / {}",
-            format_duration(position.as_millis() as i64),
-            format_duration(length.as_millis() as i64)
-        );
+    let current_position = app.current_position();
+    let current_progress = app.current_progress();
+
+    let progress_label =
+        if let (Some(position), Some(length)) = (current_position, app.player_metadata.length) {
+            let time_label = format!(
+                "{} / {}",
+                format_duration(position.as_millis() as i64),
+                format_duration(length.as_millis() as i64)
+            );
 
-        // Add pause indicator if paused
-        if app.player_metadata.status.as_deref() == Some("Paused") {
-            format!("⏸  {time_label}")
+            // Add pause indicator if paused
+            if app.player_metadata.status.as_deref() == Some("Paused") {
+                format!("⏸  {time_label}")
+            } else {
+                time_label
+            }
         } else {
-            time_label
-        }
-    } else {
-        String::from("00:00 / 00:00")
-    };
+            String::from("00:00 / 00:00")
+        };
 
-    let progress_percent = app.progress.clamp(0.0, 100.0) as u16;
+    let progress_percent = current_progress.clamp(0.0, 100.0) as u16;
 
     // Use interpolated color from extracted palette
     let progress_color = if let Some(ref palette) = app.color_palette {
-        interpolate_color(&palette.progress_colors, app.progress)
+        interpolate_color(&palette.progress_colors, current_progress)
     } else {
         Color::Cyan
     };
@@ -340,10 +895,15 @@ fn ui(f: &mut Frame, app: &App) {
         .style(Style::default().fg(Color::Yellow));
 
     let metadata_text = if let Some(ref album) = app.album_metadata {
-        format_album_metadata(album, &app.player_metadata, app.color_palette.as_ref())
+        format_album_metadata(
+            album,
+            &app.player_metadata,
+            app.track_metadata.as_ref(),
+            app.color_palette.as_ref(),
+        )
     } else {
         // Show playerctl metadata when Tidal API fails
-        format_playerctl_metadata(&app.player_metadata)
+        format_playerctl_metadata(&app.player_metadata, app.track_metadata.as_ref())
     };
 
     let metadata_widget = Paragraph::new(metadata_text)
@@ -364,50 +924,101 @@ fn ui(f: &mut Frame, app: &App) {
         .borders(Borders::NONE)
         .style(Style::default().fg(Color::Green));
 
-    // Prepare lyrics content
-    let lyrics_content = if let Some(ref lyrics) = app.lyrics {
-        // Add empty line at the top for spacing
-        let mut lines: Vec<Line> = vec![Line::from("")];
+    // Lyrics follow the playback position shifted by the manual offset
+    let lyrics_position = app.lyrics_position();
 
-        // Calculate current line based on position
-        let current_line_idx = if lyrics.is_synced {
-            if let Some(position) = app.player_metadata.position {
-                parser::find_current_line(lyrics, position.as_millis() as u64)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+    // The line being sung, or the next one to stamp while recording
+    let current_line_idx = match (&app.recorder, &app.lyrics) {
+        (Some(recorder), _) => recorder.next_line(),
+        (None, Some(lyrics)) => lyrics_position
+            .and_then(|position| parser::find_current_line(lyrics, position.as_millis() as u64)),
+        (None, None) => None,
+    };
+    // Intro or break with nobody singing, counted down to the next line
+    let gap = match (&app.recorder, &app.lyrics, lyrics_position) {
+        (None, Some(lyrics), Some(position)) => {
+            parser::find_gap(lyrics, position.as_millis() as u64)
+        }
+        _ => None,
+    };
+
+    // Prepare lyrics content, noting the timestamp of the lyric on each row
+    // and where each lyric starts so scrolling and clicks can find them
+    let query = app.lyrics_view.query().map(str::to_string);
+    let mut rows: Vec<Option<u64>> = Vec::new();
+    let mut line_rows: Vec<usize> = Vec::new();
+    let lyrics_content = if let Some(ref recorder) = app.recorder {
+        let lines = recorder_lines(recorder, current_line_style(app.color_palette.as_ref()));
+        rows = vec![None; lines.len()];
+        line_rows = (1..lines.len()).collect();
+        lines
+    } else if let Some(lyrics) = app.lyrics.as_ref().filter(|l| l.instrumental) {
+        let lines = vec![
+            lyrics_header(lyrics, 0),
+            Line::from(""),
+            Line::from(instrumental_spans(
+                lyrics_position,
+                app.color_palette.as_ref(),
+            )),
+        ];
+        rows = vec![None; lines.len()];
+        lines
+    } else if let Some(ref lyrics) = app.lyrics {
+        // The top line says where the lyrics came from and how they are shifted
+        let mut header = lyrics_header(lyrics, app.lyrics_offset_ms);
+        header.spans.extend(viewer_spans(&app.lyrics_view));
+        let mut lines: Vec<Line> = vec![header];
+        rows.push(None);
+
+        // Before the first line the countdown gets a row of its own
+        if let (Some(gap), None, Some(position)) = (gap, current_line_idx, lyrics_position) {
+            lines.push(Line::from(gap_spans(
+                &gap,
+                position,
+                app.color_palette.as_ref(),
+            )));
+            rows.push(None);
+        }
 
         // Format lyrics with highlighting
         for (idx, line) in lyrics.lines.iter().enumerate() {
             let is_current = current_line_idx == Some(idx);
             let style = if is_current {
-                // Use color from palette if available
-                if let Some(ref palette) = app.color_palette {
-                    if let Some(&(r, g, b)) = palette.info_colors.first() {
-                        Style::default()
-                            .fg(Color::Rgb(r, g, b))
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    }
-                } else {
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
-                }
+                current_line_style(app.color_palette.as_ref())
             } else {
                 Style::default().fg(Color::White)
             };
+            // Only synced lines can be clicked to seek
+            let timestamp = line.timestamp_ms.filter(|_| lyrics.is_synced);
+
+            let mut spans = vec![Span::raw("  ")]; // Add left padding to lyrics
+            match (lyrics_position, &query, gap) {
+                // A break marked by this blank line
+                (Some(position), _, Some(gap)) if is_current => {
+                    spans = gap_spans(&gap, position, app.color_palette.as_ref())
+                }
+                // Karaoke: colour the words sung so far
+                (Some(position), None, _) if is_current && !line.words.is_empty() => spans.extend(
+                    karaoke_spans(line, position, style, app.color_palette.as_ref()),
+                ),
+                _ => spans.extend(search_spans(&line.text, query.as_deref(), style)),
+            }
+            line_rows.push(lines.len());
+            lines.push(Line::from(spans));
+            rows.push(timestamp);
 
-            lines.push(Line::from(vec![
-                Span::raw("  "), // Add left padding to lyrics
-                Span::styled(line.text.clone(), style),
-            ]));
+            // Romanization and translation, dimmed under the original
+            let secondary_style = if is_current {
+                Style::default().fg(Color::Gray)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            for text in secondary_lines(line, app.show_romanization) {
+                let mut spans = vec![Span::raw("  ")];
+                spans.extend(search_spans(&text, query.as_deref(), secondary_style));
+                lines.push(Line::from(spans));
+                rows.push(timestamp);
+            }
         }
         lines
     } else if app.lyrics_loading {
@@ -428,40 +1039,20 @@ fn ui(f: &mut Frame, app: &App) {
         ]
     };
 
-    // Calculate scroll offset to center current line
+    // Keep the current line in view while following
     let visible_height = main_chunks[2].height.saturating_sub(2) as usize; // subtract top padding
-    let scroll_offset = if let Some(ref lyrics) = app.lyrics {
-        if lyrics.is_synced {
-            if let Some(current_idx) = parser::find_current_line(
-                lyrics,
-                app.player_metadata
-                    .position
-                    .map(|p| p.as_millis() as u64)
-                    .unwrap_or(0),
-            ) {
-                // Account for the empty line at the top (current_idx + 1)
-                let adjusted_idx = current_idx + 1;
-
-                // Only scroll when current line is 3/4 down the visible area
-                // This keeps lyrics more stable and only scrolls when necessary
-                let three_quarters_down = (visible_height * 3) / 4;
-                if adjusted_idx > three_quarters_down {
-                    // Ensure we never scroll past 0 (which would show content above lyrics)
-                    adjusted_idx
-                        .saturating_sub(three_quarters_down)
-                        .min(lyrics.lines.len().saturating_sub(visible_height))
-                } else {
-                    0
-                }
-            } else {
-                0
-            }
-        } else {
-            0
+    let follow_top = match current_line_idx.and_then(|idx| line_rows.get(idx)) {
+        Some(&current_row) => {
+            // Only scroll when current line is 3/4 down the visible area
+            // This keeps lyrics more stable and only scrolls when necessary
+            let three_quarters_down = (visible_height * 3) / 4;
+            current_row.saturating_sub(three_quarters_down)
         }
-    } else {
-        0
+        None => 0,
     };
+    let scroll_offset = app
+        .lyrics_view
+        .layout(main_chunks[2], rows, line_rows, follow_top);
 
     let lyrics_widget = Paragraph::new(lyrics_content)
         .block(lyrics_block)
@@ -469,11 +1060,442 @@ fn ui(f: &mut Frame, app: &App) {
         .scroll((scroll_offset as u16, 0));
 
     f.render_widget(lyrics_widget, main_chunks[2]);
+
+    if let Some(ref picker) = app.picker {
+        render_picker(f, picker);
+    }
+    if let Some(ref request) = app.publish_prompt {
+        render_publish_prompt(f, request);
+    }
+}
+
+/// Popup listing the LRCLIB candidates on the left and a preview of the
+/// selected one on the right
+fn render_picker(f: &mut Frame, picker: &LyricsPicker) {
+    let area = f.area();
+    let width = (area.width * 4 / 5).max(40).min(area.width);
+    let height = (area.height * 3 / 4).max(12).min(area.height);
+    let popup = ratatui::layout::Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(format!(
+            " Lyrics for {} - {} ",
+            picker.track.artist, picker.track.title
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Search field
+            Constraint::Min(0),    // Candidates and preview
+            Constraint::Length(1), // Keys
+        ])
+        .split(inner);
+
+    let field_style = if picker.editing {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+    let cursor = if picker.editing { "▏" } else { "" };
+    let field = Paragraph::new(Line::from(vec![
+        Span::styled(" Search: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(format!("{}{cursor}", picker.query), field_style),
+    ]));
+    f.render_widget(field, rows[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[1]);
+
+    let list_lines = picker_lines(picker);
+    // Keep the selected candidate in view
+    let list_height = columns[0].height as usize;
+    let list_scroll = (picker.selected + 1).saturating_sub(list_height);
+    let list = Paragraph::new(list_lines).scroll((list_scroll as u16, 0));
+    f.render_widget(list, columns[0]);
+
+    let preview_lines: Vec<Line> = picker
+        .selected()
+        .map(preview)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|text| Line::from(Span::styled(text, Style::default().fg(Color::Gray))))
+        .collect();
+    let preview_widget = Paragraph::new(preview_lines)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::LEFT)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .padding(ratatui::widgets::Padding::horizontal(1)),
+        );
+    f.render_widget(preview_widget, columns[1]);
+
+    let keys = Paragraph::new(Span::styled(
+        " ↑↓ choose · Enter pin · / search · x unpin · Esc close",
+        Style::default().fg(Color::DarkGray),
+    ));
+    f.render_widget(keys, rows[2]);
+}
+
+/// One line per candidate: whether it is synced, how far its duration is
+/// off, artist and title, album
+fn picker_lines(picker: &LyricsPicker) -> Vec<Line<'static>> {
+    if picker.is_searching() && picker.results.is_empty() {
+        return vec![Line::from(Span::styled(
+            " Searching LRCLIB...",
+            Style::default().fg(Color::DarkGray),
+        ))];
+    }
+    if let Some(ref error) = picker.error {
+        return vec![Line::from(Span::styled(
+            format!(" Search failed: {error}"),
+            Style::default().fg(Color::Red),
+        ))];
+    }
+    if picker.results.is_empty() {
+        return vec![Line::from(Span::styled(
+            " No results, press / to search for something else",
+            Style::default().fg(Color::DarkGray),
+        ))];
+    }
+
+    picker
+        .results
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let is_selected = index == picker.selected;
+            let title_style = if is_selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let dim = Style::default().fg(Color::DarkGray);
+            let kind = lyrics_kind(candidate);
+            let kind_style = match kind {
+                "synced" => Style::default().fg(Color::Green),
+                "plain" => Style::default().fg(Color::Yellow),
+                _ => dim,
+            };
+            let delta = picker
+                .duration_delta(candidate)
+                .map(format_delta)
+                .unwrap_or_else(|| "?".to_string());
+
+            // Status first, long names are cut off at the preview
+            let mut spans = vec![
+                Span::styled(format!(" {kind:<12}"), kind_style),
+                Span::styled(format!("{delta:>5}  "), dim),
+                Span::styled(
+                    format!("{} - {}", candidate.artist_name, candidate.track_name),
+                    title_style,
+                ),
+            ];
+            if let Some(ref album) = candidate.album_name {
+                spans.push(Span::styled(format!(" · {album}"), dim));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Centered box asking to confirm what gets published
+fn render_publish_prompt(f: &mut Frame, request: &PublishRequest) {
+    let lines = publish_prompt_lines(request);
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 6;
+    let height = lines.len() as u16 + 2;
+    let area = f.area();
+    let popup = ratatui::layout::Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height: height.min(area.height),
+    };
+
+    let prompt = Paragraph::new(lines)
+        .alignment(ratatui::layout::Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+    f.render_widget(Clear, popup);
+    f.render_widget(prompt, popup);
+}
+
+fn publish_prompt_lines(request: &PublishRequest) -> Vec<Line<'static>> {
+    vec![
+        Line::from(Span::styled(
+            "Publish synced lyrics to LRCLIB?",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(format!("{} - {}", request.artist_name, request.track_name)),
+        Line::from(format!(
+            "{} · {} · {} lines",
+            request.album_name,
+            format_duration(request.duration as i64 * 1000),
+            request.synced_lyrics.lines().count()
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y to publish, any other key cancels",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ]
+}
+
+/// Dim line naming the lyrics source that answered, with the manual offset
+fn lyrics_header(lyrics: &ParsedLyrics, offset_ms: i64) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ")];
+    if let Some(ref source) = lyrics.source {
+        let label = match source.as_str() {
+            "local" => "Local file",
+            "embedded" => "Embedded tags",
+            "lrclib" => "LRCLIB",
+            PINNED_SOURCE => "Pinned from LRCLIB",
+            other => other,
+        };
+        spans.push(Span::styled(
+            label.to_string(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if offset_ms != 0 && lyrics.is_synced {
+        let separator = if spans.len() > 1 { "  " } else { "" };
+        spans.push(Span::styled(
+            format!("{separator}offset {}", format_offset(offset_ms)),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if spans.len() == 1 {
+        return Line::from("");
+    }
+    Line::from(spans)
+}
+
+/// Text shown dimmed under a lyric: its romanization when turned on,
+/// then its translation
+fn secondary_lines(line: &LyricLine, show_romanization: bool) -> Vec<String> {
+    let romanized = show_romanization.then(|| romanize(&line.text)).flatten();
+    romanized
+        .into_iter()
+        .chain(line.secondary.clone())
+        .collect()
+}
+
+/// Search input while typing, and a hint while scrolled away from the
+/// current line
+fn viewer_spans(view: &LyricsView) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if let Some(ref search) = view.search {
+        let cursor = if search.editing { "▏" } else { "" };
+        spans.push(Span::styled(
+            format!("  /{}{cursor}", search.query),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    if !view.is_following() {
+        spans.push(Span::styled(
+            "  f to follow",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    spans
+}
+
+/// `text` with the matches of the search query highlighted
+fn search_spans(text: &str, query: Option<&str>, style: Style) -> Vec<Span<'static>> {
+    let ranges = query
+        .map(|query| match_ranges(text, query))
+        .unwrap_or_default();
+    let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+
+    let mut spans = Vec::new();
+    let mut last_end = 0;
+    for (start, end) in ranges {
+        if start > last_end {
+            spans.push(Span::styled(text[last_end..start].to_string(), style));
+        }
+        spans.push(Span::styled(text[start..end].to_string(), highlight));
+        last_end = end;
+    }
+    if last_end < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[last_end..].to_string(), style));
+    }
+    spans
+}
+
+/// Bold palette colour for the line being sung
+fn current_line_style(palette: Option<&ColorPalette>) -> Style {
+    let color = palette
+        .and_then(|palette| palette.info_colors.first())
+        .map(|&(r, g, b)| Color::Rgb(r, g, b))
+        .unwrap_or(Color::Yellow);
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+/// The recorder's lines: stamped ones with their time, the next to stamp
+/// highlighted and the rest dimmed
+fn recorder_lines(recorder: &SyncRecorder, next_style: Style) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(vec![
+        Span::raw("  "),
+        Span::styled(
+            format!("● REC {}/{}", recorder.stamped(), recorder.len()),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            "  Space stamp · Backspace undo · R save · Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ])];
+
+    let next_line = recorder.next_line();
+    for (idx, (timestamp, text)) in recorder.lines().enumerate() {
+        let line = match timestamp {
+            Some(timestamp) => vec![
+                Span::styled(
+                    format!("  {} ", format_duration(timestamp as i64)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(text.to_string(), Style::default().fg(Color::White)),
+            ],
+            None if next_line == Some(idx) => vec![
+                Span::styled("  ▶ ", next_style),
+                Span::styled(text.to_string(), next_style),
+            ],
+            None => vec![
+                Span::raw("    "),
+                Span::styled(text.to_string(), Style::default().fg(Color::DarkGray)),
+            ],
+        };
+        lines.push(Line::from(line));
+    }
+    lines
+}
+
+/// Signed offset like "+300ms" or "-1.2s"
+fn format_offset(offset_ms: i64) -> String {
+    if offset_ms.abs() < 1000 {
+        format!("{offset_ms:+}ms")
+    } else {
+        format!("{:+.1}s", offset_ms as f64 / 1000.0)
+    }
+}
+
+/// "♪ ♪ ♪" that go out one by one as the next line gets closer, the last
+/// one pulsing, then the seconds left
+fn gap_spans(
+    gap: &LyricsGap,
+    position: Duration,
+    palette: Option<&ColorPalette>,
+) -> Vec<Span<'static>> {
+    let position_ms = position.as_millis() as u64;
+    let remaining_ms = gap.remaining_ms(position_ms);
+    let length_ms = gap.end_ms.saturating_sub(gap.start_ms).max(1);
+    let lit = (remaining_ms * GAP_NOTES as u64)
+        .div_ceil(length_ms)
+        .clamp(1, GAP_NOTES as u64) as usize;
+
+    let mut spans = vec![Span::raw("  ")];
+    spans.extend(note_spans(lit, position_ms, palette));
+    spans.push(Span::styled(
+        format!("   {}s", remaining_ms.div_ceil(1000)),
+        Style::default().fg(Color::DarkGray),
+    ));
+    spans
+}
+
+/// Notes walking across for a track without vocals
+fn instrumental_spans(
+    position: Option<Duration>,
+    palette: Option<&ColorPalette>,
+) -> Vec<Span<'static>> {
+    let position_ms = position.map_or(0, |position| position.as_millis() as u64);
+    let lit = (position_ms / PULSE_MS) as usize % GAP_NOTES + 1;
+
+    let mut spans = vec![Span::raw("  ")];
+    spans.extend(note_spans(lit, position_ms, palette));
+    spans.push(Span::styled(
+        "   Instrumental",
+        Style::default().fg(Color::DarkGray),
+    ));
+    spans
+}
+
+/// `lit` notes in the palette colour, the rest dimmed. The last lit note
+/// blinks with the frame timing.
+fn note_spans(lit: usize, position_ms: u64, palette: Option<&ColorPalette>) -> Vec<Span<'static>> {
+    let lit_style = current_line_style(palette);
+    let pulse = (position_ms / PULSE_MS).is_multiple_of(2);
+    (0..GAP_NOTES)
+        .map(|note| {
+            let style = if note + 1 < lit || (note + 1 == lit && pulse) {
+                lit_style
+            } else if note + 1 == lit {
+                lit_style.remove_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let separator = if note + 1 < GAP_NOTES { " " } else { "" };
+            Span::styled(format!("♪{separator}"), style)
+        })
+        .collect()
+}
+
+/// Words of the current line, those already sung in the palette's progress
+/// colours and the rest in plain bold
+fn karaoke_spans(
+    line: &LyricLine,
+    position: Duration,
+    sung_style: Style,
+    palette: Option<&ColorPalette>,
+) -> Vec<Span<'static>> {
+    let sung = line.sung_words(position.as_millis() as u64);
+    let progress_colors = palette.map(|p| p.progress_colors.as_slice()).unwrap_or(&[]);
+
+    line.words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            let style = if index >= sung {
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else if progress_colors.is_empty() {
+                sung_style
+            } else {
+                // Sweep through the gradient along the line
+                let (r, g, b) = progress_colors[index * progress_colors.len() / line.words.len()];
+                Style::default()
+                    .fg(Color::Rgb(r, g, b))
+                    .add_modifier(Modifier::BOLD)
+            };
+            Span::styled(word.text.clone(), style)
+        })
+        .collect()
 }
 
 fn format_album_metadata(
     album: &AlbumMetadata,
     player: &PlayerMetadata,
+    track: Option<&TrackMetadata>,
     color_palette: Option<&ColorPalette>,
 ) -> Vec<Line<'static>> {
     let mut lines = vec![];
@@ -538,6 +1560,21 @@ fn format_album_metadata(
         ),
     ]));
 
+    // Track number, ISRC and explicit flag from the track lookup
+    let track_details = track
+        .map(|track| track_rows(track, album.number_of_tracks))
+        .unwrap_or_default();
+    for (label, value) in track_details {
+        lines.push(Line::from(vec![
+            Span::raw(padding),
+            Span::styled(
+                format!("{label:<label_width$}"),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(value, Style::default().fg(get_color(2, Color::White))),
+        ]));
+    }
+
     // Release Date
     if let Some(date) = &album.release_date {
         lines.push(Line::from(vec![
@@ -612,6 +1649,18 @@ fn format_album_metadata(
         ]));
     }
 
+    // Producer and writer credits
+    for (role, names) in track.map(credit_rows).unwrap_or_default() {
+        lines.push(Line::from(vec![
+            Span::raw(padding),
+            Span::styled(
+                format!("{role:<label_width$}"),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(names, Style::default().fg(get_color(3, Color::White))),
+        ]));
+    }
+
     // Copyright (use muted color)
     if let Some(copyright) = &album.copyright {
         lines.push(Line::from(""));
@@ -632,6 +1681,12 @@ fn format_duration(ms: i64) -> String {
     format!("{minutes:02}:{seconds:02}")
 }
 
+/// xesam:userRating (0.0 to 1.0) as five stars
+fn format_rating(rating: f64) -> String {
+    let stars = (rating.clamp(0.0, 1.0) * 5.0).round() as usize;
+    format!("{}{}", "★".repeat(stars), "☆".repeat(5 - stars))
+}
+
 fn interpolate_color(colors: &[(u8, u8, u8)], progress: f32) -> Color {
     if colors.is_empty() {
         return Color::Cyan;
@@ -662,7 +1717,42 @@ fn interpolate_color(colors: &[(u8, u8, u8)], progress: f32) -> Color {
     Color::Rgb(r, g, b)
 }
 
-fn format_playerctl_metadata(player: &PlayerMetadata) -> Vec<Line<'static>> {
+/// Track number, with the disc and album length, then ISRC and the
+/// explicit flag
+fn track_rows(track: &TrackMetadata, album_tracks: Option<u32>) -> Vec<(String, String)> {
+    let mut rows = Vec::new();
+    if let Some(number) = track.track_number {
+        let mut position = match album_tracks {
+            Some(total) => format!("{number} of {total}"),
+            None => number.to_string(),
+        };
+        if let Some(disc) = track.volume_number.filter(|disc| *disc > 1) {
+            position.push_str(&format!(" (disc {disc})"));
+        }
+        rows.push(("Track".to_string(), position));
+    }
+    if let Some(ref isrc) = track.isrc {
+        rows.push(("ISRC".to_string(), isrc.clone()));
+    }
+    if track.explicit {
+        rows.push(("Explicit".to_string(), "Yes".to_string()));
+    }
+    rows
+}
+
+/// Producer and writer credits, one row per role
+fn credit_rows(track: &TrackMetadata) -> Vec<(String, String)> {
+    track
+        .credited(&SHOWN_CREDITS)
+        .into_iter()
+        .map(|credit| (credit.role.clone(), credit.names.join(", ")))
+        .collect()
+}
+
+fn format_playerctl_metadata(
+    player: &PlayerMetadata,
+    track: Option<&TrackMetadata>,
+) -> Vec<Line<'static>> {
     let mut lines = vec![];
 
     // Add empty line for spacing since we removed the title
@@ -698,6 +1788,30 @@ fn format_playerctl_metadata(player: &PlayerMetadata) -> Vec<Line<'static>> {
         ]));
     }
 
+    // Album artist, when it adds something
+    if let Some(ref album_artist) = player.album_artist {
+        if *album_artist != player.artist {
+            lines.push(Line::from(vec![
+                Span::raw(padding),
+                Span::styled("Album Artist: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(album_artist.clone(), Style::default().fg(Color::White)),
+            ]));
+        }
+    }
+
+    // Track number, with the disc for multi-disc albums
+    if let Some(track_number) = player.track_number {
+        let track = match player.disc_number {
+            Some(disc) if disc > 1 => format!("{track_number} (disc {disc})"),
+            _ => track_number.to_string(),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(padding),
+            Span::styled("Track: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(track, Style::default().fg(Color::White)),
+        ]));
+    }
+
     // Duration
     if let Some(length) = player.length {
         let duration = format_duration(length.as_millis() as i64);
@@ -708,6 +1822,50 @@ fn format_playerctl_metadata(player: &PlayerMetadata) -> Vec<Line<'static>> {
         ]));
     }
 
+    // What the provider found for the track, without an album to show
+    if let Some(track) = track {
+        let mut rows = track_rows(track, None);
+        // The player's own track number is shown above
+        if player.track_number.is_some() {
+            rows.retain(|(label, _)| label != "Track");
+        }
+        rows.extend(credit_rows(track));
+        for (label, value) in rows {
+            lines.push(Line::from(vec![
+                Span::raw(padding),
+                Span::styled(format!("{label}: "), Style::default().fg(Color::DarkGray)),
+                Span::styled(value, Style::default().fg(Color::White)),
+            ]));
+        }
+    }
+
+    // Genre
+    if let Some(ref genre) = player.genre {
+        lines.push(Line::from(vec![
+            Span::raw(padding),
+            Span::styled("Genre: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(genre.clone(), Style::default().fg(Color::White)),
+        ]));
+    }
+
+    // Composer
+    if let Some(ref composer) = player.composer {
+        lines.push(Line::from(vec![
+            Span::raw(padding),
+            Span::styled("Composer: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(composer.clone(), Style::default().fg(Color::White)),
+        ]));
+    }
+
+    // Rating
+    if let Some(rating) = player.user_rating {
+        lines.push(Line::from(vec![
+            Span::raw(padding),
+            Span::styled("Rating: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(format_rating(rating), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+
     lines.push(Line::from(""));
 
     // Show source if available
@@ -728,7 +1886,12 @@ fn format_playerctl_metadata(player: &PlayerMetadata) -> Vec<Line<'static>> {
 pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, crossterm::cursor::Hide)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        crossterm::cursor::Hide
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
@@ -740,8 +1903,542 @@ pub fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
+        DisableMouseCapture,
         crossterm::cursor::Show
     )?;
     terminal.show_cursor()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::mock::MockBackend;
+    use crate::player::watcher::TrackWatcher;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn shifted(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::SHIFT)
+    }
+
+    #[test]
+    fn test_command_for_key() {
+        assert_eq!(
+            command_for_key(key(KeyCode::Tab)),
+            Some(PlayerCommand::CyclePlayer)
+        );
+        assert_eq!(
+            command_for_key(key(KeyCode::Char(' '))),
+            Some(PlayerCommand::Control(PlaybackControl::PlayPause))
+        );
+        assert_eq!(
+            command_for_key(shifted(KeyCode::Right)),
+            Some(PlayerCommand::Control(PlaybackControl::Seek(30)))
+        );
+        assert_eq!(command_for_key(key(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn test_keys_control_mock_backend() {
+        let backend = MockBackend::with_track(PlayerMetadata {
+            artist: "Queen".to_string(),
+            title: "Bohemian Rhapsody".to_string(),
+            ..Default::default()
+        });
+        let mut watcher = TrackWatcher::new(Box::new(backend.clone()));
+
+        let keys = [
+            key(KeyCode::Char(' ')),
+            key(KeyCode::Char('n')),
+            key(KeyCode::Char('p')),
+            key(KeyCode::Right),
+            key(KeyCode::Left),
+            shifted(KeyCode::Right),
+            shifted(KeyCode::Left),
+            key(KeyCode::Char('+')),
+            key(KeyCode::Char('=')),
+            key(KeyCode::Char('-')),
+            key(KeyCode::Char('s')),
+            key(KeyCode::Char('r')),
+        ];
+        for key in keys {
+            match command_for_key(key) {
+                Some(PlayerCommand::Control(control)) => watcher.control(control).unwrap(),
+                other => panic!("{key:?} mapped to {other:?}"),
+            }
+        }
+
+        assert_eq!(
+            backend.controls(),
+            vec![
+                PlaybackControl::PlayPause,
+                PlaybackControl::Next,
+                PlaybackControl::Previous,
+                PlaybackControl::Seek(5),
+                PlaybackControl::Seek(-5),
+                PlaybackControl::Seek(30),
+                PlaybackControl::Seek(-30),
+                PlaybackControl::Volume(5),
+                PlaybackControl::Volume(5),
+                PlaybackControl::Volume(-5),
+                PlaybackControl::ToggleShuffle,
+                PlaybackControl::ToggleLoop,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_rating() {
+        assert_eq!(format_rating(0.0), "☆☆☆☆☆");
+        assert_eq!(format_rating(0.6), "★★★☆☆");
+        assert_eq!(format_rating(1.0), "★★★★★");
+        assert_eq!(format_rating(7.0), "★★★★★");
+    }
+
+    #[test]
+    fn test_feedback_expires() {
+        let mut app = App::new();
+        assert_eq!(app.current_feedback(), None);
+
+        app.show_feedback("Next track");
+        assert_eq!(app.current_feedback(), Some("Next track"));
+
+        app.feedback = Some(("Old".to_string(), Instant::now() - FEEDBACK_DURATION));
+        assert_eq!(app.current_feedback(), None);
+    }
+
+    #[test]
+    fn test_karaoke_spans() {
+        let line = parser::parse_lrc("[00:12.00]<00:12.00>Hello <00:12.40>big <00:12.80>world")
+            .lines
+            .remove(0);
+        let palette = ColorPalette {
+            progress_colors: vec![(255, 0, 0), (0, 255, 0), (0, 0, 255)],
+            info_colors: vec![],
+        };
+        let sung_style = Style::default().fg(Color::Yellow);
+        let colors = |position_ms: u64, palette: Option<&ColorPalette>| -> Vec<Option<Color>> {
+            karaoke_spans(
+                &line,
+                Duration::from_millis(position_ms),
+                sung_style,
+                palette,
+            )
+            .iter()
+            .map(|span| span.style.fg)
+            .collect()
+        };
+
+        assert_eq!(
+            colors(12_500, Some(&palette)),
+            vec![
+                Some(Color::Rgb(255, 0, 0)),
+                Some(Color::Rgb(0, 255, 0)),
+                Some(Color::White)
+            ]
+        );
+        assert_eq!(
+            colors(13_000, None),
+            vec![
+                Some(Color::Yellow),
+                Some(Color::Yellow),
+                Some(Color::Yellow)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lyrics_header() {
+        let header = |source: Option<&str>, offset_ms: i64| -> String {
+            let lyrics = ParsedLyrics {
+                source: source.map(str::to_string),
+                is_synced: true,
+                ..Default::default()
+            };
+            lyrics_header(&lyrics, offset_ms)
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+
+        assert_eq!(header(None, 0), "");
+        assert_eq!(header(Some("local"), 0), "  Local file");
+        assert_eq!(header(Some("embedded"), 0), "  Embedded tags");
+        assert_eq!(header(Some("lrclib"), 0), "  LRCLIB");
+        assert_eq!(header(Some("lrclib"), -300), "  LRCLIB  offset -300ms");
+        assert_eq!(header(None, 1500), "  offset +1.5s");
+    }
+
+    #[test]
+    fn test_lyrics_offset() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(lyrics_offset_for_key(key('.')), Some(100));
+        assert_eq!(lyrics_offset_for_key(key(',')), Some(-100));
+        assert_eq!(lyrics_offset_for_key(key('>')), Some(500));
+        assert_eq!(lyrics_offset_for_key(key('<')), Some(-500));
+        assert_eq!(lyrics_offset_for_key(key('n')), None);
+
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-ui-offset-{}", std::process::id()));
+        let mut app = App::new();
+        app.lyrics_cache = Some(LyricsCache::with_dir(cache_dir.clone()).unwrap());
+        app.clock.sync(Some(Duration::from_secs(10)), false, None);
+
+        app.set_lyrics_track("Daft Punk", "One More Time");
+        app.nudge_lyrics_offset(500);
+        app.nudge_lyrics_offset(-100);
+        assert_eq!(app.lyrics_position(), Some(Duration::from_millis(10_400)));
+        assert_eq!(app.current_feedback(), Some("Lyrics offset +400ms"));
+
+        // Another track starts from its own offset, coming back restores this one
+        app.set_lyrics_track("Daft Punk", "Aerodynamic");
+        assert_eq!(app.lyrics_offset_ms, 0);
+        app.set_lyrics_offset(-20_000);
+        assert_eq!(app.lyrics_position(), Some(Duration::ZERO));
+        app.set_lyrics_track("Daft Punk", "One More Time");
+        assert_eq!(app.lyrics_offset_ms, 400);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_request_publish() {
+        let mut app = App::new();
+        app.player_metadata = PlayerMetadata {
+            artist: "Daft Punk".to_string(),
+            title: "Digital Love".to_string(),
+            album: Some("Discovery".to_string()),
+            length: Some(Duration::from_secs(301)),
+            ..Default::default()
+        };
+        app.lyrics = Some(ParsedLyrics {
+            source: Some("lrclib".to_string()),
+            ..parser::parse_lrc("[00:21.00]Last night I had a dream about you")
+        });
+
+        // Lyrics from LRCLIB are only sent back once they have been corrected
+        app.request_publish();
+        assert!(app.publish_prompt.is_none());
+        app.lyrics_offset_ms = -250;
+        app.request_publish();
+
+        let request = app.publish_prompt.as_ref().unwrap();
+        assert_eq!(
+            request.synced_lyrics,
+            "[00:21.25]Last night I had a dream about you"
+        );
+        let text: Vec<String> = publish_prompt_lines(request)
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(text[2], "Daft Punk - Digital Love");
+        assert_eq!(text[3], "Discovery · 05:01 · 1 lines");
+    }
+
+    #[test]
+    fn test_secondary_lines() {
+        let lyrics = parser::parse_lrc("[00:01.00]사랑해\n[00:01.00]I love you\n[00:03.00]Oh");
+        assert_eq!(secondary_lines(&lyrics.lines[0], false), vec!["I love you"]);
+        assert_eq!(
+            secondary_lines(&lyrics.lines[0], true),
+            vec!["saranghae", "I love you"]
+        );
+        assert!(secondary_lines(&lyrics.lines[1], true).is_empty());
+    }
+
+    #[test]
+    fn test_record_sync() {
+        let lyrics_dir =
+            std::env::temp_dir().join(format!("trackwatch-ui-record-{}", std::process::id()));
+        let mut app = App::new();
+        app.local_lyrics = LocalLyrics::new(vec![lyrics_dir.clone()]);
+        app.player_metadata = PlayerMetadata {
+            artist: "Daft Punk".to_string(),
+            title: "Something About Us".to_string(),
+            ..Default::default()
+        };
+
+        app.start_recording();
+        assert!(app.recorder.is_none());
+
+        app.lyrics = Some(parser::parse_lrc("It might not be\nThe right time"));
+        app.start_recording();
+        for seconds in [3, 5, 8] {
+            app.clock
+                .sync(Some(Duration::from_secs(seconds)), false, None);
+            app.stamp_line();
+        }
+        app.undo_stamp();
+        assert_eq!(app.recorder.as_ref().unwrap().next_line(), Some(1));
+
+        app.clock.sync(Some(Duration::from_secs(6)), false, None);
+        app.stamp_line();
+        app.finish_recording();
+        assert!(app.recorder.is_none());
+
+        let lyrics = app.lyrics.as_ref().unwrap();
+        assert!(lyrics.is_synced);
+        assert_eq!(lyrics.source.as_deref(), Some("local"));
+        assert_eq!(lyrics.lines[1].timestamp_ms, Some(6_000));
+        let saved = lyrics_dir.join("Daft Punk - Something About Us.lrc");
+        assert!(std::fs::read_to_string(saved)
+            .unwrap()
+            .contains("[00:03.00]It might not be"));
+
+        std::fs::remove_dir_all(lyrics_dir).unwrap();
+    }
+
+    #[test]
+    fn test_lyrics_viewer() {
+        let text: String = (0..40)
+            .map(|n| format!("[00:{n:02}.00]Line {n}\n[00:{n:02}.00]Zeile {n}\n"))
+            .collect();
+        let mut app = App::new();
+        app.waiting_for_player = false;
+        app.lyrics = Some(parser::parse_lrc(&text));
+        app.clock.sync(Some(Duration::from_secs(2)), false, None);
+        app.lyrics_offset_ms = 500;
+
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        let view = &app.lyrics_view;
+        assert!(view.is_following());
+        // Header, then each line followed by its translation
+        assert_eq!(view.line_rows[..3], [1, 3, 5]);
+
+        // Clicking the line undoes the offset, so it starts as it is shown
+        let area = view.area;
+        let row = area.y + (view.line_rows[3] - view.top) as u16;
+        app.lyrics_view.scroll_by(0);
+        assert!(!app.lyrics_view.is_following());
+        assert_eq!(
+            app.seek_to_lyric_at(area.x + 4, row + 1),
+            Some(PlayerCommand::Control(PlaybackControl::SeekTo(
+                Duration::from_millis(2_500)
+            )))
+        );
+        assert!(app.lyrics_view.is_following());
+        assert_eq!(app.seek_to_lyric_at(area.x + 4, area.y), None);
+
+        // Typing a query scrolls to its first match
+        app.lyrics_view.start_search();
+        for c in "zeile 30".chars() {
+            app.search_key(key(KeyCode::Char(c)));
+        }
+        app.search_key(key(KeyCode::Enter));
+        assert!(!app.lyrics_view.is_searching());
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        let view = &app.lyrics_view;
+        let shown = view.top..view.top + view.visible_height();
+        assert!(shown.contains(&view.line_rows[30]));
+    }
+
+    #[test]
+    fn test_update_lyrics_drops_stale_fetches() {
+        let mut app = App::new();
+        app.set_lyrics_track("Daft Punk", "One More Time");
+        let previous = app.lyrics_request;
+        app.set_lyrics_loading(true);
+        app.set_lyrics_track("Daft Punk", "Aerodynamic");
+        let current = app.lyrics_request;
+
+        // The previous track's lyrics land after the track changed
+        app.update_lyrics(previous, Some(parser::parse_lrc("One more time")));
+        assert!(app.lyrics.is_none());
+        assert!(app.lyrics_loading);
+
+        app.update_lyrics(current, Some(parser::parse_lrc("Aerodynamic")));
+        assert_eq!(app.lyrics.as_ref().unwrap().lines[0].text, "Aerodynamic");
+        assert!(!app.lyrics_loading);
+    }
+
+    #[test]
+    fn test_pick_candidate() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("trackwatch-ui-picker-{}", std::process::id()));
+        let cache = LyricsCache::with_dir(cache_dir.clone()).unwrap();
+        let mut app = App::new();
+        app.lyrics_cache = Some(cache.clone());
+
+        assert_eq!(app.open_picker(), None);
+        app.player_metadata = PlayerMetadata {
+            artist: "Daft Punk".to_string(),
+            title: "Digital Love (Remastered)".to_string(),
+            ..Default::default()
+        };
+        app.set_lyrics_track("Daft Punk", "Digital Love");
+        let fetching = app.lyrics_request;
+        let search = app.open_picker().unwrap();
+
+        // The search field takes keys after /, Enter searches for them
+        app.picker_key(key(KeyCode::Char('/')));
+        app.picker_key(key(KeyCode::Backspace));
+        for c in "e live".chars() {
+            app.picker_key(key(KeyCode::Char(c)));
+        }
+        let keywords = app.picker_key(key(KeyCode::Enter)).unwrap();
+        assert_eq!(
+            keywords,
+            CandidateSearch::Keywords("Daft Punk Digital Love live".to_string())
+        );
+
+        let candidates = vec![
+            crate::lyrics::source::tests::response(None, Some("Wrong song")),
+            crate::lyrics::source::tests::response(Some("[00:01.00]Last night"), None),
+        ];
+        let picker = app.picker.as_mut().unwrap();
+        picker.set_results(&search, Ok(Vec::new()));
+        picker.set_results(&keywords, Ok(candidates));
+        app.picker_key(key(KeyCode::Char('j')));
+        app.picker_key(key(KeyCode::Enter));
+
+        assert!(app.picker.is_none());
+        let lyrics = app.lyrics.as_ref().unwrap();
+        assert_eq!(lyrics.source.as_deref(), Some(PINNED_SOURCE));
+        assert_eq!(lyrics.lines[0].text, "Last night");
+        // The lyrics chain answering late doesn't replace the pick
+        app.update_lyrics(fetching, None);
+        assert_eq!(app.lyrics.as_ref().unwrap().lines[0].text, "Last night");
+        // Pinned under the same key the lyrics chain looks up
+        let pinned = cache.get_pinned("Daft Punk", "Digital Love").unwrap();
+        assert!(pinned.has_synced_lyrics());
+
+        app.open_picker();
+        app.picker_key(key(KeyCode::Char('x')));
+        assert!(cache.get_pinned("Daft Punk", "Digital Love").is_none());
+        app.picker_key(key(KeyCode::Esc));
+        assert!(app.picker.is_none());
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_gap_indicator() {
+        let gap = LyricsGap {
+            start_ms: 0,
+            end_ms: 12_000,
+            next_line: 0,
+        };
+        let text = |spans: &[Span]| {
+            spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        };
+        let dimmed = |spans: &[Span]| {
+            spans
+                .iter()
+                .filter(|span| {
+                    span.content.starts_with('♪') && span.style.fg == Some(Color::DarkGray)
+                })
+                .count()
+        };
+
+        let start = gap_spans(&gap, Duration::from_millis(500), None);
+        assert_eq!(text(&start), "  ♪ ♪ ♪   12s");
+        assert_eq!(dimmed(&start), 0);
+        // A note goes out every third of the gap
+        let end = gap_spans(&gap, Duration::from_millis(10_100), None);
+        assert_eq!(text(&end), "  ♪ ♪ ♪   2s");
+        assert_eq!(dimmed(&end), 2);
+
+        // The intro gets its own row, a break replaces its blank line
+        let mut app = App::new();
+        app.waiting_for_player = false;
+        app.lyrics = Some(parser::parse_lrc(
+            "[00:12.00]Last night\n[00:15.00]\n[00:30.00]I had a dream",
+        ));
+        app.clock.sync(Some(Duration::from_secs(3)), false, None);
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.lyrics_view.line_rows, vec![2, 3, 4]);
+        app.clock.sync(Some(Duration::from_secs(20)), false, None);
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.lyrics_view.line_rows, vec![1, 2, 3]);
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("♪ ♪ ♪   10s"));
+
+        app.lyrics = Some(ParsedLyrics {
+            instrumental: true,
+            source: Some("lrclib".to_string()),
+            ..Default::default()
+        });
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Instrumental"));
+        assert!(!screen.contains("No lyrics available"));
+    }
+
+    #[test]
+    fn test_track_rows() {
+        let mut track = TrackMetadata {
+            id: "2".to_string(),
+            title: "One More Time".to_string(),
+            artists: Vec::new(),
+            album: None,
+            track_number: Some(1),
+            volume_number: Some(1),
+            duration: Some(320),
+            isrc: Some("GBDUW0000052".to_string()),
+            explicit: false,
+            popularity: None,
+            credits: vec![
+                crate::models::Credit {
+                    role: "Mastering Engineer".to_string(),
+                    names: vec!["Nilesh Patel".to_string()],
+                },
+                crate::models::Credit {
+                    role: "Producer".to_string(),
+                    names: vec![
+                        "Thomas Bangalter".to_string(),
+                        "Guy-Manuel de Homem-Christo".to_string(),
+                    ],
+                },
+            ],
+        };
+        let row = |label: &str, value: &str| (label.to_string(), value.to_string());
+
+        assert_eq!(
+            track_rows(&track, Some(14)),
+            vec![row("Track", "1 of 14"), row("ISRC", "GBDUW0000052")]
+        );
+        track.volume_number = Some(2);
+        track.explicit = true;
+        assert_eq!(
+            track_rows(&track, None),
+            vec![
+                row("Track", "1 (disc 2)"),
+                row("ISRC", "GBDUW0000052"),
+                row("Explicit", "Yes")
+            ]
+        );
+        assert_eq!(
+            credit_rows(&track),
+            vec![row(
+                "Producer",
+                "Thomas Bangalter, Guy-Manuel de Homem-Christo"
+            )]
+        );
+    }
+}